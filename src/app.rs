@@ -1,9 +1,14 @@
-use crate::net::SearchResult;
+use crate::config::{EqPreset, QueueEndAction, TitleMatch};
+use crate::logfile::FileLogger;
+use crate::lyrics::{LyricLine, LyricsState};
+use crate::net::{Chapter, LoadStage, SearchResult};
+use crate::stats::ListeningStats;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
 
 #[derive(Clone)]
 pub enum PlayerStatus {
@@ -12,7 +17,41 @@ pub enum PlayerStatus {
     SearchResults,
     Playing,
     Paused,
-    Error(String),
+    /// 搜索失败：停留在当前界面，等用户重试
+    SearchError(String),
+    /// 播放失败：由 `check_and_play_next` 自动跳到下一首
+    PlaybackError(String),
+    /// 歌曲不可用（下架、地区限制、私有）：不再重试，同样由 `check_and_play_next` 跳到下一首
+    Unavailable(String),
+}
+
+impl PlayerStatus {
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Self::SearchError(_) | Self::PlaybackError(_) | Self::Unavailable(_)
+        )
+    }
+
+    pub fn error_message(&self) -> Option<&str> {
+        match self {
+            Self::SearchError(e) | Self::PlaybackError(e) | Self::Unavailable(e) => {
+                Some(e.as_str())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 驱动自动播放的列表。
+///
+/// 优先级：从搜索结果中播放后，搜索结果就是播放列表，直到从收藏或播放历史重新开始播放；
+/// 其余情况下为当前收藏分组。各播放模式（单曲、列表循环、顺序、随机）对两种列表的规则相同，
+/// 只有收藏分组会在开启 `follow_favorite_sort` 时按显示排序播放。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaybackContext {
+    Favorites,
+    SearchResults,
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -23,12 +62,65 @@ pub enum PlayMode {
     Shuffle,    // 随机播放
 }
 
+/// 收藏列表的显示排序，只影响显示（以及可选的顺序播放），不改变保存的顺序
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FavoriteSort {
+    /// 收藏时的顺序（即保存的顺序）
+    #[default]
+    Insertion,
+    TitleAsc,
+    TitleDesc,
+    /// 按搜索源分组，同一来源内按标题排序
+    Source,
+    /// 播放次数从多到少，次数相同时按标题排序
+    PlayCount,
+}
+
+impl FavoriteSort {
+    pub fn next(self) -> Self {
+        match self {
+            FavoriteSort::Insertion => FavoriteSort::TitleAsc,
+            FavoriteSort::TitleAsc => FavoriteSort::TitleDesc,
+            FavoriteSort::TitleDesc => FavoriteSort::Source,
+            FavoriteSort::Source => FavoriteSort::PlayCount,
+            FavoriteSort::PlayCount => FavoriteSort::Insertion,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FavoriteSort::Insertion => "收藏顺序",
+            FavoriteSort::TitleAsc => "标题 A→Z",
+            FavoriteSort::TitleDesc => "标题 Z→A",
+            FavoriteSort::Source => "来源",
+            FavoriteSort::PlayCount => "播放次数",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct FavoriteItem {
+    /// 搜索 / 解析时使用的关键词，也用于判断是否正在播放
     pub title: String,
     pub source: String,
     #[serde(default)]
     pub local_path: Option<String>,
+    /// 列表中显示的自定义名称（按 e 设置），为空时显示 `title`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// 播放次数：这首歌每次开始播放时累加（无论从收藏、搜索结果还是历史中播放）
+    #[serde(default)]
+    pub play_count: u32,
+    /// 播放时跳过的开头秒数（按 S 标记），用于跳过较长的前奏
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_offset_secs: Option<u64>,
+}
+
+impl FavoriteItem {
+    /// 列表中显示的名称
+    pub fn label(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.title)
+    }
 }
 
 /// 收藏分组：一个命名的歌曲集合
@@ -55,6 +147,33 @@ struct FavoritesData {
     groups: Vec<FavoriteGroup>,
 }
 
+/// 上次退出时的播放会话，保存在配置目录下的 `session.json`（见 [`crate::config::config_dir`]）
+#[derive(Serialize, Deserialize)]
+struct SessionData {
+    current_song: String,
+    current_source: String,
+    #[serde(default)]
+    local_path: Option<String>,
+    volume: u8,
+    play_mode: String,
+}
+
+/// 播放历史最多保留的条数
+const PLAY_HISTORY_MAX: usize = 100;
+/// 上一首 / 下一首播放栈各自最多保留的歌曲数
+const TRACK_STACK_MAX: usize = 100;
+/// 没有按键多久之后显示空闲屏保
+const SCREENSAVER_IDLE: Duration = Duration::from_secs(60);
+
+/// 最后一次按键后多久开始显示空闲提示
+const TIP_IDLE: Duration = Duration::from_secs(5);
+
+/// 每条空闲提示的显示时长（秒）
+const TIP_INTERVAL_SECS: u64 = 8;
+
+/// 搜索输入框下最多显示的历史建议条数
+const SEARCH_SUGGESTION_MAX: usize = 5;
+
 /// 旧格式（单列表），用于向后兼容迁移
 #[derive(Deserialize)]
 struct LegacyFavoritesData {
@@ -68,9 +187,51 @@ pub struct App {
     pub status: PlayerStatus,
     pub current_song: String,
     pub current_local_path: Option<String>,
+    /// 当前歌曲的分享链接（观看页面地址，拿不到时为网络直链），按 y 复制
+    pub current_track_url: Option<String>,
     pub progress: f64,
+    /// 当前播放位置（秒），用于同步歌词
+    pub time_pos: f64,
+    /// 当前曲目总时长（秒），未知时为 0
+    pub duration: f64,
+    /// mpv 正在等待缓冲时的缓冲填充百分比；None 表示缓冲充足
+    pub buffering: Option<u8>,
+    /// 正在加载的歌曲所处的阶段；开始播放（播放位置推进）后为 None
+    pub load_stage: Option<LoadStage>,
+    /// 当前音频编码名和码率（bit/s），mpv 尚未上报时为 None
+    pub audio_codec: Option<String>,
+    pub audio_bitrate: Option<u32>,
+    /// 当前曲目的章节列表与所在章节，来自 mpv 的 `chapter-list` / `chapter`
+    pub chapters: Vec<Chapter>,
+    pub chapter: Option<usize>,
+    /// 当前瞬时响度（LUFS），用于电平表；mpv 没有提供时为 None，界面改为随播放跳动
+    pub loudness: Option<f64>,
+    /// 是否在进度条旁显示电平表（来自 `ui.level_meter` 配置）
+    pub level_meter: bool,
+    /// 是否在“正在播放”视图中显示封面（`video_mode = "art"` 且终端支持内联图片）
+    pub cover_enabled: bool,
+    /// 正在试听的搜索结果标题，试听结束或开始正常播放后清空
+    pub preview: Option<String>,
+    /// 当前歌曲的缩略图地址，解析成功后由播放器写入
+    pub current_thumbnail: Option<String>,
+    /// 已下载的封面图片数据，对应 `cover_title`
+    pub cover_art: Option<Vec<u8>>,
+    /// `cover_art` 对应的歌名
+    cover_title: String,
+    /// `cover_title` 的封面是否已发起下载
+    cover_requested: bool,
+    /// 上一次渲染中封面所在的区域，由主循环在绘制后写入图片；不显示封面时为 None
+    pub cover_area: Option<ratatui::layout::Rect>,
     pub volume: u8,
+    /// 是否静音，以 mpv 的 `mute` 属性为准
+    pub muted: bool,
+    /// 静音前的音量，取消静音时恢复
+    pub pre_mute_volume: u8,
+    /// 当前均衡器预设（按 E 切换）
+    pub eq_preset: EqPreset,
     pub logs: VecDeque<String>,
+    /// 启用 `logging.file` 时，日志同时追加写入文件
+    pub file_logger: Option<FileLogger>,
     pub input_mode: bool,
     pub input_buffer: String,
     /// 搜索历史，最新的在前（index 0 = 最近一条）
@@ -83,8 +244,16 @@ pub struct App {
     pub groups: Vec<FavoriteGroup>,
     /// 当前激活的分组索引
     pub selected_group: usize,
-    /// 当前激活分组内选中的歌曲索引
+    /// 当前激活分组内选中的歌曲索引（始终是完整列表中的索引，不受过滤影响）
     pub selected_favorite: usize,
+    /// 收藏列表过滤关键字（不区分大小写的子串匹配），为空表示不过滤
+    pub favorite_filter: String,
+    /// 是否正在输入过滤关键字
+    pub filter_input_mode: bool,
+    /// 收藏列表的显示排序
+    pub favorite_sort: FavoriteSort,
+    /// 顺序播放 / 列表循环 / 手动切歌是否按当前显示排序（来自 `playback.follow_favorite_sort`）
+    pub follow_favorite_sort: bool,
     pub play_mode: PlayMode,
     pub search_results: Vec<SearchResult>,
     pub selected_search_result: usize,
@@ -95,6 +264,8 @@ pub struct App {
     pub total_pages: usize,
     pub search_cache: HashMap<usize, Vec<SearchResult>>,
     pub is_loading_page: bool,
+    /// 正在后台预取的搜索结果页
+    prefetching_page: Option<usize>,
     /// 是否处于新建分组的输入模式
     pub group_input_mode: bool,
     /// 是否处于移动歌曲的分组选择模式
@@ -103,10 +274,93 @@ pub struct App {
     pub move_target_group: usize,
     /// 是否处于删除分组的二次确认模式
     pub delete_confirm_mode: bool,
+    /// 是否处于清空全部收藏的二次确认模式
+    pub clear_favorites_confirm_mode: bool,
     /// 是否处于修改分组名称的输入模式
     pub rename_mode: bool,
+    /// 为选中的收藏设置显示名称
+    pub display_name_mode: bool,
+    /// 编辑搜索结果的排除关键词
+    pub exclude_input_mode: bool,
+    /// 当前生效的搜索排除关键词（来自 `search.exclude_patterns`，可按 B 在运行时修改）
+    pub exclude_patterns: Vec<String>,
+    /// 是否处于输入跳转时间点的模式
+    pub seek_input_mode: bool,
+    /// 是否处于输入精确音量的模式
+    pub volume_input_mode: bool,
+    /// 是否处于输入搜索结果页码的模式
+    pub page_input_mode: bool,
+    /// 是否处于输入快照分组名的模式（播放历史视图中按 F）
+    pub snapshot_input_mode: bool,
     pub help_mode: bool,
-    pub playing_from_search: bool,
+    /// 帮助浮层的滚动位置（行），渲染时按内容高度截断
+    pub help_scroll: u16,
+    /// 是否显示收听统计浮层
+    pub stats_mode: bool,
+    /// 累计收听统计，持久化到配置目录下的 `stats.json`
+    pub stats: ListeningStats,
+    /// 上次累加收听时长的时刻；不在播放时为 None
+    stats_tick: Option<Instant>,
+    /// 最近一次 yt-dlp 报错的完整 stderr，状态为 Error 时可展开查看
+    pub last_error_detail: Option<String>,
+    /// 是否展开错误详情面板
+    pub error_detail_mode: bool,
+    /// 是否显示全屏“正在播放”视图
+    pub now_playing_mode: bool,
+    /// 快进/快退步长（秒）与音量步长（%），来自 `playback` 配置，用于帮助栏显示
+    pub seek_seconds: i32,
+    pub volume_step: i32,
+    /// 音量上限，来自 `playback.max_volume`
+    pub max_volume: u8,
+    /// 播放中退出前是否需要确认（来自 `ui.confirm_quit` 配置）
+    pub confirm_quit: bool,
+    /// 标题行是否显示时钟（来自 `ui.show_clock` 配置）
+    pub show_clock: bool,
+    /// 是否显示日志面板（来自 `ui.show_logs` 配置，按 L 切换）
+    pub show_logs: bool,
+    /// 当前歌曲与收藏标题的比较方式（来自 `ui.title_match` 配置）
+    pub title_match: TitleMatch,
+    /// 是否启用空闲屏保（来自 `ui.screensaver` 配置）
+    pub screensaver: bool,
+    /// 最近一次按键的时间，用于判断是否空闲
+    pub last_input: Instant,
+    /// 空闲时轮播的提示（来自 `ui.show_tips` / `ui.tips`），为空表示不显示
+    pub tips: Vec<String>,
+    /// 是否正在显示退出确认框
+    pub quit_confirm_mode: bool,
+    /// 当前驱动自动播放的列表
+    pub playback_context: PlaybackContext,
+    /// 最近播放过的歌曲，最新的在前，持久化到配置目录下的 `history.json`
+    pub play_history: VecDeque<FavoriteItem>,
+    /// 列表区域是否显示播放历史（而不是收藏分组）
+    pub history_mode: bool,
+    pub selected_history: usize,
+    /// 随机模式下提前选好的下一首（用于预取），get_next_song 会优先使用
+    planned_shuffle: Option<String>,
+    /// 随机模式要避开的最近播放歌曲数（来自 `playback.shuffle_avoid_recent` 配置）
+    pub shuffle_avoid_recent: usize,
+    /// 最近开始播放的歌曲标题，最新的在末尾，最多保留 `shuffle_avoid_recent` 首
+    recent_plays: VecDeque<String>,
+    /// 按 p 回退时返回的已播放歌曲，最近的在末尾
+    back_stack: Vec<FavoriteItem>,
+    /// 回退时离开的歌曲，下一首优先从这里返回；正常播放新歌时清空
+    forward_stack: Vec<FavoriteItem>,
+    /// 最近一首成功开始播放的歌曲
+    now_track: Option<FavoriteItem>,
+    /// 正在通过播放栈切换到的歌曲，它开始播放时不再改动播放栈
+    stack_nav_target: Option<String>,
+    /// mpv 连续报告停止（且进度未到结尾）的轮询次数，用于确认是否真的播放结束
+    pub stopped_polls: u32,
+    /// 动画帧计数，每个刷新周期加一（回绕），驱动加载 spinner
+    pub animation_tick: u64,
+    /// 随机播放用的伪随机数状态，启动时播种一次
+    rng_state: Cell<u64>,
+    /// 当前歌曲的歌词
+    pub lyrics: LyricsState,
+    /// `lyrics` 对应的歌名
+    lyrics_title: String,
+    /// 已查询过的歌词，按歌名缓存；空列表表示没有找到
+    lyrics_cache: HashMap<String, Vec<LyricLine>>,
     request_seq: u64,
     active_request_id: u64,
     favorites_path: PathBuf,
@@ -115,16 +369,81 @@ pub struct App {
 impl App {
     // ── 路径工具 ───────────────────────────────────────────────────────────────
 
+    /// 配置中的收藏文件路径转为实际路径。取默认值（或留空）时按 XDG 规则选择位置，见
+    /// [`crate::config::default_favorites_path`]；相对路径（如 `./favorites.json`）以配置文件
+    /// 所在目录为基准，而不是当前工作目录（从桌面启动时工作目录不确定）
     fn resolve_favorites_path(configured_path: &str) -> PathBuf {
-        if let Some(rest) = configured_path.strip_prefix('~') {
-            let rest = rest.strip_prefix(['/', '\\']).unwrap_or(rest);
-            crate::config::home_dir().join(rest)
-        } else {
+        let configured_path = configured_path.trim();
+        if configured_path.is_empty() || configured_path == crate::config::DEFAULT_FAVORITES_FILE {
+            crate::config::default_favorites_path()
+        } else if configured_path.starts_with('~') {
+            crate::config::expand_home(configured_path)
+        } else if Path::new(configured_path).is_absolute() {
             PathBuf::from(configured_path)
+        } else {
+            crate::config::Config::config_file_dir().join(configured_path)
+        }
+    }
+
+    /// 默认收藏位置移到 XDG 数据目录后，把旧的 `~/.maboroshi_favorites.json` 迁移过去。
+    /// 新位置已有文件时不做任何事；返回需要记录的日志
+    fn migrate_legacy_favorites(path: &Path) -> Option<String> {
+        let legacy = crate::config::legacy_favorites_path();
+        if path == legacy || path.exists() || !legacy.is_file() {
+            return None;
+        }
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                // 跨文件系统时 rename 会失败，退回复制后删除
+                fs::rename(&legacy, path).or_else(|_| {
+                    fs::copy(&legacy, path)?;
+                    fs::remove_file(&legacy)
+                })
+            });
+        Some(match result {
+            Ok(()) => format!("已将收藏从 {} 迁移到 {}", legacy.display(), path.display()),
+            Err(e) => format!(
+                "⚠ 收藏迁移失败 ({} → {}): {}",
+                legacy.display(),
+                path.display(),
+                e
+            ),
+        })
+    }
+
+    /// 检查收藏文件所在目录是否可写（目录尚不存在时检查最近的已存在上级目录），不可写时返回警告
+    fn favorites_dir_warning(path: &Path) -> Option<String> {
+        let parent = path.parent().unwrap_or(Path::new("."));
+        let dir = parent
+            .ancestors()
+            .find(|p| p.is_dir())
+            .unwrap_or(Path::new("."));
+        let probe = dir.join(format!(".maboroshi-write-test-{}", std::process::id()));
+        match fs::File::create(&probe) {
+            Ok(_) => {
+                let _ = fs::remove_file(&probe);
+                None
+            }
+            Err(e) => Some(format!(
+                "⚠ 收藏目录不可写 ({}): {}，对收藏的修改将无法保存",
+                dir.display(),
+                e
+            )),
         }
     }
 
-    fn backup_corrupted_favorites(path: &Path) -> Result<PathBuf, String> {
+    fn session_path() -> PathBuf {
+        crate::config::config_dir().join("session.json")
+    }
+
+    fn play_history_path() -> PathBuf {
+        crate::config::config_dir().join("history.json")
+    }
+
+    /// 生成 `<name>.<tag>.<时间戳>` 形式的备份路径（与原文件同目录）
+    fn timestamped_backup_path(path: &Path, tag: &str) -> PathBuf {
         let ts = SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
@@ -132,12 +451,19 @@ impl App {
         let file_name = path
             .file_name()
             .and_then(|n| n.to_str())
-            .unwrap_or("favorites.json");
+            .unwrap_or("data.json");
         let mut backup_path = path.to_path_buf();
-        backup_path.set_file_name(format!("{}.corrupt.{}", file_name, ts));
+        backup_path.set_file_name(format!("{}.{}.{}", file_name, tag, ts));
+        backup_path
+    }
+
+    /// 将无法解析的文件重命名为 `<name>.corrupt.<时间戳>`，`label` 用于错误信息（如“收藏文件”）
+    pub(crate) fn backup_corrupted_file(path: &Path, label: &str) -> Result<PathBuf, String> {
+        let backup_path = Self::timestamped_backup_path(path, "corrupt");
         fs::rename(path, &backup_path).map_err(|e| {
             format!(
-                "收藏文件解析失败，且备份失败 ({} -> {}): {}",
+                "{}解析失败，且备份失败 ({} -> {}): {}",
+                label,
                 path.display(),
                 backup_path.display(),
                 e
@@ -183,7 +509,7 @@ impl App {
         }
 
         // 两种格式都解析失败，备份并返回空
-        match Self::backup_corrupted_favorites(path) {
+        match Self::backup_corrupted_file(path, "收藏文件") {
             Ok(backup_path) => (
                 vec![FavoriteGroup::new("默认")],
                 Some(format!(
@@ -212,12 +538,57 @@ impl App {
         fs::write(path, json).map_err(|e| format!("保存收藏失败 ({}): {}", path.display(), e))
     }
 
+    fn load_play_history() -> (VecDeque<FavoriteItem>, Option<String>) {
+        let path = Self::play_history_path();
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return (VecDeque::new(), None);
+            }
+            Err(e) => {
+                return (
+                    VecDeque::new(),
+                    Some(format!("读取播放历史失败 ({}): {}", path.display(), e)),
+                );
+            }
+        };
+        match serde_json::from_str::<VecDeque<FavoriteItem>>(&content) {
+            Ok(mut history) => {
+                history.truncate(PLAY_HISTORY_MAX);
+                (history, None)
+            }
+            Err(_) => match Self::backup_corrupted_file(&path, "播放历史") {
+                Ok(backup_path) => (
+                    VecDeque::new(),
+                    Some(format!(
+                        "播放历史已损坏并自动备份到: {}",
+                        backup_path.display()
+                    )),
+                ),
+                Err(e) => (VecDeque::new(), Some(e)),
+            },
+        }
+    }
+
+    fn save_play_history(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.play_history)
+            .map_err(|e| format!("序列化播放历史失败: {}", e))?;
+        let path = Self::play_history_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("创建播放历史目录失败 ({}): {}", parent.display(), e))?;
+        }
+        fs::write(&path, json).map_err(|e| format!("保存播放历史失败 ({}): {}", path.display(), e))
+    }
+
     // ── 构建 ──────────────────────────────────────────────────────────────────
 
     pub fn new(favorites_file: &str) -> Self {
         let favorites_path = Self::resolve_favorites_path(favorites_file);
+        let migration_log = Self::migrate_legacy_favorites(&favorites_path);
         let (groups, load_warning) = Self::load_favorites(&favorites_path);
         let mut logs = VecDeque::from(vec!["应用启动".to_string()]);
+        logs.extend(migration_log);
         let total: usize = groups.iter().map(|g| g.items.len()).sum();
         if total > 0 {
             logs.push_back(format!(
@@ -229,15 +600,48 @@ impl App {
         if let Some(warning) = load_warning {
             logs.push_back(warning);
         }
+        if let Some(warning) = Self::favorites_dir_warning(&favorites_path) {
+            logs.push_back(warning);
+        }
+        let (play_history, history_warning) = Self::load_play_history();
+        if let Some(warning) = history_warning {
+            logs.push_back(warning);
+        }
+        let (stats, stats_warning) = ListeningStats::load();
+        if let Some(warning) = stats_warning {
+            logs.push_back(warning);
+        }
 
         Self {
             running: true,
             status: PlayerStatus::Waiting,
             current_song: String::new(),
             current_local_path: None,
+            current_track_url: None,
             progress: 0.0,
+            time_pos: 0.0,
+            duration: 0.0,
+            buffering: None,
+            load_stage: None,
+            audio_codec: None,
+            chapters: Vec::new(),
+            chapter: None,
+            loudness: None,
+            level_meter: false,
+            cover_enabled: false,
+            preview: None,
+            current_thumbnail: None,
+            cover_art: None,
+            cover_title: String::new(),
+            cover_requested: false,
+            cover_area: None,
+            audio_bitrate: None,
             volume: 100,
+            muted: false,
+            pre_mute_volume: 100,
+            eq_preset: EqPreset::Flat,
             logs,
+            file_logger: None,
             input_mode: false,
             input_buffer: String::new(),
             search_history: VecDeque::new(),
@@ -246,6 +650,10 @@ impl App {
             groups,
             selected_group: 0,
             selected_favorite: 0,
+            favorite_filter: String::new(),
+            filter_input_mode: false,
+            favorite_sort: FavoriteSort::Insertion,
+            follow_favorite_sort: false,
             play_mode: PlayMode::Shuffle,
             search_results: Vec::new(),
             selected_search_result: 0,
@@ -256,13 +664,56 @@ impl App {
             total_pages: 1,
             search_cache: HashMap::new(),
             is_loading_page: false,
+            prefetching_page: None,
             group_input_mode: false,
             move_mode: false,
             move_target_group: 0,
             delete_confirm_mode: false,
+            clear_favorites_confirm_mode: false,
             rename_mode: false,
+            display_name_mode: false,
+            exclude_input_mode: false,
+            exclude_patterns: Vec::new(),
+            seek_input_mode: false,
+            volume_input_mode: false,
+            page_input_mode: false,
+            snapshot_input_mode: false,
             help_mode: false,
-            playing_from_search: false,
+            help_scroll: 0,
+            stats_mode: false,
+            stats,
+            stats_tick: None,
+            last_error_detail: None,
+            error_detail_mode: false,
+            now_playing_mode: false,
+            seek_seconds: 10,
+            volume_step: 5,
+            max_volume: crate::net::VOLUME_MAX,
+            confirm_quit: false,
+            show_clock: false,
+            show_logs: true,
+            title_match: TitleMatch::default(),
+            screensaver: false,
+            last_input: Instant::now(),
+            tips: Vec::new(),
+            quit_confirm_mode: false,
+            playback_context: PlaybackContext::Favorites,
+            play_history,
+            history_mode: false,
+            selected_history: 0,
+            planned_shuffle: None,
+            shuffle_avoid_recent: 0,
+            recent_plays: VecDeque::new(),
+            stopped_polls: 0,
+            animation_tick: 0,
+            rng_state: Cell::new(Self::seed_rng()),
+            back_stack: Vec::new(),
+            forward_stack: Vec::new(),
+            now_track: None,
+            stack_nav_target: None,
+            lyrics: LyricsState::Idle,
+            lyrics_title: String::new(),
+            lyrics_cache: HashMap::new(),
             request_seq: 0,
             active_request_id: 0,
             favorites_path,
@@ -319,6 +770,40 @@ impl App {
         }
     }
 
+    /// 进入显示名称编辑，预填选中收藏当前显示的名称
+    pub fn start_edit_display_name(&mut self) {
+        let Some(label) = self
+            .active_items()
+            .get(self.selected_favorite)
+            .map(|item| item.label().to_string())
+        else {
+            return;
+        };
+        self.display_name_mode = true;
+        self.input_buffer = label;
+    }
+
+    /// 设置选中收藏的显示名称；留空或与原标题相同时恢复显示原标题。
+    /// 播放和正在播放的判断仍然使用原标题。
+    pub fn set_display_name(&mut self, name: String) {
+        let name = name.trim().to_string();
+        let group = self.selected_group.min(self.groups.len().saturating_sub(1));
+        let Some(item) = self.groups[group].items.get_mut(self.selected_favorite) else {
+            return;
+        };
+        let message = if name.is_empty() || name == item.title {
+            item.display_name = None;
+            format!("已恢复显示原标题: {}", item.title)
+        } else {
+            item.display_name = Some(name.clone());
+            format!("已将「{}」显示为「{}」", item.title, name)
+        };
+        self.add_log(message);
+        if let Err(e) = Self::save_favorites(&self.groups, &self.favorites_path) {
+            self.add_log(e);
+        }
+    }
+
     /// 删除当前分组（至少保留一个）
     pub fn delete_current_group(&mut self) {
         if self.groups.len() <= 1 {
@@ -337,11 +822,47 @@ impl App {
         }
     }
 
+    /// 清空全部收藏（所有分组），只保留一个空的「默认」分组。
+    /// 清空前先把收藏文件复制为 `<name>.bak.<时间戳>`，备份失败时放弃清空。
+    pub fn clear_all_favorites(&mut self) {
+        let backup = if self.favorites_path.exists() {
+            let backup_path = Self::timestamped_backup_path(&self.favorites_path, "bak");
+            if let Err(e) = fs::copy(&self.favorites_path, &backup_path) {
+                self.add_log(format!(
+                    "⚠ 备份收藏失败，已取消清空 ({}): {}",
+                    backup_path.display(),
+                    e
+                ));
+                return;
+            }
+            Some(backup_path)
+        } else {
+            None
+        };
+
+        self.groups = vec![FavoriteGroup::new("默认")];
+        self.selected_group = 0;
+        self.selected_favorite = 0;
+        self.favorite_filter.clear();
+        self.planned_shuffle = None;
+        match backup {
+            Some(path) => self.add_log(format!(
+                "已清空全部收藏，原收藏已备份到: {}",
+                path.display()
+            )),
+            None => self.add_log("已清空全部收藏".to_string()),
+        }
+        if let Err(e) = Self::save_favorites(&self.groups, &self.favorites_path) {
+            self.add_log(e);
+        }
+    }
+
     /// 切换到下一个分组
     pub fn select_next_group(&mut self) {
         if self.groups.len() > 1 {
             self.selected_group = (self.selected_group + 1) % self.groups.len();
             self.selected_favorite = 0;
+            self.clamp_selection_to_filter();
         }
     }
 
@@ -354,6 +875,7 @@ impl App {
                 self.selected_group -= 1;
             }
             self.selected_favorite = 0;
+            self.clamp_selection_to_filter();
         }
     }
 
@@ -441,6 +963,9 @@ impl App {
         if self.logs.back().is_some_and(|last| last == &message) {
             return;
         }
+        if let Some(logger) = &self.file_logger {
+            logger.log(&message);
+        }
         self.logs.push_back(message);
         if self.logs.len() > 50 {
             self.logs.pop_front();
@@ -461,6 +986,14 @@ impl App {
         }
     }
 
+    /// `r` 重新搜索用的关键词：优先取当前结果的关键词，离开结果列表后退回到最近一次搜索历史
+    pub fn repeat_search_keyword(&self) -> Option<String> {
+        if !self.last_search_keyword.is_empty() {
+            return Some(self.last_search_keyword.clone());
+        }
+        self.search_history.front().cloned()
+    }
+
     pub fn history_prev(&mut self) {
         if self.search_history.is_empty() {
             return;
@@ -496,9 +1029,41 @@ impl App {
         self.input_draft.clear();
     }
 
+    /// 输入搜索关键词时来自搜索历史的建议：前缀匹配在前，其余包含输入的条目在后（不区分大小写），
+    /// 各自保持从新到旧的顺序。用 ↑↓ 浏览历史时不给出建议
+    pub fn search_suggestions(&self) -> Vec<&str> {
+        let input = self.input_buffer.trim().to_lowercase();
+        if !self.input_mode || self.history_cursor.is_some() || input.is_empty() {
+            return Vec::new();
+        }
+        let candidates: Vec<(&str, String)> = self
+            .search_history
+            .iter()
+            .map(|k| (k.as_str(), k.to_lowercase()))
+            .filter(|(_, lower)| *lower != input && lower.contains(&input))
+            .collect();
+        let (prefix, rest): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .partition(|(_, lower)| lower.starts_with(&input));
+        prefix
+            .into_iter()
+            .chain(rest)
+            .map(|(keyword, _)| keyword)
+            .take(SEARCH_SUGGESTION_MAX)
+            .collect()
+    }
+
+    /// Tab：用第一条建议替换当前输入
+    pub fn accept_search_suggestion(&mut self) {
+        if let Some(keyword) = self.search_suggestions().first() {
+            self.input_buffer = keyword.to_string();
+        }
+    }
+
     // ── 收藏管理 ──────────────────────────────────────────────────────────────
 
-    /// 播放中按 f：在当前激活分组中切换当前播放歌曲的收藏状态
+    /// 播放中按 f：在当前激活分组中切换当前播放歌曲的收藏状态。
+    /// 收藏按（标题, 搜索源）区分，同一首歌可以分别从不同的源收藏
     pub fn toggle_favorite(&mut self) {
         if self.current_song.is_empty() {
             return;
@@ -510,7 +1075,11 @@ impl App {
         // 用块作用域限制 mutable borrow 的生命周期
         let (removed, group_name) = {
             let group = &mut self.groups[idx];
-            if let Some(pos) = group.items.iter().position(|item| item.title == song) {
+            if let Some(pos) = group
+                .items
+                .iter()
+                .position(|item| item.title == song && item.source == source)
+            {
                 group.items.remove(pos);
                 (true, String::new())
             } else {
@@ -519,6 +1088,9 @@ impl App {
                     title: song.clone(),
                     source,
                     local_path: self.current_local_path.clone(),
+                    display_name: None,
+                    play_count: 0,
+                    start_offset_secs: None,
                 });
                 (false, name)
             }
@@ -555,12 +1127,16 @@ impl App {
     pub fn toggle_favorite_from_search_result(&mut self) {
         if let Some(result) = self.get_selected_search_result() {
             let title = result.title.clone();
-            let source = self.current_source.clone();
+            let source = result.source.clone();
 
             let idx = self.selected_group.min(self.groups.len().saturating_sub(1));
             let (removed, group_name) = {
                 let group = &mut self.groups[idx];
-                if let Some(pos) = group.items.iter().position(|item| item.title == title) {
+                if let Some(pos) = group
+                    .items
+                    .iter()
+                    .position(|item| item.title == title && item.source == source)
+                {
                     group.items.remove(pos);
                     (true, group.name.clone())
                 } else {
@@ -569,6 +1145,9 @@ impl App {
                         title: title.clone(),
                         source,
                         local_path: None,
+                        display_name: None,
+                        play_count: 0,
+                        start_offset_secs: None,
                     });
                     (false, name)
                 }
@@ -591,20 +1170,26 @@ impl App {
             self.add_log("当前没有搜索结果".to_string());
             return;
         }
-        let source = self.current_source.clone();
         let idx = self.selected_group.min(self.groups.len().saturating_sub(1));
         let group = &mut self.groups[idx];
         let group_name = group.name.clone();
         let mut added = 0usize;
         let mut skipped = 0usize;
         for result in &self.search_results {
-            if group.items.iter().any(|item| item.title == result.title) {
+            if group
+                .items
+                .iter()
+                .any(|item| item.title == result.title && item.source == result.source)
+            {
                 skipped += 1;
             } else {
                 group.items.push(FavoriteItem {
                     title: result.title.clone(),
-                    source: source.clone(),
+                    source: result.source.clone(),
                     local_path: None,
+                    display_name: None,
+                    play_count: 0,
+                    start_offset_secs: None,
                 });
                 added += 1;
             }
@@ -624,63 +1209,346 @@ impl App {
     }
 
     pub fn is_favorite(&self) -> bool {
+        self.active_items().iter().any(|item| {
+            item.source == self.current_source
+                && titles_match(&item.title, &self.current_song, self.title_match)
+        })
+    }
+
+    /// 指定（标题, 搜索源）是否就是当前播放的歌曲
+    pub fn is_current_track(&self, title: &str, source: &str) -> bool {
+        title == self.current_song && source == self.current_source
+    }
+
+    /// 检查指定（标题, 搜索源）是否已在当前激活分组的收藏中
+    pub fn is_in_active_group(&self, title: &str, source: &str) -> bool {
         self.active_items()
             .iter()
-            .any(|item| item.title == self.current_song)
+            .any(|item| item.title == title && item.source == source)
+    }
+
+    /// 所有分组中收藏歌曲的（规范化标题, 搜索源）集合，渲染搜索结果时构建一次，逐行查询
+    pub fn favorite_title_set(&self) -> HashSet<(String, String)> {
+        self.groups
+            .iter()
+            .flat_map(|group| group.items.iter())
+            .map(|item| (normalize_title(&item.title), item.source.clone()))
+            .collect()
+    }
+
+    /// 当前搜索结果中（标题, 搜索源）对应条目的观看链接
+    pub fn search_result_url(&self, title: &str, source: &str) -> Option<String> {
+        self.search_results
+            .iter()
+            .find(|r| r.title == title && r.source == source)
+            .and_then(|r| r.webpage_url.clone())
     }
 
-    /// 检查指定标题是否已在当前激活分组的收藏中
-    pub fn is_title_in_active_group(&self, title: &str) -> bool {
-        self.active_items().iter().any(|item| item.title == title)
+    /// 查找歌曲所属的搜索源。同名歌曲可能来自不同的源，因此依次尝试：
+    /// 当前播放列表中选中的条目、列表中与 current_source 相同的条目、当前搜索结果、当前分组，
+    /// 都找不到时沿用 current_source
+    pub fn source_for_title(&self, title: &str) -> String {
+        let selected = match self.playback_context {
+            PlaybackContext::SearchResults => self
+                .search_results
+                .get(self.selected_search_result)
+                .filter(|r| r.title == title)
+                .map(|r| r.source.clone()),
+            PlaybackContext::Favorites => self
+                .active_items()
+                .get(self.selected_favorite)
+                .filter(|item| item.title == title)
+                .map(|item| item.source.clone()),
+        };
+        if let Some(source) = selected {
+            return source;
+        }
+        let listed_with_current_source = self
+            .search_results
+            .iter()
+            .any(|r| r.title == title && r.source == self.current_source)
+            || self.is_in_active_group(title, &self.current_source);
+        if listed_with_current_source {
+            return self.current_source.clone();
+        }
+        self.search_results
+            .iter()
+            .find(|r| r.title == title)
+            .map(|r| r.source.clone())
+            .or_else(|| {
+                self.active_items()
+                    .iter()
+                    .find(|item| item.title == title)
+                    .map(|item| item.source.clone())
+            })
+            .unwrap_or_else(|| self.current_source.clone())
     }
 
-    pub fn update_favorite_local_path(&mut self, song: &str, local_path: String) {
+    /// 一首歌开始播放时，为所有分组中对应（标题, 搜索源）的收藏累加播放次数，
+    /// 并回写解析时缓存到本地的文件路径
+    pub fn record_favorite_play(&mut self, song: &str, source: &str, local_path: Option<String>) {
         let mut save_needed = false;
         for group in &mut self.groups {
             for item in &mut group.items {
-                if item.title == song && item.local_path != Some(local_path.clone()) {
-                    item.local_path = Some(local_path.clone());
-                    save_needed = true;
+                if item.title != song || item.source != source {
+                    continue;
                 }
+                item.play_count = item.play_count.saturating_add(1);
+                if local_path.is_some() {
+                    item.local_path = local_path.clone();
+                }
+                save_needed = true;
             }
         }
         if save_needed {
             if let Err(e) = Self::save_favorites(&self.groups, &self.favorites_path) {
-                self.add_log(format!("回写 local_path 失败: {}", e));
+                self.add_log(format!("保存播放次数失败: {}", e));
             }
         }
     }
 
+    /// 歌曲在收藏中标记的起始位置（秒）；未收藏或未标记时返回 None
+    pub fn favorite_start_offset(&self, song: &str, source: &str) -> Option<u64> {
+        self.groups
+            .iter()
+            .flat_map(|g| &g.items)
+            .filter(|item| item.title == song && item.source == source)
+            .find_map(|item| item.start_offset_secs)
+    }
+
+    /// 按 S：把当前播放位置标记为这首歌的起始位置，已标记过时清除。
+    /// 同一首歌在多个分组中的收藏一起更新
+    pub fn toggle_start_offset(&mut self) {
+        if !matches!(self.status, PlayerStatus::Playing | PlayerStatus::Paused) {
+            return;
+        }
+        let (song, source) = (self.current_song.clone(), self.current_source.clone());
+        let current = self.favorite_start_offset(&song, &source);
+        let offset = match current {
+            Some(_) => None,
+            None => Some(self.time_pos.max(0.0) as u64),
+        };
+        let mut found = false;
+        for item in self
+            .groups
+            .iter_mut()
+            .flat_map(|g| &mut g.items)
+            .filter(|item| item.title == song && item.source == source)
+        {
+            item.start_offset_secs = offset;
+            found = true;
+        }
+        if !found {
+            self.add_log("当前歌曲未收藏，无法标记起始位置".to_string());
+            return;
+        }
+        if let Err(e) = Self::save_favorites(&self.groups, &self.favorites_path) {
+            self.add_log(format!("保存起始位置失败: {}", e));
+            return;
+        }
+        match offset {
+            Some(secs) => self.add_log(format!(
+                "⏩ 已标记起始位置（第 {} 秒），下次从这里开始播放",
+                secs
+            )),
+            None => self.add_log("已清除起始位置，下次从头播放".to_string()),
+        }
+    }
+
     // ── 收藏列表导航 ──────────────────────────────────────────────────────────
 
+    /// 在（过滤后的）收藏列表中选择下一首，首尾回绕
     pub fn select_next_favorite(&mut self) {
-        let len = self.active_items().len();
-        if len > 0 {
-            self.selected_favorite = (self.selected_favorite + 1) % len;
+        let visible = self.visible_favorite_indices();
+        if visible.is_empty() {
+            return;
         }
+        let pos = visible
+            .iter()
+            .position(|&i| i == self.selected_favorite)
+            .map_or(0, |p| (p + 1) % visible.len());
+        self.selected_favorite = visible[pos];
     }
 
+    /// 在（过滤后的）收藏列表中选择上一首，首尾回绕
     pub fn select_prev_favorite(&mut self) {
-        let len = self.active_items().len();
-        if len > 0 {
-            if self.selected_favorite == 0 {
-                self.selected_favorite = len - 1;
-            } else {
-                self.selected_favorite -= 1;
-            }
+        let visible = self.visible_favorite_indices();
+        if visible.is_empty() {
+            return;
         }
+        let pos = visible
+            .iter()
+            .position(|&i| i == self.selected_favorite)
+            .map_or(0, |p| (p + visible.len() - 1) % visible.len());
+        self.selected_favorite = visible[pos];
     }
 
     pub fn get_selected_favorite(&self) -> Option<&FavoriteItem> {
         self.active_items().get(self.selected_favorite)
     }
 
+    // ── 收藏过滤 ──────────────────────────────────────────────────────────────
+
+    /// 当前分组中匹配过滤关键字的歌曲索引，按当前排序排列；没有过滤时返回全部索引
+    pub fn visible_favorite_indices(&self) -> Vec<usize> {
+        let filter = self.favorite_filter.trim().to_lowercase();
+        let items = self.active_items();
+        self.sorted_favorite_indices()
+            .into_iter()
+            .filter(|&i| {
+                filter.is_empty()
+                    || items[i].title.to_lowercase().contains(&filter)
+                    || items[i].label().to_lowercase().contains(&filter)
+            })
+            .collect()
+    }
+
+    pub fn push_filter_char(&mut self, c: char) {
+        self.favorite_filter.push(c);
+        self.clamp_selection_to_filter();
+    }
+
+    pub fn pop_filter_char(&mut self) {
+        self.favorite_filter.pop();
+        self.clamp_selection_to_filter();
+    }
+
+    /// 清除过滤，恢复完整列表；选中项保持不变（必要时收回到列表范围内）
+    pub fn clear_favorite_filter(&mut self) {
+        self.favorite_filter.clear();
+        self.filter_input_mode = false;
+        self.clamp_selection_to_filter();
+    }
+
+    /// 选中项不在过滤结果中时，移到第一条匹配的歌曲
+    fn clamp_selection_to_filter(&mut self) {
+        let visible = self.visible_favorite_indices();
+        if !visible.contains(&self.selected_favorite) {
+            self.selected_favorite = visible.first().copied().unwrap_or_else(|| {
+                self.selected_favorite
+                    .min(self.active_items().len().saturating_sub(1))
+            });
+        }
+    }
+
+    // ── 收藏排序 ──────────────────────────────────────────────────────────────
+
+    /// 当前分组全部歌曲的索引，按 `favorite_sort` 排列（不受过滤影响）
+    pub fn sorted_favorite_indices(&self) -> Vec<usize> {
+        let items = self.active_items();
+        let mut indices: Vec<usize> = (0..items.len()).collect();
+        let title_key = |i: &usize| normalize_title(items[*i].label());
+        match self.favorite_sort {
+            FavoriteSort::Insertion => {}
+            FavoriteSort::TitleAsc => indices.sort_by_cached_key(title_key),
+            FavoriteSort::TitleDesc => {
+                indices.sort_by_cached_key(title_key);
+                indices.reverse();
+            }
+            FavoriteSort::Source => {
+                indices.sort_by_cached_key(|i| (items[*i].source.clone(), title_key(i)))
+            }
+            FavoriteSort::PlayCount => indices
+                .sort_by_cached_key(|i| (std::cmp::Reverse(items[*i].play_count), title_key(i))),
+        }
+        indices
+    }
+
+    /// 顺序播放时的歌曲顺序：开启 `follow_favorite_sort` 时按显示排序，否则按保存的顺序
+    fn favorite_play_order(&self) -> Vec<usize> {
+        if self.follow_favorite_sort {
+            self.sorted_favorite_indices()
+        } else {
+            (0..self.active_items().len()).collect()
+        }
+    }
+
+    /// 切换到下一种显示排序；选中项始终是同一首歌（`selected_favorite` 是保存顺序中的索引）
+    pub fn cycle_favorite_sort(&mut self) {
+        self.favorite_sort = self.favorite_sort.next();
+        self.add_log(format!("收藏排序: {}", self.favorite_sort.label()));
+    }
+
+    /// 把当前显示排序写入分组的保存顺序，之后恢复为“收藏顺序”显示
+    pub fn save_favorite_sort(&mut self) {
+        if self.favorite_sort == FavoriteSort::Insertion {
+            self.add_log("当前已是收藏顺序，无需保存".to_string());
+            return;
+        }
+        let order = self.sorted_favorite_indices();
+        let selected = order
+            .iter()
+            .position(|&i| i == self.selected_favorite)
+            .unwrap_or(0);
+        let group = self.active_group_mut();
+        let mut items: Vec<Option<FavoriteItem>> = group.items.drain(..).map(Some).collect();
+        group.items = order.iter().filter_map(|&i| items[i].take()).collect();
+        self.selected_favorite = selected;
+        let label = self.favorite_sort.label();
+        self.favorite_sort = FavoriteSort::Insertion;
+        match Self::save_favorites(&self.groups, &self.favorites_path) {
+            Ok(()) => self.add_log(format!("已按「{}」重新排列并保存当前分组", label)),
+            Err(e) => self.add_log(e),
+        }
+    }
+
+    /// 将选中的收藏与上一条交换位置，已在顶部时不做任何事
+    pub fn move_favorite_up(&mut self) {
+        self.move_favorite(false);
+    }
+
+    /// 将选中的收藏与下一条交换位置，已在底部时不做任何事
+    pub fn move_favorite_down(&mut self) {
+        self.move_favorite(true);
+    }
+
+    /// 与界面上相邻的收藏交换位置。过滤时只和可见的条目交换，被隐藏的条目位置不变
+    fn move_favorite(&mut self, down: bool) {
+        if !self.can_reorder_favorites() {
+            return;
+        }
+        let idx = self.selected_favorite;
+        let visible = self.visible_favorite_indices();
+        let Some(pos) = visible.iter().position(|&i| i == idx) else {
+            return;
+        };
+        let neighbor = if down {
+            visible.get(pos + 1)
+        } else {
+            pos.checked_sub(1).and_then(|p| visible.get(p))
+        };
+        let Some(&target) = neighbor else {
+            return;
+        };
+        self.active_group_mut().items.swap(idx, target);
+        self.selected_favorite = target;
+        if let Err(e) = Self::save_favorites(&self.groups, &self.favorites_path) {
+            self.add_log(e);
+        }
+    }
+
+    /// 只有按收藏顺序显示时才能手动调整顺序，否则交换结果在界面上看不出来
+    fn can_reorder_favorites(&mut self) -> bool {
+        if self.favorite_sort == FavoriteSort::Insertion {
+            return true;
+        }
+        self.add_log("按其他方式排序时不能调整顺序，按 o 切回收藏顺序".to_string());
+        false
+    }
+
     pub fn sync_selected_favorite(&mut self) {
-        if let Some(idx) = self
-            .active_items()
+        // 优先完全相同的条目，找不到时再按 `title_match` 放宽比较
+        let items = self.active_items();
+        let found = items
             .iter()
-            .position(|item| item.title == self.current_song)
-        {
+            .position(|item| self.is_current_track(&item.title, &item.source))
+            .or_else(|| {
+                items.iter().position(|item| {
+                    item.source == self.current_source
+                        && titles_match(&item.title, &self.current_song, self.title_match)
+                })
+            });
+        if let Some(idx) = found {
             self.selected_favorite = idx;
             self.add_log(format!("同步收藏索引到: {}", idx));
         } else {
@@ -720,12 +1588,18 @@ impl App {
         }
     }
 
+    /// 搜索进行中追加一条已到达的结果，状态保持为 Searching
+    pub fn push_partial_search_result(&mut self, result: SearchResult) {
+        self.search_results.push(result);
+    }
+
     pub fn clear_search_results(&mut self) {
         self.search_results.clear();
         self.selected_search_result = 0;
         self.last_search_keyword.clear();
         self.search_cache.clear();
         self.is_loading_page = false;
+        self.prefetching_page = None;
     }
 
     // ── 异步请求追踪 ──────────────────────────────────────────────────────────
@@ -741,6 +1615,10 @@ impl App {
         self.active_request_id == request_id
     }
 
+    pub fn active_request_id(&self) -> u64 {
+        self.active_request_id
+    }
+
     // ── 翻页缓存 ──────────────────────────────────────────────────────────────
 
     pub fn get_cached_page(&self, page: usize) -> Option<&Vec<SearchResult>> {
@@ -757,6 +1635,25 @@ impl App {
         }
     }
 
+    /// 判断是否需要预取第 `page` 页：已缓存、超出已知总页数或正在预取时返回 false，
+    /// 否则标记为预取中并返回 true
+    pub fn begin_page_prefetch(&mut self, page: usize) -> bool {
+        if page > self.total_pages
+            || self.search_cache.contains_key(&page)
+            || self.prefetching_page == Some(page)
+        {
+            return false;
+        }
+        self.prefetching_page = Some(page);
+        true
+    }
+
+    pub fn finish_page_prefetch(&mut self, page: usize) {
+        if self.prefetching_page == Some(page) {
+            self.prefetching_page = None;
+        }
+    }
+
     // ── 搜索状态保存/恢复 ─────────────────────────────────────────────────────
 
     pub fn save_status_before_search(&mut self) {
@@ -771,7 +1668,11 @@ impl App {
     pub fn restore_status_after_search(&mut self) {
         if matches!(
             self.status,
-            PlayerStatus::Playing | PlayerStatus::Paused | PlayerStatus::Error(_)
+            PlayerStatus::Playing
+                | PlayerStatus::Paused
+                | PlayerStatus::SearchError(_)
+                | PlayerStatus::PlaybackError(_)
+                | PlayerStatus::Unavailable(_)
         ) {
             self.saved_status = None;
             return;
@@ -784,40 +1685,513 @@ impl App {
         }
     }
 
-    // ── 播放模式 ──────────────────────────────────────────────────────────────
+    // ── 错误 ──────────────────────────────────────────────────────────────────
 
-    pub fn toggle_play_mode(&mut self) {
-        self.play_mode = match self.play_mode {
-            PlayMode::Shuffle => PlayMode::Single,
-            PlayMode::Single => PlayMode::ListLoop,
-            PlayMode::ListLoop => PlayMode::Sequential,
-            PlayMode::Sequential => PlayMode::Shuffle,
-        };
-        let mode_text = match self.play_mode {
-            PlayMode::Single => "单曲循环",
-            PlayMode::ListLoop => "列表循环",
-            PlayMode::Sequential => "顺序播放",
-            PlayMode::Shuffle => "随机播放",
-        };
-        self.add_log(format!("播放模式: {}", mode_text));
+    /// 进入搜索错误状态，`detail` 为可展开查看的详细信息（如 yt-dlp 的完整 stderr）
+    pub fn set_search_error(&mut self, message: String, detail: Option<String>) {
+        self.set_error(PlayerStatus::SearchError(message), detail);
     }
 
-    pub fn set_play_mode_from_config(&mut self, mode: &str) -> bool {
-        let normalized = mode.trim().to_lowercase();
-        let parsed = match normalized.as_str() {
-            "single" | "single_loop" | "single-loop" => Some(PlayMode::Single),
-            "list_loop" | "list-loop" | "loop" | "list" => Some(PlayMode::ListLoop),
-            "sequential" | "sequence" | "seq" => Some(PlayMode::Sequential),
-            "shuffle" | "random" => Some(PlayMode::Shuffle),
-            _ => None,
-        };
-        if let Some(play_mode) = parsed {
-            self.play_mode = play_mode;
-            true
-        } else {
-            self.play_mode = PlayMode::Shuffle;
-            false
-        }
+    /// 进入播放错误状态，下一次轮询时会自动跳到下一首
+    pub fn set_playback_error(&mut self, message: String, detail: Option<String>) {
+        self.set_error(PlayerStatus::PlaybackError(message), detail);
+    }
+
+    /// 进入歌曲不可用状态，与播放错误一样在下一次轮询时跳到下一首
+    pub fn set_unavailable_error(&mut self, message: String, detail: Option<String>) {
+        self.set_error(PlayerStatus::Unavailable(message), detail);
+    }
+
+    fn set_error(&mut self, status: PlayerStatus, detail: Option<String>) {
+        self.status = status;
+        self.load_stage = None;
+        self.last_error_detail = detail;
+        self.error_detail_mode = false;
+    }
+
+    /// 切换错误详情面板；没有详情时记录日志说明
+    pub fn toggle_error_detail(&mut self) {
+        if self.last_error_detail.is_some() {
+            self.error_detail_mode = !self.error_detail_mode;
+        } else {
+            self.add_log("这个错误没有更多详情".to_string());
+        }
+    }
+
+    /// 显示/隐藏日志面板
+    pub fn toggle_logs(&mut self) {
+        self.show_logs = !self.show_logs;
+    }
+
+    // ── 屏保 ──────────────────────────────────────────────────────────────────
+
+    /// 是否正在显示屏保：已启用、没有在播放，且超过 `SCREENSAVER_IDLE` 没有按键
+    pub fn screensaver_active(&self) -> bool {
+        self.screensaver
+            && matches!(self.status, PlayerStatus::Waiting)
+            && self.last_input.elapsed() >= SCREENSAVER_IDLE
+    }
+
+    /// 记录一次按键。返回按键前屏保是否正在显示（此时这次按键只用于唤醒界面）
+    pub fn wake_screensaver(&mut self) -> bool {
+        let was_active = self.screensaver_active();
+        self.last_input = Instant::now();
+        was_active
+    }
+
+    /// 当前应显示的空闲提示：只在等待播放、没有输入框打开且一段时间没有按键时轮播，
+    /// 按键后立即隐藏，再次空闲时从第一条重新开始
+    pub fn current_tip(&self) -> Option<&str> {
+        if self.tips.is_empty() || self.input_mode || !matches!(self.status, PlayerStatus::Waiting)
+        {
+            return None;
+        }
+        let idle = self.last_input.elapsed().checked_sub(TIP_IDLE)?;
+        let index = (idle.as_secs() / TIP_INTERVAL_SECS) as usize % self.tips.len();
+        Some(&self.tips[index])
+    }
+
+    // ── 退出 ──────────────────────────────────────────────────────────────────
+
+    /// 按下 q 时调用：需要确认时进入确认模式并返回 false，否则返回 true 表示可以直接退出
+    pub fn request_quit(&mut self) -> bool {
+        if self.confirm_quit && matches!(self.status, PlayerStatus::Playing | PlayerStatus::Paused)
+        {
+            self.quit_confirm_mode = true;
+            false
+        } else {
+            true
+        }
+    }
+
+    // ── 歌词 ──────────────────────────────────────────────────────────────────
+
+    /// 切换到 `title` 的歌词。缓存命中或与当前歌名相同时返回 false；
+    /// 否则进入加载状态并返回 true，由调用方发起查询。
+    #[cfg_attr(not(feature = "lyrics"), allow(dead_code))]
+    pub fn begin_lyrics(&mut self, title: &str) -> bool {
+        if self.lyrics_title == title {
+            return false;
+        }
+        self.lyrics_title = title.to_string();
+        match self.lyrics_cache.get(title) {
+            Some(lines) if lines.is_empty() => self.lyrics = LyricsState::NotFound,
+            Some(lines) => self.lyrics = LyricsState::Found(lines.clone()),
+            None => {
+                self.lyrics = LyricsState::Loading;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// 写入查询结果。`lines` 为 None 表示查询失败，不写入缓存，下次播放时重试；
+    /// 查询期间已切歌时只更新缓存。
+    #[cfg_attr(not(feature = "lyrics"), allow(dead_code))]
+    pub fn finish_lyrics(&mut self, title: &str, lines: Option<Vec<LyricLine>>) {
+        if let Some(lines) = &lines {
+            self.lyrics_cache.insert(title.to_string(), lines.clone());
+        }
+        if self.lyrics_title != title {
+            return;
+        }
+        self.lyrics = match lines {
+            Some(lines) if !lines.is_empty() => LyricsState::Found(lines),
+            _ => LyricsState::NotFound,
+        };
+    }
+
+    // ── 封面 ──────────────────────────────────────────────────────────────────
+
+    /// 切换到 `title` 的封面。未启用封面、已发起过下载或缩略图地址尚未解析出来时返回 None；
+    /// 否则返回需要下载的缩略图地址，由调用方发起下载。
+    pub fn begin_cover(&mut self, title: &str) -> Option<String> {
+        if !self.cover_enabled || title.is_empty() {
+            return None;
+        }
+        if self.cover_title != title {
+            self.cover_title = title.to_string();
+            self.cover_art = None;
+            self.cover_requested = false;
+        }
+        if self.cover_requested {
+            return None;
+        }
+        let url = self.current_thumbnail.clone()?;
+        self.cover_requested = true;
+        Some(url)
+    }
+
+    /// 写入下载好的封面；下载期间已切歌时丢弃
+    pub fn finish_cover(&mut self, title: &str, data: Vec<u8>) {
+        if self.cover_title == title {
+            self.cover_art = Some(data);
+        }
+    }
+
+    // ── 播放历史 ──────────────────────────────────────────────────────────────
+
+    /// 新歌开始播放时维护上一首 / 下一首播放栈：正常切歌把上一首压入回退栈并清空前进栈；
+    /// 由播放栈导航触发的播放已在导航时调整过栈，这里只记录当前歌曲。
+    fn update_track_stacks(&mut self, title: &str, source: &str, local_path: &Option<String>) {
+        if let Some(now) = self.now_track.as_mut().filter(|t| t.title == title) {
+            if local_path.is_some() {
+                now.local_path = local_path.clone();
+            }
+            return;
+        }
+        let navigating = self.stack_nav_target.take().is_some_and(|t| t == title);
+        if !navigating {
+            if let Some(previous) = self.now_track.take() {
+                push_bounded(&mut self.back_stack, previous);
+            }
+            self.forward_stack.clear();
+        }
+        self.now_track = Some(FavoriteItem {
+            title: title.to_string(),
+            source: source.to_string(),
+            local_path: local_path.clone(),
+            display_name: None,
+            play_count: 0,
+            start_offset_secs: None,
+        });
+    }
+
+    /// 取出回退栈顶的歌曲，并把当前歌曲压入前进栈。栈为空时返回 None。
+    pub fn track_back(&mut self) -> Option<FavoriteItem> {
+        let target = self.back_stack.pop()?;
+        if let Some(current) = self.now_track.take() {
+            push_bounded(&mut self.forward_stack, current);
+        }
+        self.stack_nav_target = Some(target.title.clone());
+        Some(target)
+    }
+
+    /// 取出前进栈顶的歌曲（之前回退时离开的），并把当前歌曲压回回退栈
+    pub fn track_forward(&mut self) -> Option<FavoriteItem> {
+        let target = self.forward_stack.pop()?;
+        if let Some(current) = self.now_track.take() {
+            push_bounded(&mut self.back_stack, current);
+        }
+        self.stack_nav_target = Some(target.title.clone());
+        Some(target)
+    }
+
+    /// 开始加载新歌曲：切到 Searching，并清空上一首的进度、音质、章节、链接等状态
+    pub fn reset_track_state(&mut self, song: &str) {
+        self.status = PlayerStatus::Searching;
+        self.current_song = song.to_string();
+        self.progress = 0.0;
+        self.buffering = None;
+        self.audio_codec = None;
+        self.audio_bitrate = None;
+        self.chapters.clear();
+        self.chapter = None;
+        self.loudness = None;
+        self.current_track_url = None;
+        self.current_thumbnail = None;
+        self.preview = None;
+        self.stopped_polls = 0;
+        self.load_stage = Some(LoadStage::Resolving);
+    }
+
+    /// 记录一首成功开始播放的歌曲。与最近一条同名时只更新本地路径，不重复记录。
+    pub fn record_play(&mut self, title: &str, source: &str, local_path: Option<String>) {
+        self.stats.tracks_played += 1;
+        if let Err(e) = self.stats.save() {
+            self.add_log(e);
+        }
+        self.update_track_stacks(title, source, &local_path);
+        if self.recent_plays.back().map(String::as_str) != Some(title) {
+            self.recent_plays.push_back(title.to_string());
+            while self.recent_plays.len() > self.shuffle_avoid_recent {
+                self.recent_plays.pop_front();
+            }
+        }
+        if let Some(latest) = self.play_history.front_mut() {
+            if latest.title == title {
+                if local_path.is_some() {
+                    latest.local_path = local_path;
+                }
+                return;
+            }
+        }
+        self.play_history.push_front(FavoriteItem {
+            title: title.to_string(),
+            source: source.to_string(),
+            local_path,
+            display_name: None,
+            play_count: 0,
+            start_offset_secs: None,
+        });
+        self.play_history.truncate(PLAY_HISTORY_MAX);
+        if self.history_mode {
+            // 新条目插在最前面，保持高亮停留在原来那首歌上
+            self.selected_history = (self.selected_history + 1).min(self.play_history.len() - 1);
+        }
+        if let Err(e) = self.save_play_history() {
+            self.add_log(e);
+        }
+    }
+
+    // ── 收听统计 ──────────────────────────────────────────────────────────────
+
+    /// 每个 tick 调用：正在播放时把距上一次调用的时间计入收听时长，攒够一定时长才写盘
+    pub fn tick_listening(&mut self, playing: bool) {
+        let now = Instant::now();
+        if let (true, Some(last)) = (playing, self.stats_tick) {
+            if self.stats.add_listening(now - last) {
+                if let Err(e) = self.stats.save() {
+                    self.add_log(e);
+                }
+            }
+        }
+        self.stats_tick = playing.then_some(now);
+    }
+
+    /// 退出前保存尚未写盘的收听时长
+    pub fn save_stats(&mut self) -> Result<(), String> {
+        if self.stats.is_dirty() {
+            self.stats.save()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 读取收藏文件（不创建 App），用于 `--stats` 等无界面命令
+    pub fn read_favorites(favorites_file: &str) -> (Vec<FavoriteGroup>, Option<String>) {
+        Self::load_favorites(&Self::resolve_favorites_path(favorites_file))
+    }
+
+    /// 播放次数最多的 `limit` 首收藏（同一首歌在多个分组中只算播放次数最多的那条）
+    pub fn top_played(groups: &[FavoriteGroup], limit: usize) -> Vec<&FavoriteItem> {
+        let mut best: HashMap<(&str, &str), &FavoriteItem> = HashMap::new();
+        for item in groups.iter().flat_map(|g| &g.items) {
+            if item.play_count == 0 {
+                continue;
+            }
+            let entry = best
+                .entry((item.title.as_str(), item.source.as_str()))
+                .or_insert(item);
+            if item.play_count > entry.play_count {
+                *entry = item;
+            }
+        }
+        let mut items: Vec<&FavoriteItem> = best.into_values().collect();
+        items.sort_by(|a, b| {
+            b.play_count
+                .cmp(&a.play_count)
+                .then_with(|| a.label().cmp(b.label()))
+        });
+        items.truncate(limit);
+        items
+    }
+
+    /// 在收藏分组和播放历史之间切换列表视图
+    pub fn toggle_history_mode(&mut self) {
+        self.history_mode = !self.history_mode;
+        self.selected_history = 0;
+        if self.history_mode && self.play_history.is_empty() {
+            self.add_log("暂无播放历史".to_string());
+        }
+    }
+
+    pub fn select_next_history(&mut self) {
+        let len = self.play_history.len();
+        if len > 0 {
+            self.selected_history = (self.selected_history + 1) % len;
+        }
+    }
+
+    pub fn select_prev_history(&mut self) {
+        let len = self.play_history.len();
+        if len > 0 {
+            self.selected_history = (self.selected_history + len - 1) % len;
+        }
+    }
+
+    pub fn get_selected_history(&self) -> Option<&FavoriteItem> {
+        self.play_history.get(self.selected_history)
+    }
+
+    /// 历史视图按 f：将选中的歌曲收藏到当前分组（已存在时跳过）
+    pub fn favorite_selected_history(&mut self) {
+        let Some(item) = self.get_selected_history().cloned() else {
+            return;
+        };
+        if self.is_in_active_group(&item.title, &item.source) {
+            self.add_log(format!(
+                "「{}」中已有: {}",
+                self.active_group().name,
+                item.title
+            ));
+            return;
+        }
+        let group_name = self.active_group().name.clone();
+        self.add_log(format!("已收藏到「{}」: {}", group_name, item.title));
+        self.active_group_mut().items.push(item);
+        if let Err(e) = Self::save_favorites(&self.groups, &self.favorites_path) {
+            self.add_log(e);
+        }
+    }
+
+    /// 把整个播放历史按播放先后加入收藏：`name` 为空时加入当前分组，否则加入同名分组（没有时新建）。
+    /// 已在目标分组中的歌曲跳过，保留每首歌原来的搜索源；全部加入后只写一次收藏文件
+    pub fn snapshot_history(&mut self, name: &str) {
+        if self.play_history.is_empty() {
+            self.add_log("播放历史为空，没有可以保存的歌曲".to_string());
+            return;
+        }
+        let name = name.trim();
+        let idx = if name.is_empty() {
+            self.selected_group.min(self.groups.len().saturating_sub(1))
+        } else if let Some(idx) = self.groups.iter().position(|g| g.name == name) {
+            idx
+        } else {
+            self.groups.push(FavoriteGroup::new(name));
+            self.groups.len() - 1
+        };
+
+        let mut added = 0;
+        let mut skipped = 0;
+        for item in self.play_history.iter().rev() {
+            let group = &mut self.groups[idx];
+            if group
+                .items
+                .iter()
+                .any(|i| i.title == item.title && i.source == item.source)
+            {
+                skipped += 1;
+                continue;
+            }
+            group.items.push(item.clone());
+            added += 1;
+        }
+
+        let group_name = self.groups[idx].name.clone();
+        self.add_log(format!(
+            "已将播放历史中的 {} 首加入「{}」{}",
+            added,
+            group_name,
+            if skipped > 0 {
+                format!("（跳过已有的 {} 首）", skipped)
+            } else {
+                String::new()
+            }
+        ));
+        if let Err(e) = Self::save_favorites(&self.groups, &self.favorites_path) {
+            self.add_log(e);
+        }
+    }
+
+    // ── 会话恢复 ──────────────────────────────────────────────────────────────
+
+    /// 退出时保存当前歌曲、搜索源、音量和播放模式
+    pub fn save_session(&self) -> Result<(), String> {
+        let data = SessionData {
+            current_song: self.current_song.clone(),
+            current_source: self.current_source.clone(),
+            local_path: self.current_local_path.clone(),
+            volume: self.volume,
+            play_mode: self.play_mode_config_value().to_string(),
+        };
+        let json =
+            serde_json::to_string_pretty(&data).map_err(|e| format!("序列化会话失败: {}", e))?;
+        let path = Self::session_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("创建会话目录失败 ({}): {}", parent.display(), e))?;
+        }
+        fs::write(&path, json).map_err(|e| format!("保存会话失败 ({}): {}", path.display(), e))
+    }
+
+    /// 读取上次的会话并恢复搜索源、音量和播放模式。
+    /// 返回需要继续播放的歌曲；会话文件不存在或没有歌曲时返回 None，损坏的文件会被备份。
+    pub fn restore_session(&mut self) -> Option<(String, Option<String>)> {
+        let path = Self::session_path();
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                self.add_log(format!("读取会话文件失败 ({}): {}", path.display(), e));
+                return None;
+            }
+        };
+        let data = match serde_json::from_str::<SessionData>(&content) {
+            Ok(data) => data,
+            Err(_) => {
+                let msg = match Self::backup_corrupted_file(&path, "会话文件") {
+                    Ok(backup_path) => {
+                        format!("会话文件已损坏并自动备份到: {}", backup_path.display())
+                    }
+                    Err(e) => e,
+                };
+                self.add_log(msg);
+                return None;
+            }
+        };
+
+        self.volume = data.volume.min(self.max_volume);
+        if !data.current_source.is_empty() {
+            self.current_source = data.current_source;
+        }
+        self.set_play_mode_from_config(&data.play_mode);
+        if data.current_song.is_empty() {
+            return None;
+        }
+        self.add_log(format!("恢复上次播放: {}", data.current_song));
+        self.current_song = data.current_song.clone();
+        Some((data.current_song, data.local_path))
+    }
+
+    // ── 播放模式 ──────────────────────────────────────────────────────────────
+
+    pub fn toggle_play_mode(&mut self) {
+        self.play_mode = match self.play_mode {
+            PlayMode::Shuffle => PlayMode::Single,
+            PlayMode::Single => PlayMode::ListLoop,
+            PlayMode::ListLoop => PlayMode::Sequential,
+            PlayMode::Sequential => PlayMode::Shuffle,
+        };
+        let mode_text = self.get_play_mode_name();
+        self.add_log(format!("播放模式: {}", mode_text));
+    }
+
+    pub fn get_play_mode_name(&self) -> &'static str {
+        match self.play_mode {
+            PlayMode::Single => "单曲循环",
+            PlayMode::ListLoop => "列表循环",
+            PlayMode::Sequential => "顺序播放",
+            PlayMode::Shuffle => "随机播放",
+        }
+    }
+
+    pub fn set_play_mode_from_config(&mut self, mode: &str) -> bool {
+        let normalized = mode.trim().to_lowercase();
+        let parsed = match normalized.as_str() {
+            "single" | "single_loop" | "single-loop" => Some(PlayMode::Single),
+            "list_loop" | "list-loop" | "loop" | "list" => Some(PlayMode::ListLoop),
+            "sequential" | "sequence" | "seq" => Some(PlayMode::Sequential),
+            "shuffle" | "random" => Some(PlayMode::Shuffle),
+            _ => None,
+        };
+        if let Some(play_mode) = parsed {
+            self.play_mode = play_mode;
+            true
+        } else {
+            self.play_mode = PlayMode::Shuffle;
+            false
+        }
+    }
+
+    /// 播放模式对应的配置值，可被 [`App::set_play_mode_from_config`] 解析
+    pub fn play_mode_config_value(&self) -> &'static str {
+        match self.play_mode {
+            PlayMode::Single => "single",
+            PlayMode::ListLoop => "list_loop",
+            PlayMode::Sequential => "sequential",
+            PlayMode::Shuffle => "shuffle",
+        }
     }
 
     pub fn get_play_mode_text(&self) -> &str {
@@ -829,51 +2203,53 @@ impl App {
         }
     }
 
+    /// 进入下一个刷新周期，推进动画帧。2^64 是各组帧数的整数倍，回绕时帧序列保持连续
+    pub fn advance_animation(&mut self) {
+        self.animation_tick = self.animation_tick.wrapping_add(1);
+    }
+
     // ── 随机数 ────────────────────────────────────────────────────────────────
 
+    /// 启动时为随机数生成器播种：混合当前时间和进程 ID，再经 splitmix64 打散，保证非 0
+    fn seed_rng() -> u64 {
+        let nanos = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        let mut z =
+            (nanos ^ ((std::process::id() as u64) << 32)).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        (z ^ (z >> 31)) | 1
+    }
+
+    /// 返回 `[0, max)` 内均匀分布的随机数（xorshift64*，拒绝采样消除取模偏差）
     fn simple_random(&self, max: usize) -> usize {
-        use std::cell::Cell;
-        use std::time::UNIX_EPOCH;
-
-        thread_local! {
-            static RNG_STATE: Cell<u64> = const { Cell::new(0) };
-        }
-
-        RNG_STATE.with(|state| {
-            let mut s = state.get();
-            if s == 0 {
-                s = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .subsec_nanos() as u64
-                    | 1;
-            }
-            let next_state = |x: u64| -> u64 {
-                let mut x = x;
-                x ^= x << 13;
-                x ^= x >> 7;
-                x ^= x << 17;
-                x
-            };
-            let threshold = u64::MAX - (u64::MAX % max as u64);
-            loop {
-                s = next_state(s);
-                if s < threshold {
-                    state.set(s);
-                    return (s % max as u64) as usize;
-                }
+        let max = max as u64;
+        let threshold = u64::MAX - (u64::MAX % max);
+        loop {
+            let mut x = self.rng_state.get();
+            x ^= x >> 12;
+            x ^= x << 25;
+            x ^= x >> 27;
+            self.rng_state.set(x);
+            let value = x.wrapping_mul(0x2545_F491_4F6C_DD1D);
+            // 超出阈值的概率小于 max / 2^64，期望一次即可返回
+            if value < threshold {
+                return (value % max) as usize;
             }
-        })
+        }
     }
 
     // ── 自动播放下一首 ────────────────────────────────────────────────────────
 
     pub fn get_next_song(&mut self) -> Option<(String, Option<String>)> {
-        if self.playing_from_search {
-            return self.get_next_search_result();
+        if self.play_mode == PlayMode::Shuffle {
+            if let Some(idx) = self.take_planned_shuffle() {
+                return Some(self.select_playlist_entry(idx));
+            }
         }
 
-        let items = self.active_items();
         match self.play_mode {
             PlayMode::Single => {
                 if !self.current_song.is_empty() {
@@ -883,123 +2259,795 @@ impl App {
                 }
             }
             PlayMode::Shuffle => {
-                let len = items.len();
-                if len == 0 {
-                    return None;
-                }
-                if len == 1 {
-                    self.selected_favorite = 0;
-                    return Some((
-                        self.active_items()[0].title.clone(),
-                        self.active_items()[0].local_path.clone(),
-                    ));
-                }
-                let current_song = self.current_song.clone();
-                let mut idx = self.simple_random(len);
-                if let Some(current_idx) = self
-                    .active_items()
-                    .iter()
-                    .position(|item| item.title == current_song)
-                {
-                    idx = self.simple_random(len - 1);
-                    if idx >= current_idx {
-                        idx += 1;
-                    }
-                }
-                self.selected_favorite = idx;
-                Some((
-                    self.active_items()[idx].title.clone(),
-                    self.active_items()[idx].local_path.clone(),
-                ))
+                let idx = self.pick_shuffle_index(&self.playlist_titles())?;
+                Some(self.select_playlist_entry(idx))
             }
             PlayMode::ListLoop | PlayMode::Sequential => {
-                let len = self.active_items().len();
-                if len == 0 {
+                let order = self.playlist_order();
+                if order.is_empty() {
                     return None;
                 }
-                let current_song = self.current_song.clone();
-                if let Some(current_idx) = self
-                    .active_items()
-                    .iter()
-                    .position(|item| item.title == current_song)
-                {
-                    let next_idx = current_idx + 1;
-                    if next_idx < self.active_items().len() {
-                        self.selected_favorite = next_idx;
-                        return Some((
-                            self.active_items()[next_idx].title.clone(),
-                            self.active_items()[next_idx].local_path.clone(),
-                        ));
-                    } else if self.play_mode == PlayMode::ListLoop {
-                        self.selected_favorite = 0;
-                        self.add_log("列表循环，回到第一首".to_string());
-                        return Some((
-                            self.active_items()[0].title.clone(),
-                            self.active_items()[0].local_path.clone(),
-                        ));
-                    }
+                let current_pos = self
+                    .current_playlist_index()
+                    .and_then(|current| order.iter().position(|&i| i == current));
+                let Some(current_pos) = current_pos else {
+                    let list = match self.playback_context {
+                        PlaybackContext::Favorites => "当前分组",
+                        PlaybackContext::SearchResults => "当前搜索结果",
+                    };
+                    self.add_log(format!("当前歌曲 '{}' 不在{}中", self.current_song, list));
+                    return None;
+                };
+                let next_idx = if current_pos + 1 < order.len() {
+                    order[current_pos + 1]
+                } else if self.play_mode == PlayMode::ListLoop {
+                    let suffix = match self.playback_context {
+                        PlaybackContext::Favorites => "",
+                        PlaybackContext::SearchResults => " (搜索结果)",
+                    };
+                    self.add_log(format!("列表循环，回到第一首{}", suffix));
+                    order[0]
                 } else {
-                    self.add_log(format!("当前歌曲 '{}' 不在当前分组中", self.current_song));
-                }
-                None
+                    return None;
+                };
+                Some(self.select_playlist_entry(next_idx))
             }
         }
     }
 
-    fn get_next_search_result(&mut self) -> Option<(String, Option<String>)> {
-        let len = self.search_results.len();
-        if len == 0 {
+    /// 列表播完后按 `on_queue_end` 继续：loop 从播放顺序的第一首开始，shuffle 切换到随机播放后选下一首。
+    /// 其余动作（stop / quit）或列表为空时返回 None
+    pub fn next_after_queue_end(
+        &mut self,
+        action: QueueEndAction,
+    ) -> Option<(String, Option<String>)> {
+        match action {
+            QueueEndAction::Loop => {
+                let first = *self.playlist_order().first()?;
+                self.add_log("列表已播完，从第一首重新开始".to_string());
+                Some(self.select_playlist_entry(first))
+            }
+            QueueEndAction::Shuffle => {
+                self.play_mode = PlayMode::Shuffle;
+                self.add_log("列表已播完，切换到随机播放".to_string());
+                self.get_next_song()
+            }
+            QueueEndAction::Stop | QueueEndAction::Quit => None,
+        }
+    }
+
+    /// 当前播放列表的标题（保存顺序）：来自搜索结果或当前分组
+    fn playlist_titles(&self) -> Vec<&str> {
+        match self.playback_context {
+            PlaybackContext::SearchResults => self
+                .search_results
+                .iter()
+                .map(|r| r.title.as_str())
+                .collect(),
+            PlaybackContext::Favorites => self
+                .active_items()
+                .iter()
+                .map(|i| i.title.as_str())
+                .collect(),
+        }
+    }
+
+    /// 播放全部收藏：以当前分组为播放列表开始连续播放。顺序/列表循环/单曲循环从选中的歌曲开始，
+    /// 之后 [`App::get_next_song`] 从这首往后推进；随机模式忽略选中位置，随机挑一首开始。
+    /// 返回要播放的标题和本地缓存路径，分组为空时返回 None
+    pub fn start_play_all_favorites(&mut self) -> Option<(String, Option<String>)> {
+        if self.active_items().is_empty() {
+            self.add_log("当前分组没有收藏，无法播放全部".to_string());
             return None;
         }
+        self.playback_context = PlaybackContext::Favorites;
+        self.planned_shuffle = None;
+        let idx = if self.play_mode == PlayMode::Shuffle {
+            self.pick_shuffle_index(&self.playlist_titles())?
+        } else {
+            self.selected_favorite.min(self.active_items().len() - 1)
+        };
+        let entry = self.select_playlist_entry(idx);
+        let item = &self.active_items()[idx];
+        self.current_source = item.source.clone();
+        let msg = if self.play_mode == PlayMode::Shuffle {
+            format!("🔀 随机播放全部收藏，从 '{}' 开始", entry.0)
+        } else {
+            format!("▶ 播放全部收藏，从第 {} 首 '{}' 开始", idx + 1, entry.0)
+        };
+        self.add_log(msg);
+        Some(entry)
+    }
 
-        match self.play_mode {
-            PlayMode::Single => {
-                if !self.current_song.is_empty() {
-                    Some((self.current_song.clone(), self.current_local_path.clone()))
-                } else {
-                    None
-                }
+    /// 当前歌曲在 `playlist_titles` 中的索引：优先匹配（标题, 搜索源），
+    /// 同名歌曲来自不同源时不会错认成另一首；源对不上时退回只按标题匹配
+    fn current_playlist_index(&self) -> Option<usize> {
+        let entries: Vec<(&str, &str)> = match self.playback_context {
+            PlaybackContext::SearchResults => self
+                .search_results
+                .iter()
+                .map(|r| (r.title.as_str(), r.source.as_str()))
+                .collect(),
+            PlaybackContext::Favorites => self
+                .active_items()
+                .iter()
+                .map(|i| (i.title.as_str(), i.source.as_str()))
+                .collect(),
+        };
+        entries
+            .iter()
+            .position(|(title, source)| self.is_current_track(title, source))
+            .or_else(|| {
+                entries
+                    .iter()
+                    .position(|(title, _)| *title == self.current_song)
+            })
+    }
+
+    /// 当前播放列表的播放顺序（`playlist_titles` 中的索引）：收藏分组在开启 `follow_favorite_sort` 时按显示排序
+    fn playlist_order(&self) -> Vec<usize> {
+        match self.playback_context {
+            PlaybackContext::SearchResults => (0..self.search_results.len()).collect(),
+            PlaybackContext::Favorites => self.favorite_play_order(),
+        }
+    }
+
+    /// 选中播放列表中的第 `idx` 首，返回其标题和本地缓存路径（搜索结果没有本地路径）
+    fn select_playlist_entry(&mut self, idx: usize) -> (String, Option<String>) {
+        match self.playback_context {
+            PlaybackContext::SearchResults => {
+                self.selected_search_result = idx;
+                (self.search_results[idx].title.clone(), None)
+            }
+            PlaybackContext::Favorites => {
+                self.selected_favorite = idx;
+                let item = &self.active_items()[idx];
+                (item.title.clone(), item.local_path.clone())
+            }
+        }
+    }
+
+    /// 取出预选的随机曲目在当前播放列表中的位置；已失效（不在列表中或就是当前歌曲）时返回 None
+    fn take_planned_shuffle(&mut self) -> Option<usize> {
+        let planned = self.planned_shuffle.take()?;
+        if planned == self.current_song {
+            return None;
+        }
+        self.playlist_titles().iter().position(|t| *t == planned)
+    }
+
+    /// 随机模式下提前选好下一首，使其可以被 [`App::peek_next_song`] 看到并预取。
+    /// 已有有效预选时不会重新选。
+    pub fn plan_next_shuffle(&mut self) {
+        if self.play_mode != PlayMode::Shuffle {
+            return;
+        }
+        let titles = self.playlist_titles();
+        let len = titles.len();
+        if len == 0 {
+            return;
+        }
+        if let Some(planned) = &self.planned_shuffle {
+            if *planned != self.current_song && titles.contains(&planned.as_str()) {
+                return;
             }
+        }
+        if len == 1 && titles[0] == self.current_song {
+            return;
+        }
+        let Some(idx) = self.pick_shuffle_index(&titles) else {
+            return;
+        };
+        self.planned_shuffle = Some(titles[idx].to_string());
+    }
+
+    /// 随机挑选下一首：避开当前歌曲和最近播放过的 `shuffle_avoid_recent` 首。
+    /// 列表较短时最多避开 `len - 1` 首，保证总有候选；只抽一次，不会在小列表上反复重试。
+    fn pick_shuffle_index(&self, titles: &[&str]) -> Option<usize> {
+        let len = titles.len();
+        if len == 0 {
+            return None;
+        }
+        let avoid_count = self.shuffle_avoid_recent.max(1).min(len - 1);
+        let mut avoid: Vec<&str> = Vec::with_capacity(avoid_count);
+        let recent = std::iter::once(self.current_song.as_str())
+            .chain(self.recent_plays.iter().rev().map(String::as_str))
+            .filter(|title| titles.contains(title));
+        for title in recent {
+            if avoid.len() >= avoid_count {
+                break;
+            }
+            if !avoid.contains(&title) {
+                avoid.push(title);
+            }
+        }
+        let candidates: Vec<usize> = (0..len).filter(|&i| !avoid.contains(&titles[i])).collect();
+        // 列表中有重名歌曲时候选可能为空，退回到完全随机
+        if candidates.is_empty() {
+            return Some(self.simple_random(len));
+        }
+        Some(candidates[self.simple_random(candidates.len())])
+    }
+
+    /// 不改变任何状态地预测自动播放的下一首，用于预取音频流。
+    /// 单曲循环时返回 None（当前歌曲已解析过）；随机模式只返回已预选的曲目。
+    /// 返回（标题, 搜索源, 本地缓存路径）。
+    pub fn peek_next_song(&self) -> Option<(String, String, Option<String>)> {
+        let titles = self.playlist_titles();
+        let idx = match self.play_mode {
+            PlayMode::Single => return None,
             PlayMode::Shuffle => {
-                let mut idx = self.simple_random(len);
-                if let Some(current_idx) = self
-                    .search_results
-                    .iter()
-                    .position(|item| item.title == self.current_song)
-                {
-                    if len > 1 {
-                        idx = self.simple_random(len - 1);
-                        if idx >= current_idx {
-                            idx += 1;
-                        }
-                    }
+                let planned = self.planned_shuffle.as_deref()?;
+                if planned == self.current_song {
+                    return None;
                 }
-                self.selected_search_result = idx;
-                Some((self.search_results[idx].title.clone(), None))
+                titles.iter().position(|t| *t == planned)?
             }
             PlayMode::ListLoop | PlayMode::Sequential => {
-                let current_song = self.current_song.clone();
-                if let Some(current_idx) = self
-                    .search_results
-                    .iter()
-                    .position(|item| item.title == current_song)
-                {
-                    let next_idx = current_idx + 1;
-                    if next_idx < len {
-                        self.selected_search_result = next_idx;
-                        Some((self.search_results[next_idx].title.clone(), None))
-                    } else if self.play_mode == PlayMode::ListLoop {
-                        self.selected_search_result = 0;
-                        self.add_log("列表循环，回到第一首 (搜索结果)".to_string());
-                        Some((self.search_results[0].title.clone(), None))
-                    } else {
-                        None
-                    }
+                let order = self.playlist_order();
+                let current = self.current_playlist_index()?;
+                let current_pos = order.iter().position(|&i| i == current)?;
+                if current_pos + 1 < order.len() {
+                    order[current_pos + 1]
+                } else if self.play_mode == PlayMode::ListLoop {
+                    *order.first()?
                 } else {
-                    self.add_log(format!("当前歌曲 '{}' 不在当前搜索结果中", current_song));
-                    None
+                    return None;
                 }
             }
+        };
+        let title = titles[idx].to_string();
+        let (source, local_path) = match self.playback_context {
+            PlaybackContext::SearchResults => (self.search_results[idx].source.clone(), None),
+            PlaybackContext::Favorites => {
+                let item = &self.active_items()[idx];
+                (item.source.clone(), item.local_path.clone())
+            }
+        };
+        Some((title, source, local_path))
+    }
+
+    /// 手动切歌（媒体键 / 上一首 / 下一首）：在当前播放列表中前后移动一首，首尾回绕。
+    /// 随机模式下“下一首”仍走随机选曲；单曲循环模式下按列表顺序切换，而不是重播当前歌曲。
+    pub fn step_song(&mut self, forward: bool) -> Option<(String, Option<String>)> {
+        if self.current_song.is_empty() {
+            return None;
+        }
+        if forward && self.play_mode == PlayMode::Shuffle {
+            return self.get_next_song();
         }
+
+        let order = self.playlist_order();
+        let len = order.len();
+        let current = self.current_playlist_index()?;
+        let current_pos = order.iter().position(|&i| i == current)?;
+        let idx = if forward {
+            order[(current_pos + 1) % len]
+        } else {
+            order[(current_pos + len - 1) % len]
+        };
+        Some(self.select_playlist_entry(idx))
+    }
+}
+
+/// 规范化标题用于比较：全角字母数字和符号转为半角，去掉首尾空白、合并连续空白并转为小写
+pub fn normalize_title(title: &str) -> String {
+    title
+        .chars()
+        .map(to_half_width)
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// 全角 ASCII 字符（`Ａ`、`（`）和全角空格转为对应的半角字符，其余字符不变
+fn to_half_width(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// 视频标题中常见的附加说明，按整词匹配（`believer` 不含噪声词 `live`）
+const TITLE_NOISE_WORDS: [&str; 16] = [
+    "official",
+    "video",
+    "audio",
+    "lyric",
+    "lyrics",
+    "mv",
+    "hd",
+    "4k",
+    "remaster",
+    "remastered",
+    "visualizer",
+    "live",
+    "feat",
+    "ft",
+    "ver",
+    "version",
+];
+
+/// 中文的附加说明没有空格分词，出现在词内即算噪声
+const TITLE_NOISE_CJK: [&str; 5] = ["官方", "歌词", "高清", "完整版", "字幕"];
+
+/// 尾缀中可以和噪声词一起出现的普通词，如 `Official Music Video`
+const TITLE_FILLER_WORDS: [&str; 3] = ["music", "full", "the"];
+
+/// 按空白和标点切分出的小写词，`m/v` 视为一个词 `mv`
+fn title_tokens(text: &str) -> Vec<String> {
+    text.replace("m/v", "mv")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn is_noise_token(token: &str) -> bool {
+    TITLE_NOISE_WORDS.contains(&token) || TITLE_NOISE_CJK.iter().any(|word| token.contains(word))
+}
+
+/// 括号段中只要有一个噪声词就整段视为噪声
+fn is_noise_segment(segment: &str) -> bool {
+    title_tokens(segment)
+        .iter()
+        .any(|token| is_noise_token(token))
+}
+
+/// ` - ` / ` | ` 之后的尾缀只由噪声词、填充词和数字组成时才视为噪声，
+/// 避免把 `Video Killed the Radio Star` 这样的歌名当作附加说明去掉
+fn is_noise_suffix(suffix: &str) -> bool {
+    let tokens = title_tokens(suffix);
+    tokens.iter().any(|token| is_noise_token(token))
+        && tokens.iter().all(|token| {
+            is_noise_token(token)
+                || TITLE_FILLER_WORDS.contains(&token.as_str())
+                || token.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+/// 模糊比较用的标题：在 [`normalize_title`] 基础上去掉含噪声词的括号段
+/// （`()`、`[]`、`【】`、`「」`）、` - Official Video` 这类以 ` - ` / ` | ` 分隔且只由噪声词组成的尾缀，
+/// 以及 ` feat. ...` / ` ft. ...` 引出的合作歌手
+pub fn fuzzy_title_key(title: &str) -> String {
+    let mut text = normalize_title(title);
+    for (open, close) in [('(', ')'), ('[', ']'), ('【', '】'), ('「', '」')] {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text.as_str();
+        while let Some(start) = rest.find(open) {
+            let Some(len) = rest[start..].find(close) else {
+                break;
+            };
+            let segment = &rest[start..start + len + close.len_utf8()];
+            result.push_str(&rest[..start]);
+            if !is_noise_segment(segment) {
+                result.push_str(segment);
+            }
+            rest = &rest[start + segment.len()..];
+        }
+        result.push_str(rest);
+        text = result;
+    }
+    for separator in [" - ", " | "] {
+        if let Some(pos) = text.rfind(separator) {
+            let suffix = &text[pos + separator.len()..];
+            if is_noise_suffix(suffix) {
+                text.truncate(pos);
+            }
+        }
+    }
+    for marker in [" feat. ", " ft. "] {
+        if let Some(pos) = text.find(marker) {
+            text.truncate(pos);
+        }
+    }
+    normalize_title(&text)
+}
+
+/// 按指定的严格程度比较两个标题
+pub fn titles_match(a: &str, b: &str, mode: TitleMatch) -> bool {
+    match mode {
+        TitleMatch::Exact => a == b,
+        TitleMatch::Normalized => normalize_title(a) == normalize_title(b),
+        TitleMatch::Fuzzy => a == b || fuzzy_title_key(a) == fuzzy_title_key(b),
+    }
+}
+
+/// 压入播放栈，超出上限时丢弃最旧的一首
+fn push_bounded(stack: &mut Vec<FavoriteItem>, item: FavoriteItem) {
+    stack.push(item);
+    if stack.len() > TRACK_STACK_MAX {
+        stack.remove(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::mock;
+    use crate::test_support::{test_app, EnvVars};
+
+    fn item(title: &str, source: &str) -> FavoriteItem {
+        FavoriteItem {
+            title: title.to_string(),
+            source: source.to_string(),
+            local_path: None,
+            display_name: None,
+            play_count: 0,
+            start_offset_secs: None,
+        }
+    }
+
+    /// 以搜索结果为播放列表的 App
+    fn with_results(name: &str, titles: &[&str]) -> App {
+        let mut app = test_app(name);
+        app.search_results = titles.iter().map(|t| mock::result(t, "yt")).collect();
+        app.playback_context = PlaybackContext::SearchResults;
+        app
+    }
+
+    /// 以当前收藏分组为播放列表的 App
+    fn with_favorites(name: &str, titles: &[&str]) -> App {
+        let mut app = test_app(name);
+        app.groups[0].items = titles.iter().map(|t| item(t, "yt")).collect();
+        app.playback_context = PlaybackContext::Favorites;
+        app
+    }
+
+    fn playing(app: &mut App, title: &str) {
+        app.current_song = title.to_string();
+        app.current_source = "yt".to_string();
+    }
+
+    fn next_title(app: &mut App) -> Option<String> {
+        app.get_next_song().map(|(title, _)| title)
+    }
+
+    #[test]
+    fn sequential_advances_and_stops_after_last() {
+        let mut app = with_results("mode-sequential", &["a", "b", "c"]);
+        app.play_mode = PlayMode::Sequential;
+        playing(&mut app, "a");
+        assert_eq!(next_title(&mut app).as_deref(), Some("b"));
+        assert_eq!(app.selected_search_result, 1);
+        playing(&mut app, "c");
+        assert_eq!(next_title(&mut app), None);
+    }
+
+    #[test]
+    fn list_loop_wraps_from_last_to_first() {
+        let mut app = with_favorites("mode-loop", &["a", "b", "c"]);
+        app.play_mode = PlayMode::ListLoop;
+        playing(&mut app, "b");
+        assert_eq!(next_title(&mut app).as_deref(), Some("c"));
+        playing(&mut app, "c");
+        assert_eq!(next_title(&mut app).as_deref(), Some("a"));
+        assert_eq!(app.selected_favorite, 0);
+    }
+
+    #[test]
+    fn single_repeats_current_song() {
+        let mut app = with_favorites("mode-single", &["a", "b"]);
+        app.play_mode = PlayMode::Single;
+        assert_eq!(next_title(&mut app), None);
+        playing(&mut app, "b");
+        assert_eq!(next_title(&mut app).as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn shuffle_never_repeats_current_song() {
+        let mut app = with_results("mode-shuffle", &["a", "b", "c"]);
+        app.play_mode = PlayMode::Shuffle;
+        for _ in 0..100 {
+            playing(&mut app, "b");
+            let next = next_title(&mut app).unwrap();
+            assert_ne!(next, "b");
+        }
+        // 只有一首时只能重播它
+        let mut app = with_favorites("mode-shuffle-one", &["a"]);
+        app.play_mode = PlayMode::Shuffle;
+        playing(&mut app, "a");
+        assert_eq!(next_title(&mut app).as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn empty_playlist_has_no_next_song() {
+        for mode in [PlayMode::Sequential, PlayMode::ListLoop, PlayMode::Shuffle] {
+            let mut app = with_results("mode-empty-results", &[]);
+            app.play_mode = mode;
+            playing(&mut app, "a");
+            assert_eq!(next_title(&mut app), None);
+
+            let mut app = with_favorites("mode-empty-favorites", &[]);
+            app.play_mode = mode;
+            playing(&mut app, "a");
+            assert_eq!(next_title(&mut app), None);
+        }
+    }
+
+    #[test]
+    fn song_outside_playlist_has_no_next_song() {
+        let mut app = with_favorites("mode-outside", &["a", "b"]);
+        app.play_mode = PlayMode::ListLoop;
+        playing(&mut app, "x");
+        assert_eq!(next_title(&mut app), None);
+    }
+
+    #[test]
+    fn manual_step_wraps_at_both_ends() {
+        for mode in [PlayMode::Sequential, PlayMode::ListLoop, PlayMode::Single] {
+            let mut app = with_favorites("mode-step", &["a", "b", "c"]);
+            app.play_mode = mode;
+            playing(&mut app, "a");
+            assert_eq!(app.step_song(false).map(|(t, _)| t).as_deref(), Some("c"));
+            playing(&mut app, "c");
+            assert_eq!(app.step_song(true).map(|(t, _)| t).as_deref(), Some("a"));
+        }
+        let mut app = with_results("mode-step-none", &["a"]);
+        assert_eq!(app.step_song(true), None);
+    }
+
+    #[test]
+    fn history_stack_walks_back_and_forward() {
+        let mut app = test_app("mode-history");
+        for title in ["a", "b", "c"] {
+            app.record_play(title, "yt", None);
+        }
+        let mut back = || {
+            let target = app.track_back().map(|item| item.title);
+            if let Some(title) = &target {
+                app.record_play(title, "yt", None);
+            }
+            target
+        };
+        assert_eq!(back().as_deref(), Some("b"));
+        assert_eq!(back().as_deref(), Some("a"));
+        // 已经退到最早的一首
+        assert_eq!(back(), None);
+
+        let target = app.track_forward().map(|item| item.title);
+        assert_eq!(target.as_deref(), Some("b"));
+        app.record_play("b", "yt", None);
+        // 回退后播放新歌曲会清空前进栈
+        app.record_play("d", "yt", None);
+        assert!(app.track_forward().is_none());
+        assert_eq!(
+            app.track_back().map(|item| item.title).as_deref(),
+            Some("b")
+        );
+    }
+
+    #[test]
+    fn same_title_from_two_sources_are_separate_favorites() {
+        let mut app = test_app("favorites-two-sources");
+        let sources = |groups: &[FavoriteGroup]| -> Vec<String> {
+            groups[0].items.iter().map(|i| i.source.clone()).collect()
+        };
+        for source in ["yt", "bili"] {
+            app.current_song = "song".to_string();
+            app.current_source = source.to_string();
+            app.toggle_favorite();
+        }
+        let (groups, warning) = App::load_favorites(&app.favorites_path);
+        assert!(warning.is_none());
+        assert_eq!(sources(&groups), ["yt", "bili"]);
+
+        app.current_source = "yt".to_string();
+        app.toggle_favorite();
+        let (groups, _) = App::load_favorites(&app.favorites_path);
+        assert_eq!(sources(&groups), ["bili"]);
+        assert_eq!(groups[0].items[0].title, "song");
+    }
+
+    #[test]
+    fn simple_random_is_roughly_uniform() {
+        let app = test_app("rng-uniform");
+        assert_ne!(App::seed_rng(), 0);
+        app.rng_state.set(0x2545_F491_4F6C_DD1D);
+        const BUCKETS: usize = 6;
+        const DRAWS: usize = 60_000;
+        let mut counts = [0usize; BUCKETS];
+        for _ in 0..DRAWS {
+            counts[app.simple_random(BUCKETS)] += 1;
+        }
+        let expected = DRAWS / BUCKETS;
+        for count in counts {
+            // 允许 ±5% 的偏差
+            assert!(count.abs_diff(expected) < expected / 20, "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn shuffle_avoids_recent_songs_when_possible() {
+        let mut app = with_results("rng-avoid-recent", &["a", "b", "c", "d", "e"]);
+        app.play_mode = PlayMode::Shuffle;
+        app.shuffle_avoid_recent = 2;
+        app.recent_plays = VecDeque::from(["a".to_string(), "b".to_string()]);
+        playing(&mut app, "c");
+        let titles = ["a", "b", "c", "d", "e"];
+        for _ in 0..200 {
+            let idx = app.pick_shuffle_index(&titles).unwrap();
+            // 当前歌曲 c 和最近播放的 b 被避开；a 超出了 2 首的范围
+            assert!(matches!(titles[idx], "a" | "d" | "e"), "{}", titles[idx]);
+        }
+
+        // 要避开的歌曲比列表长时，仍留下一首候选
+        app.shuffle_avoid_recent = 10;
+        let titles = ["a", "b", "c"];
+        for _ in 0..50 {
+            assert_eq!(app.pick_shuffle_index(&titles), Some(0));
+        }
+    }
+
+    #[test]
+    fn data_and_config_dirs_follow_xdg_env() {
+        let dir = crate::test_support::temp_dir("xdg-dirs");
+        let mut env = EnvVars::new();
+        env.set("HOME", Some(&dir.join("home")))
+            .set("XDG_CONFIG_HOME", Some(&dir.join("config")))
+            .set("XDG_DATA_HOME", Some(&dir.join("data")));
+        assert_eq!(crate::config::config_dir(), dir.join("config/maboroshi"));
+        assert_eq!(
+            crate::config::default_favorites_path(),
+            dir.join("data/maboroshi/favorites.json")
+        );
+
+        // 相对路径不符合 XDG 规范，与未设置一样回落到 home 下
+        env.set("XDG_CONFIG_HOME", Some(Path::new("relative")))
+            .set("XDG_DATA_HOME", None);
+        assert_eq!(
+            crate::config::config_dir(),
+            dir.join("home/.config/maboroshi")
+        );
+        assert_eq!(
+            crate::config::default_favorites_path(),
+            dir.join("home/.maboroshi_favorites.json")
+        );
+    }
+
+    #[test]
+    fn legacy_favorites_move_to_xdg_data_dir() {
+        let dir = crate::test_support::temp_dir("xdg-migrate");
+        let legacy = dir.join("home/.maboroshi_favorites.json");
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        fs::write(&legacy, "legacy").unwrap();
+        let mut env = EnvVars::new();
+        env.set("HOME", Some(&dir.join("home")))
+            .set("XDG_DATA_HOME", Some(&dir.join("data")));
+
+        let target = crate::config::default_favorites_path();
+        assert!(App::migrate_legacy_favorites(&target).is_some());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "legacy");
+        assert!(!legacy.exists());
+        // 已迁移过，不再处理
+        assert!(App::migrate_legacy_favorites(&target).is_none());
+
+        // 新位置已有文件时保留两边的文件
+        fs::write(&legacy, "newer legacy").unwrap();
+        assert!(App::migrate_legacy_favorites(&target).is_none());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "legacy");
+        assert!(legacy.exists());
+    }
+
+    #[test]
+    fn legacy_favorites_stay_without_xdg_data_home() {
+        let dir = crate::test_support::temp_dir("xdg-no-migrate");
+        let legacy = dir.join("home/.maboroshi_favorites.json");
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        fs::write(&legacy, "legacy").unwrap();
+        let mut env = EnvVars::new();
+        env.set("HOME", Some(&dir.join("home")))
+            .set("XDG_DATA_HOME", None);
+
+        let target = crate::config::default_favorites_path();
+        assert_eq!(target, legacy);
+        assert!(App::migrate_legacy_favorites(&target).is_none());
+        assert!(legacy.exists());
+    }
+
+    #[test]
+    fn fuzzy_titles_match_common_variants() {
+        let pairs = [
+            (
+                "YOASOBI - 夜に駆ける",
+                "YOASOBI - 夜に駆ける (Official Video)",
+            ),
+            ("Lemon", "Lemon [MV]"),
+            ("Lemon", "Lemon - Official Music Video"),
+            ("周杰伦 - 晴天", "周杰伦 - 晴天【高清歌词版】"),
+            ("Stay", "Stay (feat. Justin Bieber)"),
+            ("Stay", "Stay ft. Justin Bieber"),
+            ("Yesterday", "Yesterday - Remastered 2009"),
+            ("晴天", "晴天 | 官方MV"),
+            ("Lemon", "Lemon (M/V)"),
+            ("ＬＥＭＯＮ　（ＭＶ）", "lemon"),
+            ("  Hello   World ", "hello world"),
+        ];
+        for (a, b) in pairs {
+            assert!(titles_match(a, b, TitleMatch::Fuzzy), "{} / {}", a, b);
+        }
+    }
+
+    #[test]
+    fn different_songs_do_not_match() {
+        let pairs = [
+            ("Lemon", "Lemon Tree"),
+            ("Hello", "Hello (Adele)"),
+            ("Stay", "Stay With Me"),
+            ("夜に駆ける", "群青"),
+            ("Song - Part 1", "Song - Part 2"),
+            ("Artist - Believer", "Artist - Olive"),
+            ("Song (Childhood)", "Song"),
+            ("X - Video Killed the Radio Star", "X"),
+        ];
+        for (a, b) in pairs {
+            assert!(!titles_match(a, b, TitleMatch::Fuzzy), "{} / {}", a, b);
+        }
+    }
+
+    #[test]
+    fn strictness_modes() {
+        assert_eq!(normalize_title(" ＡＢＣ\u{3000} Def "), "abc def");
+        assert!(titles_match("Lemon", "Lemon", TitleMatch::Exact));
+        assert!(!titles_match("Lemon", "lemon", TitleMatch::Exact));
+        assert!(titles_match("Lemon", " LEMON ", TitleMatch::Normalized));
+        assert!(titles_match("Ｌｅｍｏｎ", "lemon", TitleMatch::Normalized));
+        assert!(!titles_match("Lemon", "Lemon [MV]", TitleMatch::Normalized));
+    }
+
+    #[test]
+    fn favorites_path_resolution() {
+        let dir = crate::test_support::temp_dir("favorites-path");
+        let mut env = EnvVars::new();
+        env.set("HOME", Some(&dir.join("home")))
+            .set("XDG_CONFIG_HOME", Some(&dir.join("config")))
+            .set("XDG_DATA_HOME", Some(&dir.join("data")));
+
+        let absolute = dir.join("elsewhere/favorites.json");
+        assert_eq!(
+            App::resolve_favorites_path(&absolute.to_string_lossy()),
+            absolute
+        );
+        assert_eq!(
+            App::resolve_favorites_path("~/music/favorites.json"),
+            dir.join("home/music/favorites.json")
+        );
+        // 相对路径以配置文件所在目录为基准，而不是当前工作目录
+        assert_eq!(
+            App::resolve_favorites_path("./favorites.json"),
+            dir.join("config/maboroshi/./favorites.json")
+        );
+        assert_eq!(
+            App::resolve_favorites_path(" lists/favorites.json "),
+            dir.join("config/maboroshi/lists/favorites.json")
+        );
+        for default in ["", crate::config::DEFAULT_FAVORITES_FILE] {
+            assert_eq!(
+                App::resolve_favorites_path(default),
+                dir.join("data/maboroshi/favorites.json")
+            );
+        }
+    }
+
+    #[test]
+    fn page_prefetch_skips_cached_and_out_of_range_pages() {
+        let mut app = with_results("paging-prefetch", &["a", "b"]);
+        app.total_pages = usize::MAX;
+        assert!(app.begin_page_prefetch(2));
+        // 同一页正在预取时不重复发起
+        assert!(!app.begin_page_prefetch(2));
+        app.finish_page_prefetch(2);
+        app.cache_page(2, vec![mock::result("c", "yt")]);
+        assert!(!app.begin_page_prefetch(2));
+
+        // 同一页再次写入缓存时替换而不是追加
+        app.cache_page(2, vec![mock::result("c", "yt")]);
+        assert_eq!(app.get_cached_page(2).map(Vec::len), Some(1));
+
+        app.total_pages = 2;
+        assert!(!app.begin_page_prefetch(3));
     }
 }
@@ -0,0 +1,198 @@
+//! 无界面命令行子命令：
+//! - `maboroshi play <关键词>`：直接解析并播放，进度输出到终端，播放结束后退出
+//! - `maboroshi --search <关键词>`：搜索并以 JSON 数组输出结果，不启动界面
+//! - `maboroshi --list-audio-devices`：列出 mpv 可用的音频输出设备
+//! - `maboroshi --stats`：显示累计收听时长、播放曲目数和最常播放的收藏
+
+use crate::app::App;
+use crate::config::Config;
+use crate::net::{self, AudioBackend, PauseState, PlayOptions};
+use crate::stats::{format_listening_time, ListeningStats};
+use anyhow::Result;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// 进度刷新间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 执行 `play` 子命令。`args` 为 `play` 之后的参数；`dry_run` 时只打印命令行并模拟播放。
+pub async fn play(args: &[String], dry_run: bool) -> Result<()> {
+    let mut source = None;
+    let mut words = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--source" {
+            match iter.next() {
+                Some(s) => source = Some(s.clone()),
+                None => anyhow::bail!("--source 缺少参数"),
+            }
+        } else {
+            words.push(arg.as_str());
+        }
+    }
+    let keyword = words.join(" ");
+    if keyword.trim().is_empty() {
+        anyhow::bail!("用法: maboroshi play <关键词> [--source <源>]");
+    }
+
+    let (mut config, config_warn) = Config::load_with_warning();
+    if let Some(warn) = config_warn {
+        eprintln!("⚠ 配置警告: {}", warn);
+    }
+    if let Some(source) = source {
+        config.search.source = source;
+    }
+    if let Some(warn) = config.use_pid_socket_path() {
+        eprintln!("{}", warn);
+    }
+
+    let source = config.search.source.clone();
+    let audio = AudioBackend::new(config).with_dry_run(dry_run);
+    println!("🔍 正在解析: {}", keyword);
+    audio
+        .search_and_play(&keyword, &source, None, PlayOptions::default(), |log| {
+            eprintln!("[yt-dlp] {}", log)
+        })
+        .await?;
+
+    let mut signals = crate::signals::spawn();
+    tokio::select! {
+        _ = report_progress(&audio, &keyword) => {}
+        _ = signals.recv() => {
+            println!("\n⏹ 已中断");
+        }
+    }
+
+    audio.quit().await;
+    println!();
+    Ok(())
+}
+
+/// 持续打印播放进度，直到 mpv 停止
+async fn report_progress(audio: &AudioBackend, keyword: &str) {
+    let mut ended = audio.subscribe_playback_ended();
+    loop {
+        let state = audio.get_pause_state().await;
+        if state == PauseState::Stopped {
+            break;
+        }
+        let icon = if state == PauseState::Paused {
+            "⏸"
+        } else {
+            "▶"
+        };
+        for error in audio.take_mpv_errors().await {
+            eprintln!("\r{}\x1b[K", error);
+        }
+        let buffering = match audio.get_buffering().await {
+            Some(pct) => format!("  ⟳ 缓冲中 {}%", pct),
+            None => String::new(),
+        };
+        print!(
+            "\r{} {}  {:>3.0}%{}\x1b[K",
+            icon,
+            keyword,
+            audio.get_progress().await * 100.0,
+            buffering
+        );
+        let _ = io::stdout().flush();
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = ended.changed() => {}
+        }
+    }
+}
+
+/// 执行 `--search` 子命令：按配置的 `max_results` / `list_timeout` 搜索，结果以 JSON 数组输出到 stdout。
+/// `args` 为 `--search` 之后的参数；`--json` 为默认（也是唯一）的输出格式。
+/// `dry_run` 时把 yt-dlp 命令行打印到 stderr，输出假结果。
+pub async fn search(args: &[String], dry_run: bool) -> Result<()> {
+    let mut source = None;
+    let mut words = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--source" => match iter.next() {
+                Some(s) => source = Some(s.clone()),
+                None => anyhow::bail!("--source 缺少参数"),
+            },
+            "--json" => {}
+            _ => words.push(arg.as_str()),
+        }
+    }
+    let keyword = words.join(" ");
+    if keyword.trim().is_empty() {
+        anyhow::bail!("用法: maboroshi --search <关键词> [--source <源>] [--json]");
+    }
+
+    let (mut config, config_warn) = Config::load_with_warning();
+    if let Some(warn) = config_warn {
+        eprintln!("⚠ 配置警告: {}", warn);
+    }
+    if let Some(source) = source {
+        config.search.source = source;
+    }
+
+    // stdout 只输出 JSON，yt-dlp 的日志不打印；演练模式的命令行打印到 stderr
+    let audio = AudioBackend::new(config).with_dry_run(dry_run);
+    let results = audio
+        .search(&keyword, 1, |log| {
+            if log.starts_with(net::DRY_RUN_PREFIX) {
+                eprintln!("{}", log);
+            }
+        })
+        .await?;
+    println!("{}", serde_json::to_string_pretty(&results)?);
+    Ok(())
+}
+
+/// `--stats` 列出的最常播放收藏数
+const STATS_TOP_FAVORITES: usize = 10;
+
+/// 执行 `--stats`：读取收听统计和收藏文件并打印。`favorites_override` 为 `--favorites` 指定的路径
+pub fn stats(favorites_override: Option<&str>) {
+    let (config, config_warn) = Config::load_with_warning();
+    if let Some(warn) = config_warn {
+        eprintln!("⚠ 配置警告: {}", warn);
+    }
+    let (stats, stats_warn) = ListeningStats::load();
+    if let Some(warn) = stats_warn {
+        eprintln!("⚠ {}", warn);
+    }
+    let favorites_file = favorites_override.unwrap_or(&config.paths.favorites_file);
+    let (groups, favorites_warn) = App::read_favorites(favorites_file);
+    if let Some(warn) = favorites_warn {
+        eprintln!("⚠ {}", warn);
+    }
+
+    println!("累计收听: {}", format_listening_time(stats.total_secs));
+    println!("播放曲目: {} 首", stats.tracks_played);
+    let top = App::top_played(&groups, STATS_TOP_FAVORITES);
+    if top.is_empty() {
+        println!("\n收藏还没有播放记录");
+        return;
+    }
+    println!("\n最常播放的收藏:");
+    for (i, item) in top.iter().enumerate() {
+        println!("  {:>2}. {} ({} 次)", i + 1, item.label(), item.play_count);
+    }
+}
+
+/// 执行 `--list-audio-devices`：列出 mpv 可用的音频输出设备
+pub async fn list_audio_devices() -> Result<()> {
+    let devices = net::list_audio_devices().await?;
+    if devices.is_empty() {
+        println!("未检测到音频输出设备");
+        return Ok(());
+    }
+    let width = devices
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    println!("可用的音频输出设备（填写到配置 [playback] audio_device）：\n");
+    for (name, desc) in devices {
+        println!("  {:<width$}  {}", name, desc, width = width);
+    }
+    Ok(())
+}
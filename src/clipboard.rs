@@ -0,0 +1,57 @@
+//! 剪贴板：把文本交给系统自带的剪贴板命令（macOS `pbcopy`、Wayland `wl-copy`、
+//! X11 `xclip` / `xsel`、Windows `clip`），依次尝试，使用第一个可用的。
+//!
+//! 仅在启用 `clipboard` feature 时可用；其余情况下 [`copy`] 直接返回错误说明。
+
+/// 候选的剪贴板命令及参数，按顺序尝试
+#[cfg(feature = "clipboard")]
+const COMMANDS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip", &[]),
+];
+
+/// 把 `text` 写入系统剪贴板。找不到可用的剪贴板命令或命令执行失败时返回错误说明
+#[cfg(feature = "clipboard")]
+pub async fn copy(text: &str) -> Result<(), String> {
+    use std::process::Stdio;
+    use tokio::io::AsyncWriteExt;
+    use tokio::process::Command;
+
+    for (program, args) in COMMANDS {
+        let mut child = match Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("{} 启动失败: {}", program, e)),
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .await
+                .map_err(|e| format!("写入 {} 失败: {}", program, e))?;
+        }
+        let status = child
+            .wait()
+            .await
+            .map_err(|e| format!("{} 执行失败: {}", program, e))?;
+        return if status.success() {
+            Ok(())
+        } else {
+            Err(format!("{} 退出状态 {}", program, status))
+        };
+    }
+    Err("未找到剪贴板命令（pbcopy / wl-copy / xclip / xsel / clip）".to_string())
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub async fn copy(_text: &str) -> Result<(), String> {
+    Err("编译时未启用 clipboard feature".to_string())
+}
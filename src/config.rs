@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 /// 返回当前用户的 home 目录。Unix 读 `HOME`，Windows 读 `USERPROFILE`。
 /// 都读不到时回落到当前工作目录（`.`）。
@@ -15,7 +17,39 @@ pub fn home_dir() -> PathBuf {
         .unwrap_or_else(|| PathBuf::from("."))
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// 展开开头的 `~` 为 [`home_dir`]；不以 `~` 开头的路径原样返回
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix('~') {
+        let rest = rest.strip_prefix(['/', '\\']).unwrap_or(rest);
+        home_dir().join(rest)
+    } else {
+        PathBuf::from(path)
+    }
+}
+
+/// 读取 XDG 基础目录环境变量。按规范只接受非空的绝对路径，否则回落到 home 下的 `fallback`
+fn xdg_base_dir(var: &str, fallback: &str) -> PathBuf {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home_dir().join(fallback))
+}
+
+/// 配置及其它持久化状态（会话等）所在目录：`$XDG_CONFIG_HOME/maboroshi`，未设置时为 `~/.config/maboroshi`
+pub fn config_dir() -> PathBuf {
+    xdg_base_dir("XDG_CONFIG_HOME", ".config").join("maboroshi")
+}
+
+/// 设置了 `XDG_DATA_HOME` 时的数据目录 `$XDG_DATA_HOME/maboroshi`；未设置时返回 None，
+/// 数据文件沿用 home 下的旧位置
+pub fn xdg_data_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .map(|p| p.join("maboroshi"))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub search: SearchConfig,
@@ -27,25 +61,61 @@ pub struct Config {
     pub playback: PlaybackConfig,
     #[serde(default)]
     pub paths: PathsConfig,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SearchConfig {
+    /// 搜索和解析播放地址的后端，目前只支持 `yt-dlp`；修改后需重启生效
+    #[serde(default = "default_search_backend")]
+    pub backend: String,
     #[serde(default = "default_search_source")]
     pub source: String,
     #[serde(default = "default_max_results")]
     pub max_results: usize,
-    #[serde(default = "default_search_timeout")]
-    pub timeout: u64,
+    /// 已弃用：同时作为搜索列表和解析歌曲的超时（秒），仅在未设置下面两项时使用
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// 搜索列表（关键词搜索、解析播放列表 URL）的超时（秒），未设置时沿用 `timeout`
+    #[serde(default)]
+    pub list_timeout: Option<u64>,
+    /// 解析单首歌曲播放地址的超时（秒），未设置时沿用 `timeout`
+    #[serde(default)]
+    pub resolve_timeout: Option<u64>,
+    /// cookies 来源：`browser`（`--cookies-from-browser`）、`file`（`--cookies`）或 `none`。
+    /// 留空时按 `cookies_browser` / `cookies_file` 是否填写决定，两者可同时生效。
+    #[serde(default)]
+    pub cookies_mode: String,
     #[serde(default = "default_cookies_browser")]
     pub cookies_browser: String,
     /// 预先导出的 cookies.txt 文件路径（支持 `~` 展开）。非空时会追加 yt-dlp 的 `--cookies` 参数，
     /// 可与 `cookies_browser` 同时使用。
     #[serde(default = "default_cookies_file")]
     pub cookies_file: String,
+    /// 解析音频流时传给 yt-dlp 的 `-f` 参数
+    #[serde(default = "default_audio_format")]
+    pub audio_format: String,
+    /// 按搜索源覆盖 `audio_format`，例如 `{ bili = "bestaudio[ext=m4a]" }`
+    #[serde(default)]
+    pub audio_format_by_source: HashMap<String, String>,
+    /// 按搜索源指定 yt-dlp 搜索前缀，例如 `{ sc = "scsearch" }`；
+    /// 未列出的源在源名后追加 `search`
+    #[serde(default)]
+    pub prefix_overrides: HashMap<String, String>,
+    /// 搜索结果标题中包含任一关键词（不区分大小写）时过滤掉，例如 `["karaoke", "1 hour"]`
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+    /// 在搜索结果中按 `v` 试听的时长（秒），到时自动停止
+    #[serde(default = "default_preview_secs")]
+    pub preview_secs: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CacheConfig {
     #[serde(default = "default_cache_size")]
     pub url_cache_size: usize,
@@ -56,13 +126,19 @@ pub struct CacheConfig {
     pub offline_audio: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NetworkConfig {
     #[serde(default = "default_play_timeout")]
     pub play_timeout: u64,
+    /// 歌曲启动失败时的最大重试次数（指数退避），0 表示不重试
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// HTTP 状态接口端口（需启用 `http` feature），0 表示不启用；修改需重启
+    #[serde(default)]
+    pub http_port: u16,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PlaybackConfig {
     #[serde(default = "default_play_mode")]
     pub default_mode: String,
@@ -70,9 +146,67 @@ pub struct PlaybackConfig {
     pub seek_seconds: i32,
     #[serde(default = "default_volume_step")]
     pub volume_step: i32,
+    /// 启动时恢复上次退出时的歌曲、音量和播放模式，并自动继续播放
+    #[serde(default)]
+    pub resume_last: bool,
+    /// 启动后自动搜索的关键词；恢复了上次会话时不执行
+    #[serde(default)]
+    pub autoplay_query: Option<String>,
+    /// 配合 `autoplay_query`：为 true 时直接播放第一个匹配结果，否则只显示搜索结果
+    #[serde(default)]
+    pub autostart: bool,
+    /// mpv 音频输出设备（`--audio-device`），可通过 `maboroshi --list-audio-devices` 查看
+    #[serde(default)]
+    pub audio_device: Option<String>,
+    /// 追加到 mpv 命令行的额外参数，例如 `["--audio-channels=stereo", "--cache-secs=60"]`
+    #[serde(default)]
+    pub mpv_args: Vec<String>,
+    /// mpv 预读缓冲的秒数（`--cache-secs`），未设置时使用 mpv 默认值
+    #[serde(default)]
+    pub cache_secs: Option<u64>,
+    /// mpv 预读缓冲的最大内存（MiB，`--demuxer-max-bytes`），未设置时使用 mpv 默认值
+    #[serde(default)]
+    pub cache_size: Option<u64>,
+    /// 自动切换到下一首时的淡入淡出时长（秒），0 表示直接切换
+    #[serde(default)]
+    pub crossfade_secs: u64,
+    /// 每首歌开始播放时从 0 淡入到当前音量的时长（秒），0 表示不淡入；自动切歌的淡入淡出优先
+    #[serde(default)]
+    pub fade_in_secs: u64,
+    /// 播放列表播完、没有下一首时的动作：stop / loop / shuffle / quit
+    #[serde(default = "default_on_queue_end")]
+    pub on_queue_end: String,
+    /// 均衡器预设：flat / bass / vocal / treble，运行中按 E 切换
+    #[serde(default = "default_equalizer")]
+    pub equalizer: String,
+    /// 画面显示方式：none 只播放音频 / window 由 mpv 打开视频窗口 / art 在终端内显示封面
+    #[serde(default = "default_video_mode")]
+    pub video_mode: String,
+    /// mpv 报告停止时，进度达到这个百分比才立即视为播放结束
+    #[serde(default = "default_end_threshold_pct")]
+    pub end_threshold_pct: f64,
+    /// 进度未到 `end_threshold_pct` 时，停止状态需连续出现的轮询次数，之后才切到下一首；
+    /// 用于忽略网络抖动时的短暂停止，1 表示立即切换
+    #[serde(default = "default_stop_confirm_ticks")]
+    pub stop_confirm_ticks: u32,
+    /// 启动时的初始音量（0–130），未设置时为 100
+    #[serde(default)]
+    pub volume: Option<u8>,
+    /// 音量上限（不超过 130）：加音量、设置音量和启动 mpv 时都不会超过这个值
+    #[serde(default = "default_max_volume")]
+    pub max_volume: u8,
+    /// 退出时把当前播放模式和音量写回配置文件（`default_mode` / `volume`）
+    #[serde(default)]
+    pub persist_runtime_state: bool,
+    /// 顺序播放 / 列表循环 / 上一首下一首是否按收藏列表当前的显示排序（按 o 切换）
+    #[serde(default)]
+    pub follow_favorite_sort: bool,
+    /// 随机播放时避开最近播放过的歌曲数，列表较短时自动减少
+    #[serde(default = "default_shuffle_avoid_recent")]
+    pub shuffle_avoid_recent: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PathsConfig {
     #[serde(default = "default_socket_path")]
     pub socket_path: String,
@@ -81,9 +215,172 @@ pub struct PathsConfig {
     /// 音频文件的本地缓存目录
     #[serde(default = "default_cache_dir")]
     pub cache_dir: String,
+    /// 按 w 保存当前歌曲时的下载目录
+    #[serde(default = "default_download_dir")]
+    pub download_dir: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiConfig {
+    /// 播放中按 q 时先弹出确认框，防止误触退出
+    #[serde(default)]
+    pub confirm_quit: bool,
+    /// 界面刷新 / 进度轮询间隔（毫秒），越小动画越流畅、CPU 占用越高
+    #[serde(default = "default_tick_ms")]
+    pub tick_ms: u64,
+    /// 在控制台标题行右侧显示当前本地时间（HH:MM）
+    #[serde(default)]
+    pub show_clock: bool,
+    /// 出错时在列表下方显示日志面板；关闭后列表占满高度，错误只在标题行提示（按 L 临时切换）
+    #[serde(default = "default_show_logs")]
+    pub show_logs: bool,
+    /// 空闲（未播放且一段时间没有按键）时在列表区域显示动画屏保，按任意键恢复
+    #[serde(default)]
+    pub screensaver: bool,
+    /// 判断当前歌曲是否已收藏时的标题比较方式：exact / normalized / fuzzy
+    #[serde(default = "default_title_match")]
+    pub title_match: String,
+    /// 在进度条右侧显示电平表（通过 mpv 的 lavfi `ebur128` 滤镜取得响度，从下一首开始生效）
+    #[serde(default)]
+    pub level_meter: bool,
+    /// 空闲时在标题行轮播使用提示
+    #[serde(default = "default_show_tips")]
+    pub show_tips: bool,
+    /// 自定义的提示列表，为空时使用内置提示
+    #[serde(default)]
+    pub tips: Vec<String>,
+}
+
+/// 内置的空闲提示
+const DEFAULT_TIPS: &[&str] = &[
+    "按 s 搜索歌曲，也可以直接粘贴 YouTube / Bilibili 链接",
+    "按 a 从选中的收藏开始播放全部",
+    "按 / 过滤收藏列表",
+    "按 o 切换收藏排序",
+    "按 m 切换播放模式",
+    "按 E 切换均衡器预设",
+    "按 h 查看播放历史",
+    "按 C 重新加载配置文件",
+    "按 ? 查看全部快捷键",
+];
+
+fn default_show_tips() -> bool {
+    true
+}
+
+fn default_title_match() -> String {
+    "fuzzy".to_string()
+}
+
+/// 当前歌曲与收藏标题的比较方式（`ui.title_match`）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TitleMatch {
+    /// 完全相同
+    Exact,
+    /// 忽略大小写、全角半角和多余空白
+    Normalized,
+    /// 在 normalized 的基础上去掉 "(Official Video)"、"[MV]"、"feat. ..." 之类的常见后缀
+    #[default]
+    Fuzzy,
+}
+
+impl UiConfig {
+    /// 解析 `title_match`，无法识别时返回 None（调用方按 fuzzy 处理）
+    pub fn title_match_mode(&self) -> Option<TitleMatch> {
+        match self.title_match.trim().to_lowercase().as_str() {
+            "exact" => Some(TitleMatch::Exact),
+            "normalized" => Some(TitleMatch::Normalized),
+            "fuzzy" => Some(TitleMatch::Fuzzy),
+            _ => None,
+        }
+    }
+
+    /// 实际轮播的提示：关闭 `show_tips` 时为空，`tips` 为空时使用内置提示
+    pub fn tip_list(&self) -> Vec<String> {
+        if !self.show_tips {
+            return Vec::new();
+        }
+        let custom: Vec<String> = self
+            .tips
+            .iter()
+            .map(|tip| tip.trim())
+            .filter(|tip| !tip.is_empty())
+            .map(str::to_string)
+            .collect();
+        if custom.is_empty() {
+            DEFAULT_TIPS.iter().map(|tip| tip.to_string()).collect()
+        } else {
+            custom
+        }
+    }
+}
+
+fn default_show_logs() -> bool {
+    true
+}
+
+/// 日志文件，默认不启用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// 日志文件路径，设置后每条界面日志都会带时间戳追加写入
+    #[serde(default)]
+    pub file: Option<String>,
+    /// 日志文件的大小上限（KB），超过后轮转为 `<文件名>.1`
+    #[serde(default = "default_log_max_size_kb")]
+    pub max_size_kb: u64,
+}
+
+fn default_log_max_size_kb() -> u64 {
+    1024
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            max_size_kb: default_log_max_size_kb(),
+        }
+    }
+}
+
+/// `ui.tick_ms` 的下限，避免主循环空转占满 CPU
+const MIN_TICK_MS: u64 = 50;
+
+impl UiConfig {
+    /// 实际使用的刷新间隔，不低于 50ms
+    pub fn tick_rate(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.tick_ms.max(MIN_TICK_MS))
+    }
+}
+
+/// 界面配色。`preset` 选择基础配色方案（neon / mono / solarized），
+/// 其余字段为 `#RRGGBB` 形式的十六进制颜色，留空则沿用预设中的对应颜色。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    #[serde(default = "default_theme_preset")]
+    pub preset: String,
+    #[serde(default)]
+    pub primary: Option<String>,
+    #[serde(default)]
+    pub secondary: Option<String>,
+    #[serde(default)]
+    pub success: Option<String>,
+    #[serde(default)]
+    pub selected_bg: Option<String>,
+    #[serde(default)]
+    pub warning: Option<String>,
+    #[serde(default)]
+    pub inactive: Option<String>,
+    /// 按搜索源覆盖来源标记的颜色，例如 `{ yt = "#FF4646", nico = "#AAAAAA" }`
+    #[serde(default)]
+    pub sources: HashMap<String, String>,
 }
 
 // Default values
+fn default_search_backend() -> String {
+    "yt-dlp".to_string()
+}
+
 fn default_search_source() -> String {
     "yt".to_string()
 }
@@ -92,10 +389,13 @@ fn default_max_results() -> usize {
     15
 }
 
-fn default_search_timeout() -> u64 {
-    30
+fn default_preview_secs() -> u64 {
+    15
 }
 
+/// 未设置任何超时配置时，搜索列表和解析歌曲使用的超时（秒）
+const DEFAULT_SEARCH_TIMEOUT: u64 = 30;
+
 fn default_cookies_browser() -> String {
     // Windows 下 Chrome 127+ 启用 App-Bound Encryption 后 yt-dlp 无法读取 cookie，
     // 默认留空（不使用 cookies），用户如需访问年龄限制内容可手动改为 firefox。
@@ -113,6 +413,10 @@ fn default_cookies_file() -> String {
     String::new()
 }
 
+fn default_audio_format() -> String {
+    "bestaudio".to_string()
+}
+
 fn default_cache_size() -> usize {
     30
 }
@@ -129,6 +433,10 @@ fn default_play_timeout() -> u64 {
     10
 }
 
+fn default_max_retries() -> u32 {
+    2
+}
+
 fn default_play_mode() -> String {
     "shuffle".to_string()
 }
@@ -141,6 +449,34 @@ fn default_volume_step() -> i32 {
     5
 }
 
+fn default_max_volume() -> u8 {
+    crate::net::VOLUME_MAX
+}
+
+fn default_on_queue_end() -> String {
+    "stop".to_string()
+}
+
+fn default_equalizer() -> String {
+    EqPreset::Flat.name().to_string()
+}
+
+fn default_video_mode() -> String {
+    "none".to_string()
+}
+
+fn default_end_threshold_pct() -> f64 {
+    98.0
+}
+
+fn default_stop_confirm_ticks() -> u32 {
+    2
+}
+
+fn default_shuffle_avoid_recent() -> usize {
+    5
+}
+
 pub fn default_socket_path() -> String {
     #[cfg(unix)]
     {
@@ -164,22 +500,101 @@ pub fn default_socket_path_with_pid(pid: u32) -> String {
     }
 }
 
+/// socket 路径所在目录不可用时的备用目录：优先 `$XDG_RUNTIME_DIR`，其次系统临时目录
+#[cfg(unix)]
+fn fallback_socket_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(std::iter::once(std::env::temp_dir()))
+        .find(|dir| dir_writable(dir))
+}
+
+/// 目录是否存在且可以创建文件（写入一个探测文件后立即删除）
+#[cfg(unix)]
+fn dir_writable(dir: &Path) -> bool {
+    if !dir.is_dir() {
+        return false;
+    }
+    let probe = dir.join(format!(".maboroshi-{}.probe", std::process::id()));
+    // create_new 不会跟随已存在的同名文件或符号链接
+    match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// 远程控制 socket 路径（与 mpv 的 IPC 端点分开），按 PID 区分多实例。
+/// 放在 mpv socket 所在目录，`socket_path` 应是 [`Config::use_pid_socket_path`] 处理后的路径
+#[cfg(unix)]
+pub fn control_socket_path(socket_path: &str, pid: u32) -> PathBuf {
+    Path::new(socket_path)
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .join(format!("maboroshi-{}-ctl.sock", pid))
+}
+
+/// `paths.favorites_file` 的默认值。取这个值时收藏文件的实际位置见 [`default_favorites_path`]
+pub const DEFAULT_FAVORITES_FILE: &str = "~/.maboroshi_favorites.json";
+
 fn default_favorites_file() -> String {
-    "~/.maboroshi_favorites.json".to_string()
+    DEFAULT_FAVORITES_FILE.to_string()
+}
+
+/// 默认收藏文件位置：设置了 `XDG_DATA_HOME` 时为 `$XDG_DATA_HOME/maboroshi/favorites.json`，
+/// 否则为 `~/.maboroshi_favorites.json`
+pub fn default_favorites_path() -> PathBuf {
+    xdg_data_dir()
+        .map(|dir| dir.join("favorites.json"))
+        .unwrap_or_else(legacy_favorites_path)
+}
+
+/// 旧版本固定使用的收藏文件位置
+pub fn legacy_favorites_path() -> PathBuf {
+    home_dir().join(".maboroshi_favorites.json")
 }
 
 fn default_cache_dir() -> String {
     "~/.cache/maboroshi/audio".to_string()
 }
 
+fn default_tick_ms() -> u64 {
+    200
+}
+
+fn default_download_dir() -> String {
+    "~/Music/maboroshi".to_string()
+}
+
+fn default_theme_preset() -> String {
+    "neon".to_string()
+}
+
 impl Default for SearchConfig {
     fn default() -> Self {
         Self {
+            backend: default_search_backend(),
             source: default_search_source(),
             max_results: default_max_results(),
-            timeout: default_search_timeout(),
+            timeout: None,
+            list_timeout: Some(DEFAULT_SEARCH_TIMEOUT),
+            resolve_timeout: Some(DEFAULT_SEARCH_TIMEOUT),
+            cookies_mode: String::new(),
             cookies_browser: default_cookies_browser(),
             cookies_file: default_cookies_file(),
+            audio_format: default_audio_format(),
+            audio_format_by_source: HashMap::new(),
+            prefix_overrides: HashMap::new(),
+            exclude_patterns: Vec::new(),
+            preview_secs: default_preview_secs(),
         }
     }
 }
@@ -198,6 +613,8 @@ impl Default for NetworkConfig {
     fn default() -> Self {
         Self {
             play_timeout: default_play_timeout(),
+            max_retries: default_max_retries(),
+            http_port: 0,
         }
     }
 }
@@ -208,6 +625,258 @@ impl Default for PlaybackConfig {
             default_mode: default_play_mode(),
             seek_seconds: default_seek_seconds(),
             volume_step: default_volume_step(),
+            resume_last: false,
+            autoplay_query: None,
+            autostart: false,
+            audio_device: None,
+            mpv_args: Vec::new(),
+            cache_secs: None,
+            cache_size: None,
+            crossfade_secs: 0,
+            fade_in_secs: 0,
+            on_queue_end: default_on_queue_end(),
+            equalizer: default_equalizer(),
+            video_mode: default_video_mode(),
+            end_threshold_pct: default_end_threshold_pct(),
+            stop_confirm_ticks: default_stop_confirm_ticks(),
+            volume: None,
+            max_volume: default_max_volume(),
+            persist_runtime_state: false,
+            follow_favorite_sort: false,
+            shuffle_avoid_recent: default_shuffle_avoid_recent(),
+        }
+    }
+}
+
+/// 由 maboroshi 自行管理的 mpv 选项，不能通过 `mpv_args` 覆盖
+const MANAGED_MPV_OPTIONS: [&str; 8] = [
+    "input-ipc-server",
+    "input-ipc-client",
+    "idle",
+    "video",
+    "vid",
+    "volume",
+    "mute",
+    "audio-device",
+];
+
+/// `playback.cache_secs` 的有效范围（秒）
+const CACHE_SECS_RANGE: (u64, u64) = (1, 3600);
+/// `playback.cache_size` 的有效范围（MiB）
+const CACHE_SIZE_RANGE: (u64, u64) = (1, 4096);
+
+impl SearchConfig {
+    /// 搜索列表实际使用的超时（秒）
+    pub fn list_timeout_secs(&self) -> u64 {
+        self.list_timeout
+            .or(self.timeout)
+            .unwrap_or(DEFAULT_SEARCH_TIMEOUT)
+    }
+
+    /// 解析歌曲实际使用的超时（秒）
+    pub fn resolve_timeout_secs(&self) -> u64 {
+        self.resolve_timeout
+            .or(self.timeout)
+            .unwrap_or(DEFAULT_SEARCH_TIMEOUT)
+    }
+
+    /// 标题是否命中 `exclude_patterns` 中的任一关键词
+    pub fn is_excluded(&self, title: &str) -> bool {
+        let title = title.to_lowercase();
+        self.exclude_patterns
+            .iter()
+            .map(|pattern| pattern.trim().to_lowercase())
+            .any(|pattern| !pattern.is_empty() && title.contains(&pattern))
+    }
+}
+
+/// 播放列表播完、没有下一首时的动作（`playback.on_queue_end`）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueEndAction {
+    /// 停止播放（默认）
+    Stop,
+    /// 从列表第一首重新开始
+    Loop,
+    /// 切换到随机播放继续
+    Shuffle,
+    /// 退出播放器
+    Quit,
+}
+
+/// 播放时的画面显示方式（`playback.video_mode`）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VideoMode {
+    /// 只播放音频（默认）
+    #[default]
+    None,
+    /// 不加 `--no-video`，由 mpv 打开视频窗口，需要图形环境
+    Window,
+    /// 下载缩略图，在“正在播放”视图中用终端图像协议显示
+    Art,
+}
+
+/// 内置的均衡器预设（`playback.equalizer`），通过 mpv 的 lavfi `equalizer` 滤镜实现
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EqPreset {
+    /// 不加滤镜（默认）
+    #[default]
+    Flat,
+    Bass,
+    Vocal,
+    Treble,
+}
+
+impl EqPreset {
+    /// 按 E 切换时的顺序
+    pub const ALL: [EqPreset; 4] = [
+        EqPreset::Flat,
+        EqPreset::Bass,
+        EqPreset::Vocal,
+        EqPreset::Treble,
+    ];
+
+    /// 配置文件中的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            EqPreset::Flat => "flat",
+            EqPreset::Bass => "bass",
+            EqPreset::Vocal => "vocal",
+            EqPreset::Treble => "treble",
+        }
+    }
+
+    /// 界面上显示的名称
+    pub fn label(self) -> &'static str {
+        match self {
+            EqPreset::Flat => "平直",
+            EqPreset::Bass => "低音增强",
+            EqPreset::Vocal => "人声",
+            EqPreset::Treble => "高音增强",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Self::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|p| *p == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// 各频段的 (中心频率 Hz, 增益 dB)
+    fn bands(self) -> &'static [(u32, i32)] {
+        match self {
+            EqPreset::Flat => &[],
+            EqPreset::Bass => &[(60, 6), (150, 4), (400, 1)],
+            EqPreset::Vocal => &[(250, -2), (1500, 3), (3000, 4), (8000, -1)],
+            EqPreset::Treble => &[(4000, 3), (8000, 5), (12000, 5)],
+        }
+    }
+
+    /// 传给 mpv `af` 的滤镜（不含标签）；flat 不需要滤镜，返回 None
+    pub fn af_filter(self) -> Option<String> {
+        let bands = self.bands();
+        if bands.is_empty() {
+            return None;
+        }
+        let chain: Vec<String> = bands
+            .iter()
+            .map(|(freq, gain)| format!("equalizer=f={}:t=o:w=1:g={}", freq, gain))
+            .collect();
+        Some(format!("lavfi=[{}]", chain.join(",")))
+    }
+}
+
+impl PlaybackConfig {
+    /// 实际生效的音量上限：`max_volume` 截断到 mpv 允许的 130
+    pub fn volume_ceiling(&self) -> u8 {
+        self.max_volume.min(crate::net::VOLUME_MAX)
+    }
+
+    /// 解析 `on_queue_end`，无法识别时返回 None（调用方按 stop 处理）
+    pub fn queue_end_action(&self) -> Option<QueueEndAction> {
+        match self.on_queue_end.trim().to_lowercase().as_str() {
+            "stop" => Some(QueueEndAction::Stop),
+            "loop" => Some(QueueEndAction::Loop),
+            "shuffle" => Some(QueueEndAction::Shuffle),
+            "quit" => Some(QueueEndAction::Quit),
+            _ => None,
+        }
+    }
+
+    /// 解析 `video_mode`，无法识别时返回 None（调用方按 none 处理）
+    pub fn video_mode(&self) -> Option<VideoMode> {
+        match self.video_mode.trim().to_lowercase().as_str() {
+            "none" => Some(VideoMode::None),
+            "window" => Some(VideoMode::Window),
+            "art" => Some(VideoMode::Art),
+            _ => None,
+        }
+    }
+
+    /// 解析 `equalizer`，无法识别时返回 None（调用方按 flat 处理）
+    pub fn eq_preset(&self) -> Option<EqPreset> {
+        EqPreset::from_name(&self.equalizer)
+    }
+
+    /// 拆分 `mpv_args`：返回 (可以追加的参数, 被忽略的参数)。
+    /// 与内部管理的选项冲突、或不是 `--` 开头的参数（会被 mpv 当作播放文件）都会被忽略。
+    pub fn mpv_extra_args(&self) -> (Vec<String>, Vec<String>) {
+        self.mpv_args
+            .iter()
+            .map(|arg| arg.trim())
+            .filter(|arg| !arg.is_empty())
+            .map(str::to_string)
+            .partition(|arg| {
+                let Some(option) = arg.strip_prefix("--") else {
+                    return false;
+                };
+                let name = option.split('=').next().unwrap_or(option);
+                let name = name.strip_prefix("no-").unwrap_or(name);
+                let overridden = match name {
+                    "cache-secs" => self.cache_secs.is_some(),
+                    "demuxer-max-bytes" => self.cache_size.is_some(),
+                    _ => false,
+                };
+                !MANAGED_MPV_OPTIONS.contains(&name) && !overridden
+            })
+    }
+
+    /// 限制到有效范围后的 (`cache_secs`, `cache_size`)，以及超出范围时的提示
+    pub fn cache_settings(&self) -> (Option<u64>, Option<u64>, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut clamp = |key: &str, value: Option<u64>, (min, max): (u64, u64), unit: &str| {
+            value.map(|v| {
+                let clamped = v.clamp(min, max);
+                if clamped != v {
+                    warnings.push(format!(
+                        "playback.{} = {} 超出范围 {}–{}{}，已按 {}{} 处理",
+                        key, v, min, max, unit, clamped, unit
+                    ));
+                }
+                clamped
+            })
+        };
+        let secs = clamp("cache_secs", self.cache_secs, CACHE_SECS_RANGE, " 秒");
+        let size = clamp("cache_size", self.cache_size, CACHE_SIZE_RANGE, " MiB");
+        (secs, size, warnings)
+    }
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            confirm_quit: false,
+            tick_ms: default_tick_ms(),
+            show_clock: false,
+            show_logs: default_show_logs(),
+            screensaver: false,
+            title_match: default_title_match(),
+            level_meter: false,
+            show_tips: default_show_tips(),
+            tips: Vec::new(),
         }
     }
 }
@@ -218,13 +887,71 @@ impl Default for PathsConfig {
             socket_path: default_socket_path(),
             favorites_file: default_favorites_file(),
             cache_dir: default_cache_dir(),
+            download_dir: default_download_dir(),
         }
     }
 }
 
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            preset: default_theme_preset(),
+            primary: None,
+            secondary: None,
+            success: None,
+            selected_bg: None,
+            warning: None,
+            inactive: None,
+            sources: HashMap::new(),
+        }
+    }
+}
+
+/// `--config` 指定的配置文件，设置后加载、重新加载和写回配置都使用这个文件
+static CONFIG_PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// 设置表中的值；键已存在时沿用原值前后的空白和行尾注释
+fn set_keep_decor(table: &mut dyn toml_edit::TableLike, key: &str, mut value: toml_edit::Value) {
+    if let Some(old) = table.get(key).and_then(|item| item.as_value()) {
+        *value.decor_mut() = old.decor().clone();
+    }
+    table.insert(key, toml_edit::Item::Value(value));
+}
+
 impl Config {
     fn get_config_path() -> PathBuf {
-        home_dir().join(".config/maboroshi/config.toml")
+        CONFIG_PATH_OVERRIDE
+            .get()
+            .cloned()
+            .unwrap_or_else(|| config_dir().join("config.toml"))
+    }
+
+    /// 当前使用的配置文件路径（`--config` 指定的文件或默认位置）
+    pub fn config_file_path() -> PathBuf {
+        Self::get_config_path()
+    }
+
+    /// 当前使用的配置文件所在目录，配置中的相对路径以它为基准
+    pub fn config_file_dir() -> PathBuf {
+        Self::get_config_path()
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(config_dir)
+    }
+
+    /// 改用指定的配置文件（`--config`），只在启动时调用一次。文件不存在时返回错误说明
+    pub fn use_config_path(path: PathBuf) -> Result<(), String> {
+        if !path.is_file() {
+            return Err(format!("配置文件不存在: {}", path.display()));
+        }
+        CONFIG_PATH_OVERRIDE
+            .set(path)
+            .map_err(|_| "配置文件路径已设置".to_string())
+    }
+
+    /// 是否通过 `--config` 指定了配置文件
+    pub fn has_config_override() -> bool {
+        CONFIG_PATH_OVERRIDE.get().is_some()
     }
 
     #[allow(dead_code)]
@@ -253,6 +980,79 @@ impl Config {
         (Config::default(), None)
     }
 
+    /// 运行时重新读取配置文件。与 `load_with_warning` 不同，解析失败时返回错误，
+    /// 由调用方保留当前配置；文件不存在时视为全部使用默认值。
+    pub fn reload() -> Result<Self, String> {
+        let config_path = Self::get_config_path();
+        match fs::read_to_string(&config_path) {
+            Ok(content) => toml::from_str::<Config>(&content)
+                .map_err(|e| format!("配置文件 {:?} 解析失败（{}）", config_path, e)),
+            Err(_) => Ok(Config::default()),
+        }
+    }
+
+    /// 把运行中修改的播放模式、音量和均衡器预设写回配置文件
+    /// （`playback.default_mode` / `playback.volume` / `playback.equalizer`）。
+    /// 在磁盘上的最新文件内容里只改写这三个键（缺少 `[playback]` 段时新建），
+    /// 文件中的注释、格式和其他字段原样保留。
+    pub fn persist_runtime_state(
+        play_mode: &str,
+        volume: u8,
+        equalizer: EqPreset,
+    ) -> Result<(), String> {
+        let config_path = Self::get_config_path();
+        let content = match fs::read_to_string(&config_path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("读取配置文件 {:?} 失败: {}", config_path, e)),
+        };
+        let mut doc = content
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| format!("配置文件 {:?} 解析失败（{}）", config_path, e))?;
+
+        let playback = doc
+            .entry("playback")
+            .or_insert_with(toml_edit::table)
+            .as_table_like_mut()
+            .ok_or_else(|| "配置文件中的 playback 不是表".to_string())?;
+        set_keep_decor(playback, "default_mode", play_mode.into());
+        set_keep_decor(playback, "volume", i64::from(volume).into());
+        set_keep_decor(playback, "equalizer", equalizer.name().into());
+
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+        }
+        fs::write(&config_path, doc.to_string()).map_err(|e| format!("写入配置文件失败: {}", e))
+    }
+
+    /// 与 `other` 相比发生变化的配置段，分为 (可立即生效, 需重启生效) 两组
+    pub fn changed_sections(&self, other: &Config) -> (Vec<&'static str>, Vec<&'static str>) {
+        let mut live = Vec::new();
+        if self.search != other.search {
+            live.push("search");
+        }
+        if self.cache != other.cache {
+            live.push("cache");
+        }
+        if self.network != other.network {
+            live.push("network");
+        }
+        if self.playback != other.playback {
+            live.push("playback");
+        }
+        if self.theme != other.theme {
+            live.push("theme");
+        }
+        if self.ui != other.ui {
+            live.push("ui");
+        }
+        let mut restart = Vec::new();
+        if self.paths != other.paths {
+            restart.push("paths");
+        }
+        (live, restart)
+    }
+
     pub fn save_example() -> Result<(), Box<dyn std::error::Error>> {
         let config_path = Self::get_config_path();
 
@@ -271,16 +1071,125 @@ impl Config {
         Ok(())
     }
 
-    pub fn get_search_prefix(&self) -> String {
-        // 如果 source 包含 "search" 后缀，直接使用
-        // 否则自动添加 "search" 后缀
-        // 例如: "youtube" -> "ytsearch", "bili" -> "bilisearch"
-        // 也支持直接指定: "ytsearch", "bilisearch" 等
-        let source = self.search.source.as_str();
-        if source.ends_with("search") {
-            source.to_string()
+    /// 未自定义 socket 路径时改用基于 PID 的 IPC 端点，避免多实例冲突
+    ///
+    /// 随后检查 socket 所在目录是否存在且可写（受限的沙箱环境中 `/tmp` 可能不可写），
+    /// 不满足时改用 `$XDG_RUNTIME_DIR` 或系统临时目录下的 `maboroshi-<PID>.sock`。
+    /// 发生回退或找不到可用目录时返回需要记录的提示
+    pub fn use_pid_socket_path(&mut self) -> Option<String> {
+        let pid = std::process::id();
+        if self.paths.socket_path == default_socket_path() {
+            self.paths.socket_path = default_socket_path_with_pid(pid);
+        }
+        #[cfg(unix)]
+        {
+            let path = PathBuf::from(&self.paths.socket_path);
+            let dir = path
+                .parent()
+                .filter(|d| !d.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            if dir_writable(dir) {
+                return None;
+            }
+            let reason = if dir.is_dir() {
+                "不可写"
+            } else {
+                "不存在"
+            };
+            let Some(base) = fallback_socket_dir() else {
+                return Some(format!(
+                    "❌ mpv socket 目录 {} {}，XDG_RUNTIME_DIR 和临时目录也不可用，播放可能失败",
+                    dir.display(),
+                    reason
+                ));
+            };
+            let fallback = base.join(format!("maboroshi-{}.sock", pid));
+            let message = format!(
+                "⚠ mpv socket 目录 {} {}，已改用 {}",
+                dir.display(),
+                reason,
+                fallback.display()
+            );
+            self.paths.socket_path = fallback.to_string_lossy().to_string();
+            Some(message)
+        }
+        #[cfg(windows)]
+        {
+            None
+        }
+    }
+
+    /// 解析逗号分隔的搜索源列表，例如 `"yt,bili"` -> `["yt", "bili"]`。
+    /// 列表为空时回退为默认源。
+    pub fn search_sources(&self) -> Vec<String> {
+        let sources: Vec<String> = self
+            .search
+            .source
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+        if sources.is_empty() {
+            vec![default_search_source()]
         } else {
-            format!("{}search", source)
+            sources
         }
     }
 }
+
+impl SearchConfig {
+    /// 搜索源使用的 yt-dlp 格式：优先 `audio_format_by_source` 中的设置
+    /// （`bili` 与 `bilisearch` 视为同一个源），否则使用 `audio_format`
+    pub fn audio_format_for(&self, source: &str) -> &str {
+        let source = source.trim();
+        self.audio_format_by_source
+            .get(source)
+            .or_else(|| {
+                source
+                    .strip_suffix("search")
+                    .and_then(|s| self.audio_format_by_source.get(s))
+            })
+            .or_else(|| {
+                self.audio_format_by_source
+                    .get(&format!("{}search", source))
+            })
+            .map(String::as_str)
+            .filter(|f| !f.trim().is_empty())
+            .unwrap_or(&self.audio_format)
+    }
+
+    /// 搜索源实际使用的 yt-dlp 搜索前缀：优先 `prefix_overrides` 中的设置（空值视为未设置），
+    /// 否则按 [`search_prefix`] 推导
+    pub fn prefix_for(&self, source: &str) -> String {
+        let source = source
+            .split(',')
+            .map(str::trim)
+            .find(|s| !s.is_empty())
+            .unwrap_or("yt");
+        self.prefix_overrides
+            .get(source)
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| search_prefix(source))
+    }
+}
+
+/// 将搜索源转换为 yt-dlp 搜索前缀；传入逗号分隔的列表时取第一个。
+pub fn search_prefix(source: &str) -> String {
+    // 如果 source 包含 "search" 后缀，直接使用
+    // 否则自动添加 "search" 后缀
+    // 例如: "youtube" -> "ytsearch", "bili" -> "bilisearch"
+    // 也支持直接指定: "ytsearch", "bilisearch" 等
+    let source = source
+        .split(',')
+        .map(str::trim)
+        .find(|s| !s.is_empty())
+        .unwrap_or("yt");
+    if source.ends_with("search") {
+        source.to_string()
+    } else {
+        format!("{}search", source)
+    }
+}
@@ -0,0 +1,99 @@
+//! 封面：`playback.video_mode = "art"` 时通过 curl 下载当前歌曲的缩略图，
+//! 用 iTerm2 的内联图片协议（OSC 1337）显示在“正在播放”视图中。
+//!
+//! 该协议支持 JPEG / PNG / WebP 等常见格式，无需在本地解码。iTerm2 与 WezTerm 支持该协议；
+//! 其余终端不显示封面，播放不受影响。
+
+use crate::app::App;
+use ratatui::layout::Rect;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// 封面图片的最大字节数，超出时放弃显示（避免把过大的图片写进终端）
+const MAX_IMAGE_BYTES: usize = 2 * 1024 * 1024;
+
+/// 当前终端是否支持内联图片协议（按 `TERM_PROGRAM` / `LC_TERMINAL` 判断）
+pub fn supported() -> bool {
+    let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+    let lc_terminal = std::env::var("LC_TERMINAL").unwrap_or_default();
+    matches!(term_program.as_str(), "iTerm.app" | "WezTerm") || lc_terminal == "iTerm2"
+}
+
+/// 检查当前歌曲的封面：没有下载过时在后台下载，下载期间不阻塞播放
+pub async fn sync(app: &Arc<Mutex<App>>) {
+    let (title, url) = {
+        let mut app_lock = app.lock().await;
+        let title = app_lock.current_song.clone();
+        let Some(url) = app_lock.begin_cover(&title) else {
+            return;
+        };
+        (title, url)
+    };
+
+    let app = Arc::clone(app);
+    tokio::spawn(async move {
+        let result = fetch(&url).await;
+        let mut app_lock = app.lock().await;
+        match result {
+            Ok(data) => app_lock.finish_cover(&title, data),
+            Err(e) => app_lock.add_log(format!("⚠ 封面下载失败: {}", e)),
+        }
+    });
+}
+
+/// 通过 curl 下载图片
+async fn fetch(url: &str) -> anyhow::Result<Vec<u8>> {
+    let output = tokio::process::Command::new("curl")
+        .args(["-fsSL", "--max-time", "10", url])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    if output.stdout.len() > MAX_IMAGE_BYTES {
+        anyhow::bail!("图片过大 ({} KiB)", output.stdout.len() / 1024);
+    }
+    Ok(output.stdout)
+}
+
+/// 把图片放进 `area` 的转义序列（不含光标移动）。图片按宽高比缩放，
+/// 终端字符约为 1:2，因此宽度按高度的两倍计算，得到接近正方形的封面
+pub fn escape_sequence(data: &[u8], area: Rect) -> String {
+    let width = area.width.min(area.height.saturating_mul(2));
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+        data.len(),
+        width,
+        area.height,
+        base64(data)
+    )
+}
+
+/// 图片在 `area` 中水平居中后的左上角
+pub fn origin(area: Rect) -> (u16, u16) {
+    let width = area.width.min(area.height.saturating_mul(2));
+    (area.x + (area.width - width) / 2, area.y)
+}
+
+/// 标准 base64 编码（带 `=` 填充）
+fn base64(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(TABLE[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,170 @@
+//! HTTP 状态接口：启用 `http` feature 且 `network.http_port` 不为 0 时，在 `127.0.0.1:<port>` 上
+//! 提供只读的 JSON 播放状态，供家庭看板等监控工具轮询。
+//!
+//! `GET /` 或 `GET /status` 返回：
+//!
+//! ```json
+//! {"current_song": "...", "status": "playing", "progress": {"position": 12.3, "duration": 245.0},
+//!  "volume": 80, "play_mode": "shuffle", "source": "youtube"}
+//! ```
+//!
+//! 其余路径返回 404，非 GET 请求返回 405。未启用 feature 时 [`spawn`] 直接返回 None。
+
+use crate::app::{App, PlayerStatus};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// 请求头的最大长度（字节），超出时直接断开
+#[cfg_attr(not(feature = "http"), allow(dead_code))]
+const MAX_REQUEST_LEN: usize = 8 * 1024;
+
+/// 等待客户端发完请求头的最长时间，超时直接断开，避免空闲连接一直占用任务和 socket
+#[cfg_attr(not(feature = "http"), allow(dead_code))]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 正在运行的状态服务。drop 时停止监听并中止尚未结束的连接（退出播放器时随之关闭）。
+#[cfg_attr(not(feature = "http"), allow(dead_code))]
+pub struct StatusServer {
+    #[cfg(feature = "http")]
+    task: tokio::task::JoinHandle<()>,
+}
+
+#[cfg(feature = "http")]
+impl Drop for StatusServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// 开始监听状态端口。`port` 为 0 表示不启用；绑定失败时记录日志并返回 None（不影响正常播放）。
+#[cfg(feature = "http")]
+pub async fn spawn(app: Arc<Mutex<App>>, port: u16) -> Option<StatusServer> {
+    use tokio::net::TcpListener;
+
+    if port == 0 {
+        return None;
+    }
+    let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            app.lock()
+                .await
+                .add_log(format!("⚠ HTTP 状态接口启动失败 (端口 {}): {}", port, e));
+            return None;
+        }
+    };
+    app.lock()
+        .await
+        .add_log(format!("HTTP 状态接口: http://127.0.0.1:{}/status", port));
+
+    let task = tokio::spawn(async move {
+        // 连接任务归监听任务所有：监听任务被 abort 时 JoinSet 随之 drop，未结束的连接一并中止
+        let mut connections = tokio::task::JoinSet::new();
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => {
+                        connections.spawn(handle_connection(stream, Arc::clone(&app)));
+                    }
+                    Err(_) => break,
+                },
+                // 回收已结束的连接，JoinSet 不会无限增长
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            }
+        }
+    });
+    Some(StatusServer { task })
+}
+
+#[cfg(not(feature = "http"))]
+pub async fn spawn(app: Arc<Mutex<App>>, port: u16) -> Option<StatusServer> {
+    if port != 0 {
+        app.lock().await.add_log(
+            "⚠ 已设置 network.http_port，但编译时未启用 http feature，状态接口不可用".to_string(),
+        );
+    }
+    None
+}
+
+/// 读取一个请求并回复，回复后关闭连接。`REQUEST_TIMEOUT` 内没有收到完整请求头时直接断开
+#[cfg(feature = "http")]
+async fn handle_connection(mut stream: tokio::net::TcpStream, app: Arc<Mutex<App>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let read_request = async {
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => return None,
+                Ok(n) => request.extend_from_slice(&buf[..n]),
+            }
+            if request.len() > MAX_REQUEST_LEN {
+                return None;
+            }
+        }
+        Some(request)
+    };
+    let Ok(Some(request)) = tokio::time::timeout(REQUEST_TIMEOUT, read_request).await else {
+        return;
+    };
+
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request.lines().next().unwrap_or("").split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    let path = path.split('?').next().unwrap_or("");
+
+    let (code, body) = match (method, path) {
+        ("GET", "/" | "/status") => {
+            // 只在构建快照时短暂持有锁，网络读写都在锁外进行，不阻塞界面
+            let body = status_json(&*app.lock().await);
+            ("200 OK", body.to_string())
+        }
+        ("GET", _) => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            r#"{"error":"method not allowed"}"#.to_string(),
+        ),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        code,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// 当前播放状态的 JSON 快照
+#[cfg_attr(not(feature = "http"), allow(dead_code))]
+fn status_json(app: &App) -> serde_json::Value {
+    serde_json::json!({
+        "current_song": app.current_song,
+        "status": status_name(&app.status),
+        "progress": {
+            "position": app.time_pos,
+            "duration": app.duration,
+        },
+        "volume": app.volume,
+        "play_mode": app.play_mode_config_value(),
+        "source": app.current_source,
+    })
+}
+
+/// 播放状态在 JSON 中的名称（稳定的英文标识，便于脚本判断）
+#[cfg_attr(not(feature = "http"), allow(dead_code))]
+fn status_name(status: &PlayerStatus) -> &'static str {
+    match status {
+        PlayerStatus::Waiting => "waiting",
+        PlayerStatus::Searching => "searching",
+        PlayerStatus::SearchResults => "search_results",
+        PlayerStatus::Playing => "playing",
+        PlayerStatus::Paused => "paused",
+        PlayerStatus::SearchError(_) => "search_error",
+        PlayerStatus::PlaybackError(_) => "playback_error",
+        PlayerStatus::Unavailable(_) => "unavailable",
+    }
+}
@@ -0,0 +1,82 @@
+//! 日志文件：启用 `logging.file` 后，界面日志的每一条都会带上时间戳追加写入文件，
+//! 便于反馈问题时附上完整日志。
+//!
+//! 写入在后台任务中进行，`add_log` 只把消息放进通道，不会因磁盘 IO 卡住界面。
+//! 文件超过 `logging.max_size_kb` 时重命名为 `<文件名>.1`（覆盖上一份），然后从空文件继续写。
+
+use crate::config::{expand_home, LoggingConfig};
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+/// 正在运行的日志写入器。drop 后后台任务写完已排队的消息后退出
+pub struct FileLogger {
+    tx: mpsc::UnboundedSender<String>,
+}
+
+impl FileLogger {
+    /// 追加一条日志（行首加本地时间）。后台任务已退出时静默丢弃
+    pub fn log(&self, message: &str) {
+        let line = format!(
+            "{} {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            message
+        );
+        let _ = self.tx.send(line);
+    }
+}
+
+/// 按配置启动日志写入器；未设置 `logging.file` 时返回 None
+pub fn spawn(config: &LoggingConfig) -> Option<FileLogger> {
+    let path = expand_home(config.file.as_deref()?.trim());
+    if path.as_os_str().is_empty() {
+        return None;
+    }
+    let max_bytes = config.max_size_kb.max(1) * 1024;
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(write_loop(path, max_bytes, rx));
+    Some(FileLogger { tx })
+}
+
+/// 后台写入循环：逐条追加，超过大小上限时轮转。打开或写入失败时停止记录（不影响播放）
+async fn write_loop(path: PathBuf, max_bytes: u64, mut rx: mpsc::UnboundedReceiver<String>) {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        let _ = fs::create_dir_all(dir).await;
+    }
+    let Ok(mut file) = open_append(&path).await else {
+        return;
+    };
+    let mut written = file.metadata().await.map(|m| m.len()).unwrap_or(0);
+
+    while let Some(line) = rx.recv().await {
+        if written > 0 && written + line.len() as u64 > max_bytes {
+            let _ = fs::rename(&path, rotated_path(&path)).await;
+            let Ok(new_file) = open_append(&path).await else {
+                return;
+            };
+            file = new_file;
+            written = 0;
+        }
+        // 每条都 flush，退出时不会丢失最后几条
+        if file.write_all(line.as_bytes()).await.is_err() || file.flush().await.is_err() {
+            return;
+        }
+        written += line.len() as u64;
+    }
+}
+
+async fn open_append(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+}
+
+/// 轮转后的旧日志路径：在原文件名后追加 `.1`
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".1");
+    PathBuf::from(name)
+}
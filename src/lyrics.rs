@@ -0,0 +1,150 @@
+//! 同步歌词：按歌名从 LRCLIB（<https://lrclib.net>）查询带时间轴的 LRC 歌词，
+//! 由主循环根据 mpv 的 `time-pos` 高亮当前行。
+//!
+//! 仅在启用 `lyrics` feature 时联网查询并显示歌词面板；其余情况下 [`sync`] 直接返回。
+
+use crate::app::App;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// 一行带时间戳的歌词
+#[derive(Clone, Debug)]
+pub struct LyricLine {
+    /// 该行开始的时间（秒）
+    pub time: f64,
+    pub text: String,
+}
+
+/// 当前歌曲的歌词状态
+#[derive(Clone, Debug, Default)]
+pub enum LyricsState {
+    /// 没有正在播放的歌曲
+    #[default]
+    Idle,
+    Loading,
+    /// 未找到歌词或查询失败
+    NotFound,
+    Found(Vec<LyricLine>),
+}
+
+/// 是否编译了歌词功能
+pub const ENABLED: bool = cfg!(feature = "lyrics");
+
+/// 解析 LRC 文本。支持一行多个时间戳（`[00:12.34][01:02.00]歌词`），
+/// 忽略 `[ar:...]` 等标签行，结果按时间排序。
+#[cfg_attr(not(feature = "lyrics"), allow(dead_code))]
+pub fn parse_lrc(text: &str) -> Vec<LyricLine> {
+    let mut lines = Vec::new();
+    for raw in text.lines() {
+        let mut rest = raw.trim();
+        let mut times = Vec::new();
+        while let Some(end) = rest.strip_prefix('[').and_then(|r| r.find(']')) {
+            let tag = &rest[1..=end];
+            match parse_lrc_time(tag) {
+                Some(time) => times.push(time),
+                None => break,
+            }
+            rest = &rest[end + 2..];
+        }
+        let text = rest.trim();
+        for time in times {
+            lines.push(LyricLine {
+                time,
+                text: text.to_string(),
+            });
+        }
+    }
+    lines.sort_by(|a, b| a.time.total_cmp(&b.time));
+    lines
+}
+
+/// 解析 `mm:ss.xx` 形式的时间戳
+fn parse_lrc_time(tag: &str) -> Option<f64> {
+    let (min, sec) = tag.split_once(':')?;
+    let min: u64 = min.parse().ok()?;
+    let sec: f64 = sec.parse().ok()?;
+    Some(min as f64 * 60.0 + sec)
+}
+
+/// 返回 `time_pos` 时刻应高亮的行号；第一行之前返回 None
+pub fn current_line(lines: &[LyricLine], time_pos: f64) -> Option<usize> {
+    lines
+        .partition_point(|line| line.time <= time_pos)
+        .checked_sub(1)
+}
+
+/// 检查当前歌曲的歌词：缓存中没有时在后台查询，查询期间不阻塞播放
+#[cfg(feature = "lyrics")]
+pub async fn sync(app: &Arc<Mutex<App>>) {
+    let title = {
+        let mut app_lock = app.lock().await;
+        let title = app_lock.current_song.clone();
+        if !app_lock.begin_lyrics(&title) {
+            return;
+        }
+        title
+    };
+
+    let app = Arc::clone(app);
+    tokio::spawn(async move {
+        let result = fetch(&title).await;
+        let mut app_lock = app.lock().await;
+        match result {
+            Ok(text) => {
+                let lines = text.map(|t| parse_lrc(&t)).unwrap_or_default();
+                app_lock.finish_lyrics(&title, Some(lines));
+            }
+            Err(e) => {
+                app_lock.add_log(format!("⚠ 歌词查询失败: {}", e));
+                app_lock.finish_lyrics(&title, None);
+            }
+        }
+    });
+}
+
+#[cfg(not(feature = "lyrics"))]
+pub async fn sync(_app: &Arc<Mutex<App>>) {}
+
+/// 去掉标题中的括号说明（如 `(Official Video)`、`【MV】`），提高匹配率
+#[cfg(feature = "lyrics")]
+fn clean_title(title: &str) -> String {
+    let mut cleaned = String::new();
+    let mut depth = 0usize;
+    for c in title.chars() {
+        match c {
+            '(' | '[' | '【' | '（' => depth += 1,
+            ')' | ']' | '】' | '）' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => cleaned.push(c),
+            _ => {}
+        }
+    }
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 通过 curl 查询 LRCLIB，返回第一条带时间轴的歌词；没有结果时返回 Ok(None)
+#[cfg(feature = "lyrics")]
+async fn fetch(title: &str) -> anyhow::Result<Option<String>> {
+    let query = clean_title(title);
+    if query.is_empty() {
+        return Ok(None);
+    }
+    let output = tokio::process::Command::new("curl")
+        .args(["-fsSL", "--max-time", "10", "-G"])
+        .arg("--data-urlencode")
+        .arg(format!("q={}", query))
+        .arg("https://lrclib.net/api/search")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    Ok(json.as_array().and_then(|items| {
+        items
+            .iter()
+            .find_map(|item| item["syncedLyrics"].as_str())
+            .filter(|s| !s.trim().is_empty())
+            .map(str::to_string)
+    }))
+}
@@ -1,25 +1,38 @@
 mod app;
+mod cli;
+mod clipboard;
 mod config;
+mod cover;
+mod http;
+mod logfile;
+mod lyrics;
+mod mpris;
 mod net;
 mod player;
+mod remote;
+mod signals;
+mod stats;
+#[cfg(test)]
+mod test_support;
 mod ui;
 
-use crate::app::{App, PlayerStatus};
-use crate::config::Config;
+use crate::app::{App, PlaybackContext, PlayerStatus};
+use crate::config::{Config, VideoMode};
 use crate::net::AudioBackend;
 use crate::player::Player;
 use anyhow::Result;
 use crossterm::{
-    event::{self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEventKind},
-    execute,
+    cursor,
+    event::{
+        DisableBracketedPaste, EnableBracketedPaste, Event, EventStream, KeyCode, KeyEventKind,
+        KeyModifiers,
+    },
+    execute, style,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use futures_core::Stream;
 use ratatui::prelude::*;
-use std::{
-    env, io,
-    sync::Arc,
-    time::{Duration, Instant},
-};
+use std::{env, future::Future, io, path::PathBuf, pin::Pin, sync::Arc, time::Instant};
 use tokio::sync::Mutex;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -48,18 +61,25 @@ impl Drop for TerminalCleanupGuard {
     }
 }
 
-fn check_dependencies() -> Result<()> {
-    let missing: Vec<&str> = [("mpv", "--version"), ("yt-dlp", "--version")]
-        .iter()
-        .filter(|(cmd, arg)| {
-            std::process::Command::new(cmd)
-                .arg(arg)
-                .stdout(std::process::Stdio::null())
-                .stderr(std::process::Stdio::null())
-                .status()
-                .is_err()
-        })
-        .map(|(cmd, _)| *cmd)
+/// 在 `path` 的各个目录中查找可执行文件（类似 `which`）
+fn find_in_path(name: &str, path: &str) -> Option<std::path::PathBuf> {
+    let file_name = if cfg!(windows) {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    };
+    env::split_paths(path)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// 检查 mpv 和 yt-dlp 是否在（补充了 Homebrew 目录的）PATH 中。
+/// 缺少任何一个都会打印安装提示和实际搜索的 PATH，然后以退出码 1 结束进程。
+fn check_dependencies() {
+    let path = net::get_extended_path();
+    let missing: Vec<&str> = ["mpv", "yt-dlp"]
+        .into_iter()
+        .filter(|cmd| find_in_path(cmd, &path).is_none())
         .collect();
 
     if !missing.is_empty() {
@@ -67,18 +87,50 @@ fn check_dependencies() -> Result<()> {
         for dep in &missing {
             eprintln!("   - {dep}");
         }
-        eprintln!("\n请先安装缺少的依赖后再启动：");
-        eprintln!("   brew install {}", missing.join(" "));
+        eprintln!("\n请先安装缺少的依赖后再启动，例如：");
+        if cfg!(target_os = "macos") {
+            eprintln!("   brew install {}", missing.join(" "));
+        } else if cfg!(windows) {
+            eprintln!("   scoop install {}", missing.join(" "));
+        } else {
+            eprintln!(
+                "   使用系统包管理器安装 {}（yt-dlp 也可以用 pipx 安装）",
+                missing.join(" ")
+            );
+        }
+        eprintln!("\n已在以下 PATH 中查找：");
+        for dir in env::split_paths(&path) {
+            eprintln!("   {}", dir.display());
+        }
         eprintln!();
-        anyhow::bail!("缺少必要依赖：{}", missing.join(", "));
+        std::process::exit(1);
     }
-    Ok(())
 }
 
 fn print_version() {
     println!("maboroshi v{}", VERSION);
 }
 
+/// 从参数中取出 `<flag> <路径>` / `<flag>=<路径>`（如 `--favorites`、`--config`），其余参数保持原样。
+/// 缺少路径时打印错误并退出。
+fn take_path_arg(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let prefix = format!("{}=", flag);
+    let index = args
+        .iter()
+        .position(|a| a == flag || a.starts_with(&prefix))?;
+    let arg = args.remove(index);
+    let path = match arg.strip_prefix(&prefix) {
+        Some(path) => path.to_string(),
+        None if index < args.len() => args.remove(index),
+        None => String::new(),
+    };
+    if path.trim().is_empty() {
+        eprintln!("{} 缺少路径参数", flag);
+        std::process::exit(1);
+    }
+    Some(path)
+}
+
 #[cfg(unix)]
 fn upgrade() -> Result<()> {
     println!("🔄 正在升级 maboroshi...");
@@ -106,9 +158,358 @@ fn upgrade() -> Result<()> {
     Ok(())
 }
 
+/// 记录最终的 mpv 启动参数，并提示 `playback.mpv_args` 中被忽略的参数
+fn log_mpv_args(app: &mut App, config: &Config) {
+    for arg in config.playback.mpv_extra_args().1 {
+        app.add_log(format!(
+            "⚠ mpv_args 中的 {} 与内部参数冲突或不是 mpv 选项，已忽略",
+            arg
+        ));
+    }
+    let (cache_secs, cache_size, warnings) = config.playback.cache_settings();
+    for warning in warnings {
+        app.add_log(format!("⚠ {}", warning));
+    }
+    if cache_secs.is_some() || cache_size.is_some() {
+        let describe = |value: Option<u64>, unit: &str| {
+            value.map_or("mpv 默认".to_string(), |v| format!("{}{}", v, unit))
+        };
+        app.add_log(format!(
+            "mpv 缓存: {} / {}",
+            describe(cache_secs, " 秒"),
+            describe(cache_size, " MiB")
+        ));
+    }
+    let args = AudioBackend::build_mpv_args(config, app.volume, app.muted, app.eq_preset);
+    app.add_log(format!("mpv 参数: {}", args.join(" ")));
+}
+
+/// `ui.title_match` 无法识别时记录警告；此时按 fuzzy 比较
+fn log_title_match_config(app: &mut App, config: &Config) {
+    if config.ui.title_match_mode().is_none() {
+        app.add_log(format!(
+            "⚠ ui.title_match 配置无效: {}，已按 fuzzy 处理（可选 exact / normalized / fuzzy）",
+            config.ui.title_match
+        ));
+    }
+}
+
+/// `playback.video_mode` 无法识别、或选择了 art 但终端不支持内联图片时记录警告
+fn log_video_mode_config(app: &mut App, config: &Config) {
+    match config.playback.video_mode() {
+        None => app.add_log(format!(
+            "⚠ playback.video_mode 配置无效: {}，已按 none 处理（可选 none / window / art）",
+            config.playback.video_mode
+        )),
+        Some(VideoMode::Art) if !cover::supported() => {
+            app.add_log("⚠ 当前终端不支持内联图片（需要 iTerm2 / WezTerm），不显示封面".to_string())
+        }
+        _ => {}
+    }
+}
+
+/// 是否显示封面：选择了 art 且终端支持内联图片
+fn cover_enabled(config: &Config) -> bool {
+    config.playback.video_mode() == Some(VideoMode::Art) && cover::supported()
+}
+
+/// `playback.equalizer` 无法识别时记录警告；此时不启用均衡器
+fn log_eq_config(app: &mut App, config: &Config) {
+    if config.playback.eq_preset().is_none() {
+        app.add_log(format!(
+            "⚠ playback.equalizer 配置无效: {}，已按 flat 处理（可选 flat / bass / vocal / treble）",
+            config.playback.equalizer
+        ));
+    }
+}
+
+/// `playback.on_queue_end` 无法识别时记录警告；此时列表播完后停止播放
+fn log_queue_end_config(app: &mut App, config: &Config) {
+    if config.playback.queue_end_action().is_none() {
+        app.add_log(format!(
+            "⚠ playback.on_queue_end 配置无效: {}，已按 stop 处理（可选 stop / loop / shuffle / quit）",
+            config.playback.on_queue_end
+        ));
+    }
+}
+
+/// cookies 配置有误时记录错误；此时 yt-dlp 不带任何 cookies 参数运行
+fn log_cookie_config(app: &mut App, config: &Config) {
+    if let Err(e) = net::cookie_args(&config.search) {
+        app.add_log(format!("❌ cookies 配置错误: {}，本次不使用 cookies", e));
+    }
+}
+
+/// 列出搜索列表和解析歌曲实际使用的超时；用到了已弃用的 `search.timeout` 时提示改用新配置
+fn log_timeout_config(app: &mut App, config: &Config) {
+    let search = &config.search;
+    let fallback = search.timeout.is_some();
+    let origin = |value: Option<u64>| {
+        if value.is_none() && fallback {
+            "（沿用 search.timeout）"
+        } else {
+            ""
+        }
+    };
+    app.add_log(format!(
+        "超时: 搜索列表 {} 秒{}，解析歌曲 {} 秒{}",
+        search.list_timeout_secs(),
+        origin(search.list_timeout),
+        search.resolve_timeout_secs(),
+        origin(search.resolve_timeout)
+    ));
+    if fallback {
+        app.add_log(
+            "ℹ search.timeout 已弃用，请改用 search.list_timeout / search.resolve_timeout"
+                .to_string(),
+        );
+    }
+}
+
+/// 设置了 `search.prefix_overrides` 时列出各搜索源实际使用的前缀，并提示不会用到的条目
+fn log_prefix_config(app: &mut App, config: &Config) {
+    let overrides = &config.search.prefix_overrides;
+    if overrides.is_empty() {
+        return;
+    }
+    let sources = config.search_sources();
+    let prefixes: Vec<String> = sources
+        .iter()
+        .map(|s| format!("{} → {}", s, config.search.prefix_for(s)))
+        .collect();
+    app.add_log(format!("搜索前缀: {}", prefixes.join(", ")));
+    let mut unused: Vec<&str> = overrides
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !sources.iter().any(|s| s == key))
+        .collect();
+    if !unused.is_empty() {
+        unused.sort_unstable();
+        app.add_log(format!(
+            "⚠ prefix_overrides 中的 {} 不在 search.source 里，暂不生效",
+            unused.join(", ")
+        ));
+    }
+}
+
+/// 绘制后写入封面图片；之前显示过封面时先清屏重绘，擦掉 ratatui 不会覆盖的旧图片
+fn draw_cover(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    theme: &ui::Theme,
+    shown: &mut Option<(String, Rect)>,
+) -> io::Result<()> {
+    let wanted = app.cover_area.map(|area| (app.current_song.clone(), area));
+    if wanted == *shown {
+        return Ok(());
+    }
+    if shown.is_some() {
+        terminal.clear()?;
+        terminal.draw(|f| ui::render(app, f, theme))?;
+    }
+    if let (Some((_, area)), Some(data)) = (&wanted, &app.cover_art) {
+        let (x, y) = cover::origin(*area);
+        execute!(
+            terminal.backend_mut(),
+            cursor::MoveTo(x, y),
+            style::Print(cover::escape_sequence(data, *area))
+        )?;
+    }
+    *shown = wanted;
+    Ok(())
+}
+
+/// 等待下一个终端事件
+async fn next_event(events: &mut EventStream) -> Option<io::Result<Event>> {
+    std::future::poll_fn(|cx| Pin::new(&mut *events).poll_next(cx)).await
+}
+
+/// 等待可选的命令来源；来源不存在时（功能未启用或创建失败）永远不返回
+async fn recv_optional<T>(recv: Option<impl Future<Output = Option<T>>>) -> Option<T> {
+    match recv {
+        Some(recv) => recv.await,
+        None => std::future::pending().await,
+    }
+}
+
+/// 暂停界面，用 `$VISUAL` / `$EDITOR`（都未设置时为 vi，Windows 下为 notepad）打开配置文件。
+/// 无论编辑器能否启动、是否正常退出，都会恢复 raw mode 和备用屏幕。
+/// 内层结果为要记录的说明：编辑器正常退出时为 Ok，无法启动或异常退出时为 Err
+async fn edit_config_in_editor(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+) -> io::Result<Result<String, String>> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    let path = Config::config_file_path();
+    // 编辑器命令可以带参数，例如 `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableBracketedPaste
+    )?;
+    let status = tokio::process::Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status()
+        .await;
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableBracketedPaste
+    )?;
+    terminal.clear()?;
+
+    Ok(match status {
+        Ok(status) if status.success() => Ok(format!("已用 {} 编辑配置文件", editor)),
+        Ok(status) => Err(format!(
+            "⚠ 编辑器 {} 异常退出（{}），未重新加载配置",
+            editor, status
+        )),
+        Err(e) => Err(format!(
+            "❌ 无法启动编辑器 {}: {}（可设置 $EDITOR）",
+            editor, e
+        )),
+    })
+}
+
+/// 重新读取配置文件并应用到运行中的播放器和界面。
+/// 解析失败时保留当前配置；`paths` 段在启动时已固定，修改后需重启才生效。
+/// `favorites_override` 为命令行 `--favorites` 指定的路径，重新加载时继续覆盖配置文件中的值
+async fn reload_config(
+    player: &Player,
+    app: &Arc<Mutex<App>>,
+    theme: &mut ui::Theme,
+    favorites_override: Option<&str>,
+) {
+    let mut new_config = match Config::reload() {
+        Ok(config) => config,
+        Err(e) => {
+            app.lock()
+                .await
+                .add_log(format!("⚠ 重新加载配置失败，保留当前配置: {}", e));
+            return;
+        }
+    };
+    // 回退提示在启动时已经记录过，重新加载时不再重复
+    new_config.use_pid_socket_path();
+    if let Some(path) = favorites_override {
+        new_config.paths.favorites_file = path.to_string();
+    }
+
+    let current = player.config().await;
+    let (live, restart) = current.changed_sections(&new_config);
+    new_config.paths = current.paths.clone();
+
+    let (new_theme, theme_warnings) = ui::Theme::from_config(&new_config.theme);
+    *theme = new_theme;
+
+    let mut app_lock = app.lock().await;
+    // 正在播放时 current_source 表示当前歌曲的来源，不随配置改变
+    if !matches!(
+        app_lock.status,
+        PlayerStatus::Playing | PlayerStatus::Paused
+    ) {
+        app_lock.current_source = new_config.search.source.clone();
+    }
+    app_lock.confirm_quit = new_config.ui.confirm_quit;
+    app_lock.show_clock = new_config.ui.show_clock;
+    app_lock.show_logs = new_config.ui.show_logs;
+    app_lock.screensaver = new_config.ui.screensaver;
+    app_lock.level_meter = new_config.ui.level_meter;
+    app_lock.cover_enabled = cover_enabled(&new_config);
+    app_lock.tips = new_config.ui.tip_list();
+    app_lock.title_match = new_config.ui.title_match_mode().unwrap_or_default();
+    if current.ui.title_match != new_config.ui.title_match {
+        log_title_match_config(&mut app_lock, &new_config);
+    }
+    app_lock.seek_seconds = new_config.playback.seek_seconds;
+    app_lock.volume_step = new_config.playback.volume_step;
+    let max_volume = new_config.playback.volume_ceiling();
+    app_lock.max_volume = max_volume;
+    if app_lock.volume > max_volume {
+        app_lock.add_log(format!("音量上限调整为 {}%，下一首开始生效", max_volume));
+    }
+    app_lock.follow_favorite_sort = new_config.playback.follow_favorite_sort;
+    app_lock.shuffle_avoid_recent = new_config.playback.shuffle_avoid_recent;
+    app_lock.exclude_patterns = new_config.search.exclude_patterns.clone();
+    if current.playback.mpv_args != new_config.playback.mpv_args {
+        log_mpv_args(&mut app_lock, &new_config);
+    }
+    if current.logging != new_config.logging {
+        app_lock.file_logger = logfile::spawn(&new_config.logging);
+    }
+    if current.playback.on_queue_end != new_config.playback.on_queue_end {
+        log_queue_end_config(&mut app_lock, &new_config);
+    }
+    if current.playback.equalizer != new_config.playback.equalizer {
+        log_eq_config(&mut app_lock, &new_config);
+    }
+    if current.playback.video_mode != new_config.playback.video_mode {
+        log_video_mode_config(&mut app_lock, &new_config);
+    }
+    if current.search != new_config.search {
+        log_cookie_config(&mut app_lock, &new_config);
+        log_prefix_config(&mut app_lock, &new_config);
+        log_timeout_config(&mut app_lock, &new_config);
+    }
+    if current.search.backend != new_config.search.backend {
+        app_lock.add_log("⚠ search.backend 的修改需重启后生效".to_string());
+    }
+    if current.network.http_port != new_config.network.http_port {
+        app_lock.add_log("⚠ network.http_port 的修改需重启后生效".to_string());
+    }
+    for warn in theme_warnings {
+        app_lock.add_log(format!("⚠ {}", warn));
+    }
+    if live.is_empty() && restart.is_empty() {
+        app_lock.add_log("配置已重新加载，没有变化".to_string());
+    } else {
+        if !live.is_empty() {
+            app_lock.add_log(format!("✓ 配置已重新加载: {}", live.join(", ")));
+        }
+        if !restart.is_empty() {
+            app_lock.add_log(format!("⚠ 以下配置需重启后生效: {}", restart.join(", ")));
+        }
+    }
+    drop(app_lock);
+
+    player.update_config(new_config).await;
+}
+
+/// 从参数中取出不带值的开关（如 `--dry-run`），返回是否出现过
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|a| a != flag);
+    args.len() != before
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    // 命令行上的相对路径按当前工作目录理解（配置文件中的相对路径则相对于配置目录）
+    let favorites_override = take_path_arg(&mut args, "--favorites").map(|path| {
+        let is_relative = !path.starts_with('~') && PathBuf::from(&path).is_relative();
+        match env::current_dir() {
+            Ok(cwd) if is_relative => cwd.join(&path).to_string_lossy().into_owned(),
+            _ => path,
+        }
+    });
+    let dry_run = take_flag(&mut args, "--dry-run");
+    // 明确指定的配置文件必须存在，不静默回落到默认配置
+    if let Some(path) = take_path_arg(&mut args, "--config") {
+        if let Err(e) = Config::use_config_path(PathBuf::from(path)) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
 
     if args.len() > 1 {
         match args[1].as_str() {
@@ -122,12 +523,47 @@ async fn main() -> Result<()> {
             "--help" | "-h" => {
                 println!("maboroshi v{}", VERSION);
                 println!("\n用法:");
-                println!("  maboroshi              启动音乐播放器");
-                println!("  maboroshi --version    显示版本信息");
-                println!("  maboroshi --upgrade    升级到最新版本");
-                println!("  maboroshi --help       显示帮助信息");
+                println!("  maboroshi                          启动音乐播放器");
+                println!("  maboroshi --favorites <路径>       使用指定的收藏文件启动（覆盖配置）");
+                println!(
+                    "  maboroshi --config <路径>          使用指定的配置文件（可与其它命令组合）"
+                );
+                println!(
+                    "  maboroshi --dry-run                只记录 yt-dlp / mpv 命令行并模拟播放（可与其它命令组合）"
+                );
+                println!("  maboroshi play <关键词> [--source <源>]");
+                println!(
+                    "                                     不启动界面，直接播放并在终端显示进度"
+                );
+                println!("  maboroshi --search <关键词> [--source <源>] [--json]");
+                println!("                                     搜索并以 JSON 输出结果，不启动界面");
+                println!("  maboroshi --list-audio-devices     列出可用的音频输出设备");
+                println!("  maboroshi --stats                  显示累计收听时长和最常播放的收藏");
+                println!("  maboroshi --version                显示版本信息");
+                println!("  maboroshi --upgrade                升级到最新版本");
+                println!("  maboroshi --help                   显示帮助信息");
+                return Ok(());
+            }
+            "--list-audio-devices" => {
+                return cli::list_audio_devices().await;
+            }
+            "--stats" => {
+                cli::stats(favorites_override.as_deref());
+                return Ok(());
+            }
+            "--search" => {
+                if let Err(e) = cli::search(&args[2..], dry_run).await {
+                    eprintln!("搜索失败: {}", e);
+                    std::process::exit(1);
+                }
                 return Ok(());
             }
+            "play" => {
+                if !dry_run {
+                    check_dependencies();
+                }
+                return cli::play(&args[2..], dry_run).await;
+            }
             _ => {
                 eprintln!("未知参数: {}", args[1]);
                 eprintln!("使用 --help 查看帮助");
@@ -136,8 +572,10 @@ async fn main() -> Result<()> {
         }
     }
 
-    // 进入 TUI 前检查外部依赖，失败时直接打印友好错误信息并退出
-    check_dependencies()?;
+    // 进入 TUI 前检查外部依赖，失败时直接打印友好错误信息并退出；演练模式不调用外部程序
+    if !dry_run {
+        check_dependencies();
+    }
 
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -147,66 +585,222 @@ async fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     let (config, config_warn) = Config::load_with_warning();
-    let _ = Config::save_example();
+    // 使用 --config 指定的文件时不生成默认配置
+    if !Config::has_config_override() {
+        let _ = Config::save_example();
+    }
 
-    // 动态生成 IPC 端点路径（基于 PID），避免多实例冲突
     let mut config = config;
-    if config.paths.socket_path == config::default_socket_path() {
-        config.paths.socket_path = config::default_socket_path_with_pid(std::process::id());
+    let socket_warn = config.use_pid_socket_path();
+    if let Some(path) = &favorites_override {
+        config.paths.favorites_file = path.clone();
     }
 
     let app = Arc::new(Mutex::new(App::new(&config.paths.favorites_file)));
+    if let Some(logger) = logfile::spawn(&config.logging) {
+        // 创建 App 时已产生的日志（启动、收藏迁移等）补写进文件
+        let mut app_lock = app.lock().await;
+        for line in &app_lock.logs {
+            logger.log(line);
+        }
+        app_lock.file_logger = Some(logger);
+    }
+    let (mut theme, theme_warnings) = ui::Theme::from_config(&config.theme);
 
-    {
+    let (resume, resume_volume) = {
         let mut app_lock = app.lock().await;
         app_lock.current_source = config.search.source.clone();
+        app_lock.confirm_quit = config.ui.confirm_quit;
+        app_lock.show_clock = config.ui.show_clock;
+        app_lock.show_logs = config.ui.show_logs;
+        app_lock.screensaver = config.ui.screensaver;
+        app_lock.level_meter = config.ui.level_meter;
+        app_lock.cover_enabled = cover_enabled(&config);
+        app_lock.tips = config.ui.tip_list();
+        app_lock.title_match = config.ui.title_match_mode().unwrap_or_default();
+        app_lock.seek_seconds = config.playback.seek_seconds;
+        app_lock.volume_step = config.playback.volume_step;
+        app_lock.max_volume = config.playback.volume_ceiling();
+        app_lock.volume = app_lock.volume.min(app_lock.max_volume);
+        app_lock.follow_favorite_sort = config.playback.follow_favorite_sort;
+        app_lock.shuffle_avoid_recent = config.playback.shuffle_avoid_recent;
+        app_lock.exclude_patterns = config.search.exclude_patterns.clone();
         let play_mode_ok = app_lock.set_play_mode_from_config(&config.playback.default_mode);
         // 只在有警告/错误时记录日志
         if let Some(warn) = config_warn {
             app_lock.add_log(format!("⚠ 配置警告: {}", warn));
         }
+        if let Some(warn) = socket_warn {
+            app_lock.add_log(warn);
+        }
+        for warn in theme_warnings {
+            app_lock.add_log(format!("⚠ {}", warn));
+        }
         if !play_mode_ok {
             app_lock.add_log(format!(
                 "⚠ 播放模式配置无效: {}，已回退为 shuffle",
                 config.playback.default_mode
             ));
         }
-    }
+        if let Some(volume) = config.playback.volume {
+            app_lock.volume = volume.min(app_lock.max_volume);
+        }
+        app_lock.eq_preset = config.playback.eq_preset().unwrap_or_default();
+        let resume = if config.playback.resume_last {
+            app_lock.restore_session()
+        } else {
+            None
+        };
+        log_mpv_args(&mut app_lock, &config);
+        log_queue_end_config(&mut app_lock, &config);
+        log_eq_config(&mut app_lock, &config);
+        log_video_mode_config(&mut app_lock, &config);
+        log_title_match_config(&mut app_lock, &config);
+        log_cookie_config(&mut app_lock, &config);
+        log_prefix_config(&mut app_lock, &config);
+        log_timeout_config(&mut app_lock, &config);
+        if let Err(e) = net::create_source(&config.search.backend) {
+            app_lock.add_log(format!("⚠ {}", e));
+        }
+        if dry_run {
+            app_lock.add_log(format!(
+                "{} 演练模式：不会启动 yt-dlp / mpv，只记录命令行并模拟播放",
+                net::DRY_RUN_PREFIX
+            ));
+        }
+        (resume, app_lock.volume)
+    };
 
-    let audio = Arc::new(AudioBackend::new(config.clone()));
+    let audio = Arc::new(AudioBackend::new(config.clone()).with_dry_run(dry_run));
+    audio.set_initial_volume(resume_volume).await;
+    let mut tick_rate = config.ui.tick_rate();
+    // 没有恢复上次会话时，按配置在启动后自动搜索 / 播放
+    let autoplay = config
+        .playback
+        .autoplay_query
+        .as_deref()
+        .map(str::trim)
+        .filter(|query| resume.is_none() && !query.is_empty())
+        .map(|query| (query.to_string(), config.playback.autostart));
+    let http_port = config.network.http_port;
+    let socket_path = config.paths.socket_path.clone();
     let player = Player::new(Arc::clone(&audio), Arc::clone(&app), config);
+    if let Some((song, path)) = resume {
+        player.search_and_play(song, path).await;
+    }
+    let mut mpris_rx = mpris::spawn(Arc::clone(&app), Arc::clone(&audio)).await;
+    let mut remote = remote::spawn(Arc::clone(&app), &socket_path).await;
+    // SIGINT / SIGTERM 与按 q 确认退出走同一条清理路径
+    let mut exit_signals = signals::spawn();
+    // 持有到 main 结束，drop 时停止 HTTP 监听
+    let _status_server = http::spawn(Arc::clone(&app), http_port).await;
+    // 终端输入与播放结束、远程 / mpris 命令、退出信号和 tick 在主循环里同时等待
+    let mut events = EventStream::new();
+    let mut ended_rx = audio.subscribe_playback_ended();
 
-    let tick_rate = Duration::from_millis(200);
     let mut last_tick = Instant::now();
 
     enum PendingAction {
         Search(String),
+        RepeatSearch,
         PlaySelectedResult,
+        PreviewResult,
         SearchAndPlay(String, Option<String>),
         TogglePause,
         SeekForward,
         SeekBackward,
+        NextChapter,
+        PrevChapter,
         VolumeUp,
         VolumeDown,
         NextPage,
         PrevPage,
         CreateGroup(String),
+        SeekTo(String),
+        RestartTrack,
+        ChangeVolume(i32),
+        SetVolume(String),
+        JumpToPage(String),
+        ToggleMute,
+        CycleEqPreset,
+        RefreshCurrent,
+        HistoryBack,
+        SetExcludePatterns(String),
+        DownloadCurrent,
+        CopyTrackUrl,
+        NextTrack,
+        PrevTrack,
+        ReloadConfig,
+        EditConfig,
+        CancelSearch,
         Quit,
     }
 
+    // 启动时自动执行的动作，在第一次轮询事件前分发；用户随时可以按 Esc 取消
+    let mut startup_action = autoplay.map(|(query, autostart)| {
+        if autostart {
+            PendingAction::SearchAndPlay(query, None)
+        } else {
+            PendingAction::Search(query)
+        }
+    });
+
+    // 当前已写入终端的封面（歌名与位置）
+    let mut shown_cover: Option<(String, Rect)> = None;
+
     loop {
-        {
+        let running = {
             let mut app_lock = app.lock().await;
-            terminal.draw(|f| ui::render(&mut app_lock, f))?;
-            if !app_lock.running {
-                break;
-            }
+            terminal.draw(|f| ui::render(&mut app_lock, f, &theme))?;
+            draw_cover(&mut terminal, &mut app_lock, &theme, &mut shown_cover)?;
+            app_lock.running
+        };
+        // 列表播完且 on_queue_end = "quit" 时由播放器清除 running
+        if !running {
+            player.quit().await;
+            break;
         }
 
-        let mut pending_action = None;
+        let mut pending_action = startup_action.take();
+        let mut input = None;
+        let mut exit_signal = None;
 
-        if event::poll(Duration::from_millis(10))? {
-            let evt = event::read()?;
+        // 等到有输入、命令、播放结束或下一个 tick，空闲时不再频繁重绘，
+        // 其它事件也不必等 tick 才得到响应
+        if pending_action.is_none() {
+            let next_tick = tokio::time::Instant::from_std(last_tick + tick_rate);
+            tokio::select! {
+                Some(evt) = next_event(&mut events) => input = Some(evt?),
+                // 媒体键 / 桌面“正在播放”组件发来的控制命令
+                Some(cmd) = recv_optional(mpris_rx.as_mut().map(|rx| rx.recv())) => {
+                    pending_action = Some(match cmd {
+                        mpris::MprisCommand::PlayPause => PendingAction::TogglePause,
+                        mpris::MprisCommand::Next => PendingAction::NextTrack,
+                        mpris::MprisCommand::Previous => PendingAction::PrevTrack,
+                    });
+                }
+                // 远程控制 socket 发来的命令
+                Some(cmd) = recv_optional(remote.as_mut().map(|r| r.recv())) => {
+                    pending_action = Some(match cmd {
+                        remote::RemoteCommand::Toggle => PendingAction::TogglePause,
+                        remote::RemoteCommand::Next => PendingAction::NextTrack,
+                        remote::RemoteCommand::Prev => PendingAction::PrevTrack,
+                        remote::RemoteCommand::Volume(delta) => PendingAction::ChangeVolume(delta),
+                        remote::RemoteCommand::Search(keyword) => {
+                            app.lock().await.add_to_search_history(&keyword);
+                            PendingAction::Search(keyword)
+                        }
+                        remote::RemoteCommand::Quit => PendingAction::Quit,
+                    });
+                }
+                Some(name) = exit_signals.recv() => exit_signal = Some(name),
+                // 播放结束在循环末尾处理
+                Ok(()) = ended_rx.changed() => {}
+                () = tokio::time::sleep_until(next_tick) => {}
+            }
+        }
+
+        if let Some(evt) = input {
             // 括号粘贴模式：整段粘贴内容作为 Event::Paste 投递，不含换行，不会误触 Enter
             if let Event::Paste(pasted) = evt {
                 let mut app_lock = app.lock().await;
@@ -221,6 +815,11 @@ async fn main() -> Result<()> {
                 }
                 continue;
             }
+            // 窗口尺寸变化：清屏后回到循环开头立即重绘，避免残留旧画面直到下一次按键
+            if let Event::Resize(_, _) = evt {
+                terminal.clear()?;
+                continue;
+            }
             if let Event::Key(key) = evt {
                 // Windows 会同时上报按键的 Press / Release / Repeat；Unix 只报 Press。
                 // 统一只处理 Press 事件，避免按键被重复处理（Windows 上会导致输入翻倍）。
@@ -228,12 +827,48 @@ async fn main() -> Result<()> {
                     continue;
                 }
                 let mut app_lock = app.lock().await;
+                // 屏保显示中：这次按键只唤醒界面，不触发其他操作
+                if app_lock.wake_screensaver() {
+                    continue;
+                }
                 // ── 帮助说明弹窗模式 ──────────────────────────────────
                 if app_lock.help_mode {
                     match key.code {
                         KeyCode::Char('?') | KeyCode::Esc => {
                             app_lock.help_mode = false;
                         }
+                        KeyCode::Up | KeyCode::Char('k') => {
+                            app_lock.help_scroll = app_lock.help_scroll.saturating_sub(1);
+                        }
+                        KeyCode::Down | KeyCode::Char('j') => {
+                            app_lock.help_scroll = app_lock.help_scroll.saturating_add(1);
+                        }
+                        KeyCode::PageUp => {
+                            app_lock.help_scroll = app_lock.help_scroll.saturating_sub(10);
+                        }
+                        KeyCode::PageDown => {
+                            app_lock.help_scroll = app_lock.help_scroll.saturating_add(10);
+                        }
+                        _ => {}
+                    }
+                // ── 收听统计浮层 ──────────────────────────────────────
+                } else if app_lock.stats_mode {
+                    if matches!(
+                        key.code,
+                        KeyCode::Char('T') | KeyCode::Char('q') | KeyCode::Esc
+                    ) {
+                        app_lock.stats_mode = false;
+                    }
+                // ── 退出确认 ──────────────────────────────────────────
+                } else if app_lock.quit_confirm_mode {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') => {
+                            app_lock.quit_confirm_mode = false;
+                            pending_action = Some(PendingAction::Quit);
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                            app_lock.quit_confirm_mode = false;
+                        }
                         _ => {}
                     }
                 // ── 删除分组二次确认 ──────────────────────────────────
@@ -247,16 +882,20 @@ async fn main() -> Result<()> {
                             app_lock.delete_confirm_mode = false;
                         }
                     }
+                // ── 清空全部收藏确认模式 ─────────────────────────────
+                } else if app_lock.clear_favorites_confirm_mode {
+                    app_lock.clear_favorites_confirm_mode = false;
+                    if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                        app_lock.clear_all_favorites();
+                    }
                 // ── 重命名分组输入模式 ──────────────────────────────
                 } else if app_lock.rename_mode {
                     match key.code {
-                        KeyCode::Enter => {
-                            if !app_lock.input_buffer.is_empty() {
-                                let new_name = app_lock.input_buffer.clone();
-                                app_lock.rename_mode = false;
-                                app_lock.input_buffer.clear();
-                                app_lock.rename_group(new_name);
-                            }
+                        KeyCode::Enter if !app_lock.input_buffer.is_empty() => {
+                            let new_name = app_lock.input_buffer.clone();
+                            app_lock.rename_mode = false;
+                            app_lock.input_buffer.clear();
+                            app_lock.rename_group(new_name);
                         }
                         KeyCode::Esc => {
                             app_lock.rename_mode = false;
@@ -270,6 +909,117 @@ async fn main() -> Result<()> {
                         }
                         _ => {}
                     }
+                // ── 设置收藏显示名称 ────────────────────────────────
+                } else if app_lock.display_name_mode {
+                    match key.code {
+                        KeyCode::Enter => {
+                            let name = std::mem::take(&mut app_lock.input_buffer);
+                            app_lock.display_name_mode = false;
+                            app_lock.set_display_name(name);
+                        }
+                        KeyCode::Esc => {
+                            app_lock.display_name_mode = false;
+                            app_lock.input_buffer.clear();
+                        }
+                        KeyCode::Backspace => {
+                            app_lock.input_buffer.pop();
+                        }
+                        KeyCode::Char(c) => {
+                            app_lock.input_buffer.push(c);
+                        }
+                        _ => {}
+                    }
+                // ── 编辑搜索排除关键词 ──────────────────────────────
+                } else if app_lock.exclude_input_mode {
+                    match key.code {
+                        KeyCode::Enter => {
+                            let input = std::mem::take(&mut app_lock.input_buffer);
+                            app_lock.exclude_input_mode = false;
+                            pending_action = Some(PendingAction::SetExcludePatterns(input));
+                        }
+                        KeyCode::Esc => {
+                            app_lock.exclude_input_mode = false;
+                            app_lock.input_buffer.clear();
+                        }
+                        KeyCode::Backspace => {
+                            app_lock.input_buffer.pop();
+                        }
+                        KeyCode::Char(c) => {
+                            app_lock.input_buffer.push(c);
+                        }
+                        _ => {}
+                    }
+                // ── 跳转时间点输入模式 ─────────────────────────────
+                } else if app_lock.seek_input_mode {
+                    match key.code {
+                        KeyCode::Enter => {
+                            let input = app_lock.input_buffer.clone();
+                            app_lock.seek_input_mode = false;
+                            app_lock.input_buffer.clear();
+                            if !input.is_empty() {
+                                pending_action = Some(PendingAction::SeekTo(input));
+                            }
+                        }
+                        KeyCode::Esc => {
+                            app_lock.seek_input_mode = false;
+                            app_lock.input_buffer.clear();
+                        }
+                        KeyCode::Backspace => {
+                            app_lock.input_buffer.pop();
+                        }
+                        KeyCode::Char(c) if c.is_ascii_digit() || c == ':' => {
+                            app_lock.input_buffer.push(c);
+                        }
+                        _ => {}
+                    }
+                // ── 精确音量输入模式 ─────────────────────────────────
+                } else if app_lock.volume_input_mode {
+                    match key.code {
+                        KeyCode::Enter => {
+                            let input = app_lock.input_buffer.clone();
+                            app_lock.volume_input_mode = false;
+                            app_lock.input_buffer.clear();
+                            if !input.is_empty() {
+                                pending_action = Some(PendingAction::SetVolume(input));
+                            }
+                        }
+                        KeyCode::Esc => {
+                            app_lock.volume_input_mode = false;
+                            app_lock.input_buffer.clear();
+                        }
+                        KeyCode::Backspace => {
+                            app_lock.input_buffer.pop();
+                        }
+                        KeyCode::Char(c) if app_lock.input_buffer.chars().count() < 8 => {
+                            app_lock.input_buffer.push(c);
+                        }
+                        _ => {}
+                    }
+                // ── 搜索结果页码输入模式 ─────────────────────────────
+                } else if app_lock.page_input_mode {
+                    match key.code {
+                        KeyCode::Enter => {
+                            let input = app_lock.input_buffer.clone();
+                            app_lock.page_input_mode = false;
+                            app_lock.input_buffer.clear();
+                            if !input.is_empty() {
+                                pending_action = Some(PendingAction::JumpToPage(input));
+                            }
+                        }
+                        KeyCode::Esc => {
+                            app_lock.page_input_mode = false;
+                            app_lock.input_buffer.clear();
+                        }
+                        KeyCode::Backspace => {
+                            app_lock.input_buffer.pop();
+                        }
+                        KeyCode::Char(c)
+                            if c.is_ascii_digit() && app_lock.input_buffer.len() < 6 =>
+                        {
+                            app_lock.input_buffer.push(c);
+                        }
+                        _ => {}
+                    }
                 // ── 移动模式：分组选择浮层 ─────────────────────────────
                 } else if app_lock.move_mode {
                     match key.code {
@@ -290,13 +1040,11 @@ async fn main() -> Result<()> {
                 // ── 新建分组输入模式 ─────────────────────────────
                 } else if app_lock.group_input_mode {
                     match key.code {
-                        KeyCode::Enter => {
-                            if !app_lock.input_buffer.is_empty() {
-                                let name = app_lock.input_buffer.clone();
-                                app_lock.group_input_mode = false;
-                                app_lock.input_buffer.clear();
-                                pending_action = Some(PendingAction::CreateGroup(name));
-                            }
+                        KeyCode::Enter if !app_lock.input_buffer.is_empty() => {
+                            let name = app_lock.input_buffer.clone();
+                            app_lock.group_input_mode = false;
+                            app_lock.input_buffer.clear();
+                            pending_action = Some(PendingAction::CreateGroup(name));
                         }
                         KeyCode::Esc => {
                             app_lock.group_input_mode = false;
@@ -310,18 +1058,37 @@ async fn main() -> Result<()> {
                         }
                         _ => {}
                     }
+                // ── 播放历史快照：输入分组名 ─────────────────────────────
+                } else if app_lock.snapshot_input_mode {
+                    match key.code {
+                        KeyCode::Enter => {
+                            let name = app_lock.input_buffer.clone();
+                            app_lock.snapshot_input_mode = false;
+                            app_lock.input_buffer.clear();
+                            app_lock.snapshot_history(&name);
+                        }
+                        KeyCode::Esc => {
+                            app_lock.snapshot_input_mode = false;
+                            app_lock.input_buffer.clear();
+                        }
+                        KeyCode::Backspace => {
+                            app_lock.input_buffer.pop();
+                        }
+                        KeyCode::Char(c) => {
+                            app_lock.input_buffer.push(c);
+                        }
+                        _ => {}
+                    }
                 // ── 搜索关键词输入模式 ─────────────────────────────────
                 } else if app_lock.input_mode {
                     match key.code {
-                        KeyCode::Enter => {
-                            if !app_lock.input_buffer.is_empty() {
-                                let keyword = app_lock.input_buffer.clone();
-                                app_lock.add_to_search_history(&keyword);
-                                app_lock.history_reset();
-                                app_lock.input_mode = false;
-                                app_lock.input_buffer.clear();
-                                pending_action = Some(PendingAction::Search(keyword));
-                            }
+                        KeyCode::Enter if !app_lock.input_buffer.is_empty() => {
+                            let keyword = app_lock.input_buffer.clone();
+                            app_lock.add_to_search_history(&keyword);
+                            app_lock.history_reset();
+                            app_lock.input_mode = false;
+                            app_lock.input_buffer.clear();
+                            pending_action = Some(PendingAction::Search(keyword));
                         }
                         KeyCode::Esc => {
                             app_lock.history_reset();
@@ -334,6 +1101,9 @@ async fn main() -> Result<()> {
                         KeyCode::Down => {
                             app_lock.history_next();
                         }
+                        KeyCode::Tab => {
+                            app_lock.accept_search_suggestion();
+                        }
                         KeyCode::Backspace => {
                             app_lock.input_buffer.pop();
                             // 输入时退出历史导航模式
@@ -346,11 +1116,162 @@ async fn main() -> Result<()> {
                         }
                         _ => {}
                     }
+                // ── 收藏过滤输入模式 ───────────────────────────────
+                } else if app_lock.filter_input_mode {
+                    match key.code {
+                        KeyCode::Enter => {
+                            app_lock.filter_input_mode = false;
+                        }
+                        KeyCode::Esc => {
+                            app_lock.clear_favorite_filter();
+                        }
+                        KeyCode::Up => {
+                            app_lock.select_prev_favorite();
+                        }
+                        KeyCode::Down => {
+                            app_lock.select_next_favorite();
+                        }
+                        KeyCode::Backspace => {
+                            app_lock.pop_filter_char();
+                        }
+                        KeyCode::Char(c) => {
+                            app_lock.push_filter_char(c);
+                        }
+                        _ => {}
+                    }
+                // ── 全屏“正在播放”视图：只响应播放相关按键 ───────────
+                } else if app_lock.now_playing_mode {
+                    let playing = matches!(
+                        app_lock.status,
+                        PlayerStatus::Playing | PlayerStatus::Paused
+                    );
+                    match key.code {
+                        KeyCode::Char('q') => {
+                            pending_action = app_lock.request_quit().then_some(PendingAction::Quit);
+                        }
+                        KeyCode::Char('?') => {
+                            app_lock.help_mode = true;
+                            app_lock.help_scroll = 0;
+                        }
+                        KeyCode::Char('n') | KeyCode::Esc => {
+                            app_lock.now_playing_mode = false;
+                        }
+                        KeyCode::Char(' ') => {
+                            pending_action = Some(PendingAction::TogglePause);
+                        }
+                        KeyCode::Right if playing => {
+                            pending_action = Some(PendingAction::SeekForward);
+                        }
+                        KeyCode::Left if playing => {
+                            pending_action = Some(PendingAction::SeekBackward);
+                        }
+                        // 章节跳转，没有章节时等同于快进 / 快退
+                        KeyCode::Char(']') if playing => {
+                            pending_action = Some(PendingAction::NextChapter);
+                        }
+                        KeyCode::Char('[') if playing => {
+                            pending_action = Some(PendingAction::PrevChapter);
+                        }
+                        KeyCode::Char('0') | KeyCode::Home => {
+                            pending_action = Some(PendingAction::RestartTrack);
+                        }
+                        KeyCode::Char('+') | KeyCode::Char('=') => {
+                            pending_action = Some(PendingAction::VolumeUp);
+                        }
+                        KeyCode::Char('-') => {
+                            pending_action = Some(PendingAction::VolumeDown);
+                        }
+                        KeyCode::Char('x') => {
+                            pending_action = Some(PendingAction::ToggleMute);
+                        }
+                        KeyCode::Char('E') => {
+                            pending_action = Some(PendingAction::CycleEqPreset);
+                        }
+                        KeyCode::Char('S') => {
+                            app_lock.toggle_start_offset();
+                        }
+                        KeyCode::Char('u') => {
+                            pending_action = Some(PendingAction::RefreshCurrent);
+                        }
+                        KeyCode::Char('w') => {
+                            pending_action = Some(PendingAction::DownloadCurrent);
+                        }
+                        KeyCode::Char('y') => {
+                            pending_action = Some(PendingAction::CopyTrackUrl);
+                        }
+                        // 回到上一首实际播放过的歌曲 / 下一首（回退过时先沿原路返回）
+                        KeyCode::Char('p') => {
+                            pending_action = Some(PendingAction::HistoryBack);
+                        }
+                        KeyCode::Char('N') => {
+                            pending_action = Some(PendingAction::NextTrack);
+                        }
+                        KeyCode::Char('f') if playing => {
+                            app_lock.toggle_favorite();
+                        }
+                        KeyCode::Char('m') => {
+                            app_lock.toggle_play_mode();
+                        }
+                        _ => {}
+                    }
+                // ── 播放历史视图 ─────────────────────────────────────
+                } else if app_lock.history_mode {
+                    match key.code {
+                        KeyCode::Char('q') => {
+                            pending_action = app_lock.request_quit().then_some(PendingAction::Quit);
+                        }
+                        KeyCode::Char('?') => {
+                            app_lock.help_mode = true;
+                            app_lock.help_scroll = 0;
+                        }
+                        KeyCode::Char('h') | KeyCode::Esc => {
+                            app_lock.toggle_history_mode();
+                        }
+                        KeyCode::Up => {
+                            app_lock.select_prev_history();
+                        }
+                        KeyCode::Down => {
+                            app_lock.select_next_history();
+                        }
+                        KeyCode::Enter => {
+                            if let Some(item) = app_lock.get_selected_history() {
+                                let song = item.title.clone();
+                                let source = item.source.clone();
+                                let path = item.local_path.clone();
+                                app_lock.add_log(format!("从历史播放: {} [{}]", song, source));
+                                app_lock.current_source = source;
+                                app_lock.playback_context = PlaybackContext::Favorites;
+                                pending_action = Some(PendingAction::SearchAndPlay(song, path));
+                            }
+                        }
+                        KeyCode::Char('f') => {
+                            app_lock.favorite_selected_history();
+                        }
+                        KeyCode::Char('F') => {
+                            app_lock.snapshot_input_mode = true;
+                            app_lock.input_buffer.clear();
+                        }
+                        KeyCode::Char(' ') => {
+                            pending_action = Some(PendingAction::TogglePause);
+                        }
+                        _ => {}
+                    }
                 } else if !app_lock.search_results.is_empty() {
                     // 搜索结果状态下的键盘操作
                     match key.code {
                         KeyCode::Char('q') => {
-                            pending_action = Some(PendingAction::Quit);
+                            pending_action = app_lock.request_quit().then_some(PendingAction::Quit);
+                        }
+                        KeyCode::Char('?') => {
+                            app_lock.help_mode = true;
+                            app_lock.help_scroll = 0;
+                        }
+                        // 翻页或解析中按 Esc 只取消加载，不离开搜索结果
+                        KeyCode::Esc
+                            if app_lock.is_loading_page
+                                || matches!(app_lock.status, PlayerStatus::Searching) =>
+                        {
+                            pending_action = Some(PendingAction::CancelSearch);
                         }
                         KeyCode::Esc => {
                             app_lock.clear_search_results();
@@ -365,6 +1286,9 @@ async fn main() -> Result<()> {
                         KeyCode::Enter => {
                             pending_action = Some(PendingAction::PlaySelectedResult);
                         }
+                        KeyCode::Char('v') => {
+                            pending_action = Some(PendingAction::PreviewResult);
+                        }
                         KeyCode::Char('f') => {
                             app_lock.toggle_favorite_from_search_result();
                         }
@@ -374,26 +1298,112 @@ async fn main() -> Result<()> {
                         KeyCode::Right => {
                             pending_action = Some(PendingAction::NextPage);
                         }
+                        // 重新搜索当前关键词，刷新结果
+                        KeyCode::Char('r') => {
+                            pending_action = Some(PendingAction::RepeatSearch);
+                        }
                         KeyCode::Left => {
                             pending_action = Some(PendingAction::PrevPage);
                         }
+                        // 输入页码直接跳转
+                        KeyCode::Char('G') => {
+                            app_lock.page_input_mode = true;
+                            app_lock.input_buffer.clear();
+                        }
                         KeyCode::Char(' ') => {
                             pending_action = Some(PendingAction::TogglePause);
                         }
+                        KeyCode::Char('n') => {
+                            app_lock.now_playing_mode = true;
+                        }
+                        KeyCode::Char('i') if app_lock.status.is_error() => {
+                            app_lock.toggle_error_detail();
+                        }
+                        KeyCode::Char('L') => {
+                            app_lock.toggle_logs();
+                        }
                         _ => {}
                     }
                 } else {
                     match key.code {
                         KeyCode::Char('q') => {
-                            pending_action = Some(PendingAction::Quit);
+                            pending_action = app_lock.request_quit().then_some(PendingAction::Quit);
                         }
                         KeyCode::Char('?') => {
                             app_lock.help_mode = true;
+                            app_lock.help_scroll = 0;
                         }
                         KeyCode::Char('s') => {
                             app_lock.input_mode = true;
                             app_lock.input_buffer.clear();
                         }
+                        // 重复上一次搜索
+                        KeyCode::Char('r') => {
+                            pending_action = Some(PendingAction::RepeatSearch);
+                        }
+                        // 跳转到指定时间点（仅播放中）
+                        KeyCode::Char('t')
+                            if matches!(
+                                app_lock.status,
+                                PlayerStatus::Playing | PlayerStatus::Paused
+                            ) =>
+                        {
+                            app_lock.seek_input_mode = true;
+                            app_lock.input_buffer.clear();
+                        }
+                        // 从头播放当前歌曲
+                        KeyCode::Char('0') | KeyCode::Home => {
+                            pending_action = Some(PendingAction::RestartTrack);
+                        }
+                        // 输入精确音量（仅播放中）
+                        KeyCode::Char('V')
+                            if matches!(
+                                app_lock.status,
+                                PlayerStatus::Playing | PlayerStatus::Paused
+                            ) =>
+                        {
+                            app_lock.volume_input_mode = true;
+                            app_lock.input_buffer.clear();
+                        }
+                        // 过滤收藏列表
+                        KeyCode::Char('/') => {
+                            app_lock.filter_input_mode = true;
+                        }
+                        // 搜索 / 解析中按 Esc 取消，不必等待超时
+                        KeyCode::Esc if matches!(app_lock.status, PlayerStatus::Searching) => {
+                            pending_action = Some(PendingAction::CancelSearch);
+                        }
+                        KeyCode::Esc if !app_lock.favorite_filter.is_empty() => {
+                            app_lock.clear_favorite_filter();
+                        }
+                        // 切换到播放历史视图
+                        KeyCode::Char('h') => {
+                            app_lock.toggle_history_mode();
+                        }
+                        // 收听统计
+                        KeyCode::Char('T') => {
+                            app_lock.stats_mode = true;
+                        }
+                        // 全屏“正在播放”视图
+                        KeyCode::Char('n') => {
+                            app_lock.now_playing_mode = true;
+                        }
+                        // 展开/收起错误详情
+                        KeyCode::Char('i') if app_lock.status.is_error() => {
+                            app_lock.toggle_error_detail();
+                        }
+                        // 显示/隐藏日志面板
+                        KeyCode::Char('L') => {
+                            app_lock.toggle_logs();
+                        }
+                        // 重新加载配置文件
+                        KeyCode::Char('C') => {
+                            pending_action = Some(PendingAction::ReloadConfig);
+                        }
+                        // 在 $EDITOR 中编辑配置文件，返回后重新加载
+                        KeyCode::Char(',') => {
+                            pending_action = Some(PendingAction::EditConfig);
+                        }
                         // 新建分组
                         KeyCode::Char('g') => {
                             app_lock.group_input_mode = true;
@@ -405,6 +1415,28 @@ async fn main() -> Result<()> {
                             app_lock.rename_mode = true;
                             app_lock.input_buffer = current_name;
                         }
+                        // 设置选中收藏的显示名称（预填当前名称）
+                        KeyCode::Char('e') => {
+                            app_lock.start_edit_display_name();
+                        }
+                        // 编辑搜索排除关键词（预填当前关键词，逗号分隔）
+                        KeyCode::Char('B') => {
+                            app_lock.input_buffer = app_lock.exclude_patterns.join(", ");
+                            app_lock.exclude_input_mode = true;
+                        }
+                        // 清空全部收藏（需要二次确认）
+                        KeyCode::Char('X') => {
+                            let total: usize = app_lock.groups.iter().map(|g| g.items.len()).sum();
+                            if total > 0 {
+                                app_lock.clear_favorites_confirm_mode = true;
+                                app_lock.add_log(format!(
+                                    "⚠ 清空全部 {} 首收藏? 按 y 确认，任意键取消",
+                                    total
+                                ));
+                            } else {
+                                app_lock.add_log("收藏列表已经是空的".to_string());
+                            }
+                        }
                         // 删除当前分组（需要二次确认）
                         KeyCode::Char('D') => {
                             if app_lock.groups.len() > 1 {
@@ -418,10 +1450,24 @@ async fn main() -> Result<()> {
                                 app_lock.add_log("至少保留一个分组".to_string());
                             }
                         }
+                        // 切换收藏列表的显示排序 / 按当前排序重排并保存
+                        KeyCode::Char('o') => {
+                            app_lock.cycle_favorite_sort();
+                        }
+                        KeyCode::Char('O') => {
+                            app_lock.save_favorite_sort();
+                        }
                         // 移动歌曲到其他分组
                         KeyCode::Char('M') => {
                             app_lock.enter_move_mode();
                         }
+                        // 调整收藏顺序（影响顺序播放 / 列表循环）
+                        KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                            app_lock.move_favorite_up();
+                        }
+                        KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                            app_lock.move_favorite_down();
+                        }
                         // 切换分组
                         KeyCode::Tab => {
                             app_lock.select_next_group();
@@ -450,6 +1496,17 @@ async fn main() -> Result<()> {
                         KeyCode::Down => {
                             app_lock.select_next_favorite();
                         }
+                        // 部分终端不区分 Shift+Enter 和 Enter，a 作为等价按键
+                        KeyCode::Enter if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                            if let Some((song, path)) = app_lock.start_play_all_favorites() {
+                                pending_action = Some(PendingAction::SearchAndPlay(song, path));
+                            }
+                        }
+                        KeyCode::Char('a') => {
+                            if let Some((song, path)) = app_lock.start_play_all_favorites() {
+                                pending_action = Some(PendingAction::SearchAndPlay(song, path));
+                            }
+                        }
                         KeyCode::Enter => {
                             if let Some(item) = app_lock.get_selected_favorite() {
                                 let song = item.title.clone();
@@ -457,7 +1514,7 @@ async fn main() -> Result<()> {
                                 let path = item.local_path.clone();
                                 app_lock.add_log(format!("从收藏播放: {} [{}]", song, source));
                                 app_lock.current_source = source;
-                                app_lock.playing_from_search = false;
+                                app_lock.playback_context = PlaybackContext::Favorites;
                                 pending_action = Some(PendingAction::SearchAndPlay(song, path));
                             }
                         }
@@ -480,27 +1537,79 @@ async fn main() -> Result<()> {
                                 pending_action = Some(PendingAction::SeekBackward);
                             }
                         }
+                        // 章节跳转，没有章节时等同于快进 / 快退
+                        KeyCode::Char(']') | KeyCode::Char('[')
+                            if matches!(
+                                app_lock.status,
+                                PlayerStatus::Playing | PlayerStatus::Paused
+                            ) =>
+                        {
+                            pending_action = Some(if key.code == KeyCode::Char(']') {
+                                PendingAction::NextChapter
+                            } else {
+                                PendingAction::PrevChapter
+                            });
+                        }
                         KeyCode::Char('+') | KeyCode::Char('=') => {
                             pending_action = Some(PendingAction::VolumeUp);
                         }
                         KeyCode::Char('-') => {
                             pending_action = Some(PendingAction::VolumeDown);
                         }
+                        KeyCode::Char('x') => {
+                            pending_action = Some(PendingAction::ToggleMute);
+                        }
+                        KeyCode::Char('E') => {
+                            pending_action = Some(PendingAction::CycleEqPreset);
+                        }
+                        KeyCode::Char('S') => {
+                            app_lock.toggle_start_offset();
+                        }
+                        KeyCode::Char('u') => {
+                            pending_action = Some(PendingAction::RefreshCurrent);
+                        }
+                        KeyCode::Char('w') => {
+                            pending_action = Some(PendingAction::DownloadCurrent);
+                        }
+                        KeyCode::Char('y') => {
+                            pending_action = Some(PendingAction::CopyTrackUrl);
+                        }
+                        // 回到上一首实际播放过的歌曲 / 下一首（回退过时先沿原路返回）
+                        KeyCode::Char('p') => {
+                            pending_action = Some(PendingAction::HistoryBack);
+                        }
+                        KeyCode::Char('N') => {
+                            pending_action = Some(PendingAction::NextTrack);
+                        }
                         _ => {}
                     }
                 }
             }
         }
 
+        // 退出信号优先于其它动作：即使同一轮已有待处理的操作也直接退出
+        if let Some(name) = exit_signal.or_else(|| exit_signals.try_recv().ok()) {
+            app.lock().await.add_log(format!("收到 {}，正在退出", name));
+            pending_action = Some(PendingAction::Quit);
+        }
+
         match pending_action {
             Some(PendingAction::Search(keyword)) => {
                 player.search(keyword).await;
                 continue;
             }
+            Some(PendingAction::RepeatSearch) => {
+                player.repeat_search().await;
+                continue;
+            }
             Some(PendingAction::PlaySelectedResult) => {
                 player.play_selected_result().await;
                 continue;
             }
+            Some(PendingAction::PreviewResult) => {
+                player.preview_selected_result().await;
+                continue;
+            }
             Some(PendingAction::SearchAndPlay(song, local_path)) => {
                 player.search_and_play(song, local_path).await;
                 continue;
@@ -517,6 +1626,14 @@ async fn main() -> Result<()> {
                 player.seek_backward().await;
                 continue;
             }
+            Some(PendingAction::NextChapter) => {
+                player.step_chapter(true).await;
+                continue;
+            }
+            Some(PendingAction::PrevChapter) => {
+                player.step_chapter(false).await;
+                continue;
+            }
             Some(PendingAction::VolumeUp) => {
                 player.volume_up().await;
                 continue;
@@ -538,6 +1655,89 @@ async fn main() -> Result<()> {
                 app_lock.create_group(name);
                 continue;
             }
+            Some(PendingAction::SeekTo(input)) => {
+                player.seek_to(input).await;
+                continue;
+            }
+            Some(PendingAction::RestartTrack) => {
+                player.restart_current().await;
+                continue;
+            }
+            Some(PendingAction::ChangeVolume(delta)) => {
+                player.change_volume(delta).await;
+                continue;
+            }
+            Some(PendingAction::SetVolume(input)) => {
+                player.set_volume(input).await;
+                continue;
+            }
+            Some(PendingAction::JumpToPage(input)) => {
+                player.jump_to_page(input).await;
+                continue;
+            }
+            Some(PendingAction::CycleEqPreset) => {
+                player.cycle_eq_preset().await;
+                continue;
+            }
+            Some(PendingAction::ToggleMute) => {
+                player.toggle_mute().await;
+                continue;
+            }
+            Some(PendingAction::RefreshCurrent) => {
+                player.refresh_current().await;
+                continue;
+            }
+            Some(PendingAction::DownloadCurrent) => {
+                player.download_current().await;
+                continue;
+            }
+            Some(PendingAction::CopyTrackUrl) => {
+                player.copy_track_url().await;
+                continue;
+            }
+            Some(PendingAction::NextTrack) => {
+                player.play_next().await;
+                continue;
+            }
+            Some(PendingAction::SetExcludePatterns(input)) => {
+                player.set_exclude_patterns(input).await;
+                continue;
+            }
+            Some(PendingAction::HistoryBack) => {
+                player.play_back_in_history().await;
+                continue;
+            }
+            Some(PendingAction::PrevTrack) => {
+                player.play_previous_or_back().await;
+                continue;
+            }
+            Some(PendingAction::CancelSearch) => {
+                player.cancel_search().await;
+                continue;
+            }
+            Some(PendingAction::ReloadConfig) => {
+                reload_config(&player, &app, &mut theme, favorites_override.as_deref()).await;
+                tick_rate = player.config().await.ui.tick_rate();
+                continue;
+            }
+            Some(PendingAction::EditConfig) => {
+                // 编辑器运行期间不能有线程在读终端输入，先停掉事件流，结束后重新创建
+                drop(events);
+                let result = edit_config_in_editor(&mut terminal).await?;
+                events = EventStream::new();
+                // 离开备用屏幕后终端里的封面已经消失，下次绘制时重新写入
+                shown_cover = None;
+                match result {
+                    Ok(message) => {
+                        app.lock().await.add_log(message);
+                        reload_config(&player, &app, &mut theme, favorites_override.as_deref())
+                            .await;
+                        tick_rate = player.config().await.ui.tick_rate();
+                    }
+                    Err(message) => app.lock().await.add_log(message),
+                }
+                continue;
+            }
             Some(PendingAction::Quit) => {
                 player.quit().await;
                 break;
@@ -545,12 +1745,17 @@ async fn main() -> Result<()> {
             None => {}
         }
 
-        if last_tick.elapsed() >= tick_rate {
+        // 播放结束由 IPC 事件驱动，立即切到下一首；tick 只负责定期刷新进度
+        if player.handle_playback_ended().await {
+            last_tick = Instant::now();
+        } else if last_tick.elapsed() >= tick_rate {
+            app.lock().await.advance_animation();
             player.check_and_play_next().await;
             last_tick = Instant::now();
         }
     }
 
+    drop(remote);
     terminal_cleanup_guard.disarm();
     disable_raw_mode()?;
     execute!(
@@ -558,5 +1763,22 @@ async fn main() -> Result<()> {
         LeaveAlternateScreen,
         DisableBracketedPaste
     )?;
+
+    if let Err(e) = app.lock().await.save_session() {
+        eprintln!("⚠ {}", e);
+    }
+    if let Err(e) = app.lock().await.save_stats() {
+        eprintln!("⚠ {}", e);
+    }
+    if player.config().await.playback.persist_runtime_state {
+        let app_lock = app.lock().await;
+        if let Err(e) = Config::persist_runtime_state(
+            app_lock.play_mode_config_value(),
+            app_lock.volume,
+            app_lock.eq_preset,
+        ) {
+            eprintln!("⚠ 保存播放模式和音量失败: {}", e);
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,253 @@
+//! MPRIS（D-Bus）集成：让键盘媒体键和桌面环境的“正在播放”组件可以控制 maboroshi。
+//!
+//! 仅在启用 `mpris` feature 且目标平台为 Linux 时生效；其余情况下 [`spawn`] 直接返回 None。
+
+use crate::app::App;
+use crate::net::AudioBackend;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+/// 桌面端发来的控制命令，由主循环转换为对应的 `Player` 操作
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(not(all(feature = "mpris", target_os = "linux")), allow(dead_code))]
+pub enum MprisCommand {
+    PlayPause,
+    Next,
+    Previous,
+}
+
+/// 注册 MPRIS 服务并返回命令接收端。D-Bus 不可用或注册失败时返回 None（不影响正常播放）。
+#[cfg(all(feature = "mpris", target_os = "linux"))]
+pub async fn spawn(
+    app: Arc<Mutex<App>>,
+    audio: Arc<AudioBackend>,
+) -> Option<mpsc::Receiver<MprisCommand>> {
+    let (tx, rx) = mpsc::channel(16);
+    match imp::serve(app.clone(), audio, tx).await {
+        Ok(()) => Some(rx),
+        Err(e) => {
+            app.lock()
+                .await
+                .add_log(format!("⚠ MPRIS 注册失败，媒体键不可用: {}", e));
+            None
+        }
+    }
+}
+
+#[cfg(not(all(feature = "mpris", target_os = "linux")))]
+pub async fn spawn(
+    _app: Arc<Mutex<App>>,
+    _audio: Arc<AudioBackend>,
+) -> Option<mpsc::Receiver<MprisCommand>> {
+    None
+}
+
+#[cfg(all(feature = "mpris", target_os = "linux"))]
+mod imp {
+    use super::MprisCommand;
+    use crate::app::App;
+    use crate::net::{AudioBackend, PauseState};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::{mpsc, Mutex};
+    use zbus::zvariant::{ObjectPath, OwnedValue, Value};
+
+    const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+    /// 状态变化的检查间隔：只在播放状态或歌曲标题变化时才发出 PropertiesChanged 信号
+    const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+    struct Root;
+
+    #[zbus::interface(name = "org.mpris.MediaPlayer2")]
+    impl Root {
+        fn raise(&self) {}
+
+        fn quit(&self) {}
+
+        #[zbus(property)]
+        fn can_quit(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn can_raise(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn has_track_list(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn identity(&self) -> String {
+            "Maboroshi".to_string()
+        }
+
+        #[zbus(property)]
+        fn supported_uri_schemes(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        #[zbus(property)]
+        fn supported_mime_types(&self) -> Vec<String> {
+            Vec::new()
+        }
+    }
+
+    struct PlayerIface {
+        app: Arc<Mutex<App>>,
+        audio: Arc<AudioBackend>,
+        tx: mpsc::Sender<MprisCommand>,
+    }
+
+    impl PlayerIface {
+        fn send(&self, cmd: MprisCommand) {
+            let _ = self.tx.try_send(cmd);
+        }
+    }
+
+    fn status_text(state: PauseState) -> &'static str {
+        match state {
+            PauseState::Playing => "Playing",
+            PauseState::Paused => "Paused",
+            PauseState::Stopped => "Stopped",
+        }
+    }
+
+    fn build_metadata(title: &str) -> HashMap<String, OwnedValue> {
+        let mut metadata = HashMap::new();
+        if title.is_empty() {
+            return metadata;
+        }
+        let track_id = ObjectPath::from_static_str_unchecked("/org/maboroshi/track/current");
+        if let Ok(v) = OwnedValue::try_from(Value::from(track_id)) {
+            metadata.insert("mpris:trackid".to_string(), v);
+        }
+        if let Ok(v) = OwnedValue::try_from(Value::from(title.to_string())) {
+            metadata.insert("xesam:title".to_string(), v);
+        }
+        metadata
+    }
+
+    #[zbus::interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl PlayerIface {
+        fn play_pause(&self) {
+            self.send(MprisCommand::PlayPause);
+        }
+
+        async fn play(&self) {
+            if self.audio.get_pause_state().await == PauseState::Paused {
+                self.send(MprisCommand::PlayPause);
+            }
+        }
+
+        async fn pause(&self) {
+            if self.audio.get_pause_state().await == PauseState::Playing {
+                self.send(MprisCommand::PlayPause);
+            }
+        }
+
+        fn next(&self) {
+            self.send(MprisCommand::Next);
+        }
+
+        fn previous(&self) {
+            self.send(MprisCommand::Previous);
+        }
+
+        #[zbus(property)]
+        async fn playback_status(&self) -> String {
+            status_text(self.audio.get_pause_state().await).to_string()
+        }
+
+        #[zbus(property)]
+        async fn metadata(&self) -> HashMap<String, OwnedValue> {
+            build_metadata(&self.app.lock().await.current_song)
+        }
+
+        #[zbus(property)]
+        fn can_go_next(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_go_previous(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_play(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_pause(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_seek(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn can_control(&self) -> bool {
+            true
+        }
+    }
+
+    pub(super) async fn serve(
+        app: Arc<Mutex<App>>,
+        audio: Arc<AudioBackend>,
+        tx: mpsc::Sender<MprisCommand>,
+    ) -> zbus::Result<()> {
+        // 按 MPRIS 规范为每个实例追加 instance 后缀，避免多开时总线名冲突
+        let bus_name = format!(
+            "org.mpris.MediaPlayer2.maboroshi.instance{}",
+            std::process::id()
+        );
+        let player = PlayerIface {
+            app: Arc::clone(&app),
+            audio: Arc::clone(&audio),
+            tx,
+        };
+        let connection = zbus::connection::Builder::session()?
+            .name(bus_name)?
+            .serve_at(OBJECT_PATH, Root)?
+            .serve_at(OBJECT_PATH, player)?
+            .build()
+            .await?;
+
+        tokio::spawn(async move {
+            let Ok(iface_ref) = connection
+                .object_server()
+                .interface::<_, PlayerIface>(OBJECT_PATH)
+                .await
+            else {
+                return;
+            };
+            let mut last_state = None;
+            let mut last_title = String::new();
+            loop {
+                tokio::time::sleep(WATCH_INTERVAL).await;
+                let state = audio.get_pause_state().await;
+                let title = app.lock().await.current_song.clone();
+
+                let iface = iface_ref.get().await;
+                let emitter = iface_ref.signal_emitter();
+                if last_state != Some(state) {
+                    let _ = iface.playback_status_changed(emitter).await;
+                    last_state = Some(state);
+                }
+                if title != last_title {
+                    let _ = iface.metadata_changed(emitter).await;
+                    last_title = title;
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+//! 演练模式（`--dry-run`）：不启动 yt-dlp 和 mpv，只把将要执行的完整命令行写进日志，
+//! 并用固定的假数据和一段模拟播放走完界面流程。用于在没有网络或没有安装依赖的环境里
+//! 调试配置（cookies、音频格式、mpv 参数）和界面。
+
+use super::mpv::{PauseState, PlaybackState};
+use super::source::{BoxFuture, LogFn, MusicSource, ResultFn};
+use super::ytdlp::{self, CachedSong, StreamInfo};
+use super::SearchResult;
+use crate::config::Config;
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+
+/// 演练模式日志的前缀
+pub const DRY_RUN_PREFIX: &str = "[dry-run]";
+
+/// 模拟播放的时长（秒）
+const FAKE_DURATION: f64 = 20.0;
+
+/// 模拟播放的进度刷新间隔
+const FAKE_TICK: Duration = Duration::from_millis(200);
+
+/// 每个源返回的假搜索结果数量（不超过 `max_results`）
+const FAKE_RESULTS: usize = 3;
+
+/// 只记录 yt-dlp 命令行、返回假数据的音乐来源
+pub struct DryRunSource;
+
+impl MusicSource for DryRunSource {
+    fn search<'a>(
+        &'a self,
+        config: &'a Config,
+        keyword: &'a str,
+        page: usize,
+        log_fn: &'a mut LogFn<'_>,
+        on_result: &'a mut ResultFn<'_>,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>>> {
+        Box::pin(async move {
+            let mut results = Vec::new();
+            if ytdlp::is_url(keyword) {
+                let source = ytdlp::source_for_url(config, keyword);
+                let args = ytdlp::url_search_args(config, keyword, page);
+                log_fn(format!(
+                    "{} {}",
+                    DRY_RUN_PREFIX,
+                    ytdlp::ytdlp_command_line(config, &args)
+                ));
+                results.extend(fake_results(config, keyword, &source, page));
+            } else {
+                for source in config.search_sources() {
+                    let args = ytdlp::keyword_search_args(config, &source, keyword, page);
+                    log_fn(format!(
+                        "{} {}",
+                        DRY_RUN_PREFIX,
+                        ytdlp::ytdlp_command_line(config, &args)
+                    ));
+                    results.extend(fake_results(config, keyword, &source, page));
+                }
+            }
+            for result in &results {
+                on_result(result.clone());
+            }
+            log_fn(format!("找到 {} 个结果", results.len()));
+            Ok(results)
+        })
+    }
+
+    fn resolve_url<'a>(
+        &'a self,
+        config: &'a Config,
+        keyword: &'a str,
+        source: &'a str,
+        log_fn: &'a mut LogFn<'_>,
+    ) -> BoxFuture<'a, Result<StreamInfo>> {
+        Box::pin(async move {
+            let args = ytdlp::resolve_args(config, keyword, source);
+            log_fn(format!(
+                "{} {}",
+                DRY_RUN_PREFIX,
+                ytdlp::ytdlp_command_line(config, &args)
+            ));
+            Ok(StreamInfo {
+                url: format!("https://example.invalid/dry-run/{}.m4a", source),
+                local_path: None,
+                webpage_url: Some(format!("https://example.invalid/dry-run/{}", source)),
+                thumbnail: None,
+            })
+        })
+    }
+
+    fn cached<'a>(
+        &'a self,
+        _config: &'a Config,
+        _keyword: &'a str,
+        _source: &'a str,
+    ) -> BoxFuture<'a, Option<CachedSong>> {
+        Box::pin(async { None })
+    }
+
+    fn evict<'a>(
+        &'a self,
+        _config: &'a Config,
+        _keyword: &'a str,
+        _source: &'a str,
+    ) -> BoxFuture<'a, bool> {
+        Box::pin(async { false })
+    }
+}
+
+/// 某个源第 `page` 页的假搜索结果
+fn fake_results(config: &Config, keyword: &str, source: &str, page: usize) -> Vec<SearchResult> {
+    let count = FAKE_RESULTS.min(config.search.max_results.max(1));
+    (1..=count)
+        .map(|i| {
+            let index = (page.max(1) - 1) * count + i;
+            SearchResult {
+                title: format!("{} (dry-run {} #{})", keyword, source, index),
+                duration: Some(FAKE_DURATION as u64),
+                uploader: Some("dry-run".to_string()),
+                view_count: None,
+                source: source.to_string(),
+                webpage_url: Some(format!(
+                    "https://example.invalid/dry-run/{}/{}",
+                    source, index
+                )),
+            }
+        })
+        .collect()
+}
+
+/// 启动一段模拟播放：播放中每个刷新间隔推进一次进度，暂停时停住，
+/// 播完后把状态置为 Stopped 并通知播放结束（与 mpv 自行退出时相同）
+pub fn spawn_fake_playback(
+    state: Arc<Mutex<PlaybackState>>,
+    ended_tx: watch::Sender<()>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        {
+            let mut state = state.lock().await;
+            state.duration = FAKE_DURATION;
+            state.audio_codec = Some("dry-run".to_string());
+        }
+        loop {
+            tokio::time::sleep(FAKE_TICK).await;
+            let mut state = state.lock().await;
+            if state.pause_state != PauseState::Playing {
+                continue;
+            }
+            state.time_pos = (state.time_pos + FAKE_TICK.as_secs_f64()).min(FAKE_DURATION);
+            state.progress = state.time_pos / FAKE_DURATION;
+            if state.time_pos >= FAKE_DURATION {
+                state.pause_state = PauseState::Stopped;
+                drop(state);
+                ended_tx.send_replace(());
+                return;
+            }
+        }
+    })
+}
+
+/// 模拟 mpv 对 IPC 命令的响应：只处理暂停和跳转，其余命令（音量等由调用方自行更新状态）忽略
+pub async fn simulate_command(state: &Mutex<PlaybackState>, args: &[&str]) {
+    let mut state = state.lock().await;
+    match args {
+        ["set_property", "pause", value] if state.pause_state != PauseState::Stopped => {
+            state.pause_state = if *value == "yes" {
+                PauseState::Paused
+            } else {
+                PauseState::Playing
+            };
+        }
+        ["seek", seconds, mode] => {
+            let Ok(seconds) = seconds.parse::<f64>() else {
+                return;
+            };
+            let target = if *mode == "absolute" {
+                seconds
+            } else {
+                state.time_pos + seconds
+            };
+            // 留出最后一个刷新间隔，让模拟播放照常走到结束
+            state.time_pos = target.clamp(0.0, FAKE_DURATION - FAKE_TICK.as_secs_f64());
+            state.progress = state.time_pos / FAKE_DURATION;
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,156 @@
+//! 测试用的音乐来源：搜索和解析返回预先设定的结果，并记录解析次数，用于在没有 yt-dlp 和网络的
+//! 环境里测试播放器的搜索、播放和重试流程。
+
+use super::source::{BoxFuture, LogFn, MusicSource, ResultFn};
+use super::ytdlp::{CachedSong, StreamInfo, YtDlpError};
+use super::SearchResult;
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// 解析歌曲时的结果
+enum Resolve {
+    Ok,
+    /// 普通失败（网络错误等），播放器会重试
+    Fail(String),
+    /// 歌曲不可用，播放器不再重试
+    Unavailable,
+}
+
+pub struct MockSource {
+    /// 每一页的搜索结果；只有一页时每页都返回它（与不支持分页的搜索源相同）
+    pages: Vec<Vec<SearchResult>>,
+    search_error: Option<String>,
+    resolve: Resolve,
+    resolve_calls: Arc<AtomicUsize>,
+}
+
+impl MockSource {
+    /// 每一页都返回 `results`，解析总是成功
+    pub fn new(results: Vec<SearchResult>) -> Self {
+        Self::paged(vec![results])
+    }
+
+    /// 第 n 页返回 `pages[n - 1]`，超出时返回空列表；只有一页时每页都返回它
+    pub fn paged(pages: Vec<Vec<SearchResult>>) -> Self {
+        Self {
+            pages,
+            search_error: None,
+            resolve: Resolve::Ok,
+            resolve_calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// 搜索时先逐条回调结果，最后返回错误
+    pub fn failing_search(mut self, message: &str) -> Self {
+        self.search_error = Some(message.to_string());
+        self
+    }
+
+    /// 解析以普通错误失败
+    pub fn failing_resolve(mut self, message: &str) -> Self {
+        self.resolve = Resolve::Fail(message.to_string());
+        self
+    }
+
+    /// 解析以歌曲不可用失败
+    pub fn unavailable(mut self) -> Self {
+        self.resolve = Resolve::Unavailable;
+        self
+    }
+
+    /// 解析次数计数器，交给 `AudioBackend` 后仍可读取
+    pub fn resolve_calls(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.resolve_calls)
+    }
+}
+
+/// 搜索源 `source` 下标题为 `title` 的搜索结果
+pub fn result(title: &str, source: &str) -> SearchResult {
+    SearchResult {
+        title: title.to_string(),
+        duration: Some(180),
+        uploader: None,
+        view_count: None,
+        source: source.to_string(),
+        webpage_url: Some(watch_url(title)),
+    }
+}
+
+/// 歌曲的观看链接（解析结果中的页面地址）
+pub fn watch_url(title: &str) -> String {
+    format!("https://example.invalid/watch/{}", title)
+}
+
+impl MusicSource for MockSource {
+    fn search<'a>(
+        &'a self,
+        _config: &'a Config,
+        _keyword: &'a str,
+        page: usize,
+        _log_fn: &'a mut LogFn<'_>,
+        on_result: &'a mut ResultFn<'_>,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>>> {
+        Box::pin(async move {
+            let results = match self.pages.as_slice() {
+                [only] => only.clone(),
+                pages => pages
+                    .get(page.saturating_sub(1))
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            for result in &results {
+                on_result(result.clone());
+            }
+            match &self.search_error {
+                Some(message) => Err(anyhow!("{}", message)),
+                None => Ok(results),
+            }
+        })
+    }
+
+    fn resolve_url<'a>(
+        &'a self,
+        _config: &'a Config,
+        keyword: &'a str,
+        _source: &'a str,
+        _log_fn: &'a mut LogFn<'_>,
+    ) -> BoxFuture<'a, Result<StreamInfo>> {
+        Box::pin(async move {
+            self.resolve_calls.fetch_add(1, Ordering::SeqCst);
+            match &self.resolve {
+                Resolve::Ok => Ok(StreamInfo {
+                    url: format!("https://example.invalid/stream/{}", keyword),
+                    local_path: None,
+                    webpage_url: Some(watch_url(keyword)),
+                    thumbnail: None,
+                }),
+                Resolve::Fail(message) => Err(anyhow!("{}", message)),
+                Resolve::Unavailable => Err(YtDlpError::new(
+                    "yt-dlp 执行失败".to_string(),
+                    b"ERROR: [youtube] abc: Video unavailable",
+                )
+                .into()),
+            }
+        })
+    }
+
+    fn cached<'a>(
+        &'a self,
+        _config: &'a Config,
+        _keyword: &'a str,
+        _source: &'a str,
+    ) -> BoxFuture<'a, Option<CachedSong>> {
+        Box::pin(async { None })
+    }
+
+    fn evict<'a>(
+        &'a self,
+        _config: &'a Config,
+        _keyword: &'a str,
+        _source: &'a str,
+    ) -> BoxFuture<'a, bool> {
+        Box::pin(async { false })
+    }
+}
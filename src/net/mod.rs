@@ -1,52 +1,237 @@
+mod dry_run;
+#[cfg(test)]
+pub(crate) mod mock;
 mod mpv;
+mod source;
 mod ytdlp;
 
-pub use mpv::{PauseState, PlaybackState};
-pub use ytdlp::SearchResult;
+pub use dry_run::DRY_RUN_PREFIX;
+pub use mpv::{list_audio_devices, Chapter, PauseState, PlaybackState, VOLUME_MAX};
+pub use source::{create_source, MusicSource, YtDlpSource};
+
+use source::is_cache_valid;
+pub use ytdlp::{
+    cookie_args, error_detail, get_extended_path, is_unavailable, SearchResult, StreamInfo,
+};
+
+/// 搜索结果被 `exclude_patterns` 过滤时的日志前缀，日志转发任务据此把它显示给用户
+pub const EXCLUDED_LOG_PREFIX: &str = "🚫";
+
+/// 均衡器滤镜在 mpv `af` 列表中的标签，切换预设时只替换这一项，不影响 `mpv_args` 中的其他滤镜
+const EQ_FILTER_LABEL: &str = "@maboroshi-eq";
+/// 试听时降低音量的滤镜，启动 mpv 时加入；不改动 volume 属性，实例退出后自然失效
+const PREVIEW_FILTER: &str = "@maboroshi-preview:lavfi=[volume=0.5]";
+
+/// 加载阶段日志的前缀，日志转发任务据此更新界面上的加载指示
+pub const STAGE_LOG_PREFIX: &str = "⏳";
+
+/// 播放一首歌时依次经历的加载阶段
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadStage {
+    /// yt-dlp 解析播放地址
+    Resolving,
+    /// mpv 已启动，等待 IPC 就绪
+    Connecting,
+    /// IPC 已连接，等待 mpv 缓冲出第一段音频
+    Buffering,
+}
+
+impl LoadStage {
+    pub const COUNT: usize = 3;
+
+    /// 阶段序号，从 1 开始
+    pub fn step(self) -> usize {
+        match self {
+            LoadStage::Resolving => 1,
+            LoadStage::Connecting => 2,
+            LoadStage::Buffering => 3,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LoadStage::Resolving => "解析中",
+            LoadStage::Connecting => "连接中",
+            LoadStage::Buffering => "缓冲中",
+        }
+    }
+
+    /// 该阶段开始时输出的日志行
+    pub fn log_line(self) -> String {
+        format!("{} {}", STAGE_LOG_PREFIX, self.label())
+    }
 
-use crate::config::Config;
+    /// 从 `log_line` 生成的日志行还原阶段
+    pub fn from_log_line(line: &str) -> Option<Self> {
+        let label = line.strip_prefix(STAGE_LOG_PREFIX)?.trim();
+        [
+            LoadStage::Resolving,
+            LoadStage::Connecting,
+            LoadStage::Buffering,
+        ]
+        .into_iter()
+        .find(|stage| stage.label() == label)
+    }
+}
+
+use crate::config::{Config, EqPreset, VideoMode};
 use anyhow::Result;
 use std::process::Stdio;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::time::Duration;
 use tokio::process::Command;
-use tokio::sync::Mutex;
+use tokio::sync::{watch, Mutex};
 use tokio::task::JoinHandle;
 
-use ytdlp::UrlCache;
+/// [`AudioBackend::search_and_play`] 的可选项
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlayOptions {
+    /// 不为 None 时从该位置（秒）开始播放
+    pub start_secs: Option<u64>,
+    /// 不为 None 时与上一首淡入淡出衔接
+    pub fade: Option<Duration>,
+    /// 试听：启动 mpv 时就加上降低音量的滤镜，开头不会以原音量播放
+    pub preview: bool,
+}
 
 pub struct AudioBackend {
     socket_path: String,
-    cache: Mutex<UrlCache>,
-    config: Config,
-    /// Lock ordering: ipc_task → playback_state → mpv_process
+    /// 搜索和解析播放地址的后端，启动时按 `search.backend` 选定
+    source: Box<dyn MusicSource>,
+    /// 运行时可通过 `update_config` 替换；使用时先取快照，不与其他锁嵌套
+    config: Mutex<Config>,
+    /// Lock ordering: ipc_task → playback_state → mpv_process → fade_tasks
     ipc_task: Mutex<Option<JoinHandle<()>>>,
     playback_state: Arc<Mutex<PlaybackState>>,
     mpv_process: Mutex<Option<tokio::process::Child>>,
+    /// 淡入淡出切歌时的渐变任务：淡出任务持有旧 mpv 实例，abort 时旧实例随之结束
+    fade_tasks: Mutex<Vec<JoinHandle<()>>>,
+    /// mpv 自行退出（播放结束或出错）时发出通知；主动 quit 不会触发
+    playback_ended: watch::Sender<()>,
+    /// 演练模式：只记录 yt-dlp / mpv 命令行，不启动进程，播放由模拟任务代替
+    dry_run: bool,
 }
 
 impl AudioBackend {
     pub fn new(config: Config) -> Self {
+        let source = create_source(&config.search.backend)
+            .unwrap_or_else(|_| Box::new(YtDlpSource::default()));
+        Self::with_source(config, source)
+    }
+
+    /// 使用指定的音乐来源创建后端，不按 `search.backend` 选择（测试中传入模拟来源）
+    pub fn with_source(config: Config, source: Box<dyn MusicSource>) -> Self {
+        let max_volume = config.playback.volume_ceiling();
+        let eq_preset = config.playback.eq_preset().unwrap_or_default();
         Self {
             socket_path: config.paths.socket_path.clone(),
-            cache: Mutex::new(UrlCache::new()),
-            config,
+            source,
+            config: Mutex::new(config),
             ipc_task: Mutex::new(None),
             playback_state: Arc::new(Mutex::new(PlaybackState {
                 progress: 0.0,
+                time_pos: 0.0,
+                duration: 0.0,
                 pause_state: PauseState::Stopped,
-                volume: 100,
+                volume: 100.min(max_volume),
+                max_volume,
+                muted: false,
+                pre_mute_volume: 100,
+                eq_preset,
+                keep_open: false,
+                eof_reached: false,
+                paused_for_cache: false,
+                cache_buffering: 100,
+                audio_bitrate: None,
+                audio_codec: None,
+                fade_target: None,
+                mpv_errors: Vec::new(),
+                chapters: Vec::new(),
+                chapter: None,
+                loudness: None,
             })),
             mpv_process: Mutex::new(None),
+            fade_tasks: Mutex::new(Vec::new()),
+            playback_ended: watch::Sender::new(()),
+            dry_run: false,
         }
     }
 
-    fn is_cache_valid(&self, cached_at: SystemTime) -> bool {
-        if let Ok(elapsed) = SystemTime::now().duration_since(cached_at) {
-            elapsed.as_secs() < self.config.cache.url_cache_ttl
-        } else {
-            false
+    /// 开启演练模式（`--dry-run`）：搜索和解析返回假数据，mpv 不启动，
+    /// 命令行以 [`DRY_RUN_PREFIX`] 开头写入日志
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        if dry_run {
+            self.source = Box::new(dry_run::DryRunSource);
         }
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 保留当前的音乐来源，只把播放换成演练模式的模拟播放，测试中不启动 mpv
+    #[cfg(test)]
+    pub(crate) fn with_fake_playback(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// 组装 mpv 启动参数（不含播放地址）：内置参数在前，`playback.mpv_args` 中的合法参数追加在后。
+    /// 设置了 `cache_secs` / `cache_size` 时以它们为准，`mpv_args` 中对应的选项会被忽略；
+    /// 均衡器滤镜追加在最后，不会被 `mpv_args` 中的 `--af` 覆盖
+    pub fn build_mpv_args(config: &Config, volume: u8, muted: bool, eq: EqPreset) -> Vec<String> {
+        let mut args = Vec::new();
+        // window 模式交给 mpv 打开视频窗口，其余模式都只播放音频
+        if config.playback.video_mode() != Some(VideoMode::Window) {
+            args.push("--no-video".to_string());
+        }
+        args.extend([
+            format!("--input-ipc-server={}", config.paths.socket_path),
+            "--cache=yes".to_string(),
+            format!("--volume={}", volume.min(config.playback.volume_ceiling())),
+            format!("--mute={}", if muted { "yes" } else { "no" }),
+        ]);
+        if let Some(device) = config
+            .playback
+            .audio_device
+            .as_deref()
+            .filter(|d| !d.is_empty())
+        {
+            args.push(format!("--audio-device={}", device));
+        }
+        let (cache_secs, cache_size, _) = config.playback.cache_settings();
+        if let Some(secs) = cache_secs {
+            args.push(format!("--cache-secs={}", secs));
+        }
+        if let Some(size) = cache_size {
+            args.push(format!("--demuxer-max-bytes={}MiB", size));
+        }
+        args.extend(config.playback.mpv_extra_args().0);
+        if let Some(filter) = eq.af_filter() {
+            args.push(format!("--af-add={}:{}", EQ_FILTER_LABEL, filter));
+        }
+        if config.ui.level_meter {
+            args.push(format!(
+                "--af-add=@{}:lavfi=[ebur128=metadata=1]",
+                mpv::METER_FILTER_LABEL
+            ));
+        }
+        args
+    }
+
+    /// 当前配置的快照
+    async fn config(&self) -> Config {
+        self.config.lock().await.clone()
+    }
+
+    /// 替换运行时配置。socket 路径在创建时已固定，不受影响；
+    /// 正在进行的解析继续使用旧配置，之后的操作使用新配置。
+    pub async fn update_config(&self, config: Config) {
+        self.playback_state.lock().await.max_volume = config.playback.volume_ceiling();
+        *self.config.lock().await = config;
+    }
+
+    /// 当前的音量上限（`playback.max_volume`）
+    pub async fn max_volume(&self) -> u8 {
+        self.playback_state.lock().await.max_volume
     }
 
     // ── 搜索 ──────────────────────────────────────────────────────────────────
@@ -58,66 +243,154 @@ impl AudioBackend {
         log_fn: F,
     ) -> Result<Vec<SearchResult>>
     where
-        F: FnMut(String),
+        F: FnMut(String) + Send,
     {
-        ytdlp::search(&self.config, keyword, page, log_fn).await
+        self.search_streaming(keyword, page, log_fn, |_| {}).await
+    }
+
+    /// 与 `search` 相同，但每得到一条结果就立即交给 `on_result`，用于边搜索边显示
+    pub async fn search_streaming<F, R>(
+        &self,
+        keyword: &str,
+        page: usize,
+        log_fn: F,
+        on_result: R,
+    ) -> Result<Vec<SearchResult>>
+    where
+        F: FnMut(String) + Send,
+        R: FnMut(SearchResult) + Send,
+    {
+        let config = self.config().await;
+        let mut log_fn = log_fn;
+        let mut on_result = on_result;
+        let mut on_kept = |result: SearchResult| {
+            if !config.search.is_excluded(&result.title) {
+                on_result(result);
+            }
+        };
+        let mut results = self
+            .source
+            .search(&config, keyword, page, &mut log_fn, &mut on_kept)
+            .await?;
+
+        let before = results.len();
+        results.retain(|result| !config.search.is_excluded(&result.title));
+        let excluded = before - results.len();
+        if excluded > 0 {
+            log_fn(format!(
+                "{} 已按 exclude_patterns 过滤 {} 个结果",
+                EXCLUDED_LOG_PREFIX, excluded
+            ));
+        }
+        Ok(results)
     }
 
     // ── 搜索并播放 ────────────────────────────────────────────────────────────
 
+    /// 移除歌曲的 URL 缓存，下次播放时重新解析；返回是否确实有缓存被移除
+    pub async fn evict_cached(&self, keyword: &str, source: &str) -> bool {
+        let config = self.config().await;
+        self.source.evict(&config, keyword, source).await
+    }
+
+    /// `source` 为解析歌曲时使用的搜索源（如 `yt`、`bili`）；`options.start_secs` 不为 None 时从该位置开始播放。
+    /// `options.fade` 不为 None 时与上一首淡入淡出衔接：旧实例在后台淡出，新实例从 0 音量淡入；
+    /// 否则按 `playback.fade_in_secs` 让新实例淡入。返回实际播放的地址及本地路径、页面地址。
     pub async fn search_and_play<F>(
         &self,
         keyword: &str,
+        source: &str,
         local_path_hint: Option<String>,
+        options: PlayOptions,
         mut log_fn: F,
-    ) -> Result<Option<String>>
+    ) -> Result<StreamInfo>
     where
-        F: FnMut(String),
+        F: FnMut(String) + Send,
     {
-        // 清理旧进程和 IPC 端点
-        log_fn("清理旧进程和 IPC 端点".to_string());
-        self.quit().await;
-        mpv::cleanup_ipc_file(&self.socket_path);
-
-        let (stream_url, out_local_path) = if let Some(path) = local_path_hint {
-            if std::path::Path::new(&path).exists() {
+        let PlayOptions {
+            start_secs,
+            fade,
+            preview,
+        } = options;
+        let config = self.config().await;
+        // Windows named pipe 在旧实例退出前无法被新实例复用，只能硬切；演练模式没有真实实例可渐变
+        let fade = fade.filter(|_| cfg!(unix) && !self.dry_run);
+        let fade_in = fade.or_else(|| {
+            (config.playback.fade_in_secs > 0 && !self.dry_run)
+                .then(|| Duration::from_secs(config.playback.fade_in_secs))
+        });
+
+        log_fn(LoadStage::Resolving.log_line());
+        let info = match local_path_hint {
+            Some(path) if std::path::Path::new(&path).exists() => {
                 log_fn(format!("✓ 命中缓存路径: {}", path));
-                (path.clone(), Some(path))
-            } else {
-                log_fn(format!("⚠ 缓存路径失效或文件不存在，重新解析: {}", path));
-                let info = ytdlp::fetch_stream_url(
-                    &self.config,
-                    &self.cache,
-                    keyword,
-                    |cached_at| self.is_cache_valid(cached_at),
-                    &mut log_fn,
-                )
-                .await?;
-                (info.url, info.local_path)
+                // 本地文件没有页面地址和缩略图，之前解析过时从缓存中取
+                let cached = self.source.cached(&config, keyword, source).await;
+                StreamInfo {
+                    url: path.clone(),
+                    local_path: Some(path),
+                    webpage_url: cached.as_ref().and_then(|c| c.webpage_url.clone()),
+                    thumbnail: cached.and_then(|c| c.thumbnail),
+                }
+            }
+            hint => {
+                if let Some(path) = hint {
+                    log_fn(format!("⚠ 缓存路径失效或文件不存在，重新解析: {}", path));
+                }
+                self.source
+                    .resolve_url(&config, keyword, source, &mut log_fn)
+                    .await?
             }
-        } else {
-            let info = ytdlp::fetch_stream_url(
-                &self.config,
-                &self.cache,
-                keyword,
-                |cached_at| self.is_cache_valid(cached_at),
-                &mut log_fn,
-            )
-            .await?;
-            (info.url, info.local_path)
         };
 
-        // 启动 mpv
+        // 解析完成后再处理旧实例，让上一首在解析期间继续播放
+        if let Some(duration) = fade {
+            log_fn(format!("淡出上一首（{}秒）", duration.as_secs()));
+            self.fade_out_current(duration).await;
+        } else {
+            log_fn("清理旧进程和 IPC 端点".to_string());
+            self.quit().await;
+            mpv::cleanup_ipc_file(&self.socket_path);
+        }
+
+        // 启动 mpv，沿用上一首的音量和静音状态；淡入时从 0 音量开始
+        log_fn(LoadStage::Connecting.log_line());
         log_fn("启动 mpv 播放器".to_string());
         let path = ytdlp::get_extended_path();
+        // 上限调低后，从下一首开始按新上限启动
+        let (volume, muted, eq, keep_open) = {
+            let mut state = self.playback_state.lock().await;
+            state.volume = state.volume.min(state.max_volume);
+            state.pre_mute_volume = state.pre_mute_volume.min(state.max_volume);
+            (state.volume, state.muted, state.eq_preset, state.keep_open)
+        };
+        let start_volume = if fade_in.is_some() { 0 } else { volume };
+        let mut args = Self::build_mpv_args(&config, start_volume, muted, eq);
+        if keep_open {
+            args.push("--keep-open=yes".to_string());
+        }
+        if preview {
+            args.push(format!("--af-add={}", PREVIEW_FILTER));
+        }
+        if let Some(secs) = start_secs.filter(|secs| *secs > 0) {
+            log_fn(format!("跳过开头，从第 {} 秒开始播放", secs));
+            args.push(format!("--start={}", secs));
+        }
+        if self.dry_run {
+            let args = args.into_iter().chain([info.url.clone()]);
+            log_fn(format!(
+                "{} {}",
+                DRY_RUN_PREFIX,
+                ytdlp::command_line("mpv", args)
+            ));
+            self.start_fake_playback().await;
+            log_fn(LoadStage::Buffering.log_line());
+            return Ok(info);
+        }
         let child = Command::new("mpv")
             .env("PATH", &path)
-            .args([
-                "--no-video",
-                &format!("--input-ipc-server={}", self.socket_path),
-                "--cache=yes",
-                &stream_url,
-            ])
+            .args(args)
+            .arg(&info.url)
             .stdout(Stdio::null())
             .stderr(Stdio::null())
             .kill_on_drop(true)
@@ -132,7 +405,7 @@ impl AudioBackend {
 
         // 等待 IPC 端点就绪（最多等待 network.play_timeout 秒）
         let socket_path = self.socket_path.clone();
-        let wait_timeout_secs = self.config.network.play_timeout.max(1);
+        let wait_timeout_secs = config.network.play_timeout.max(1);
         let max_attempts = (wait_timeout_secs * 10) as usize;
         let mut socket_ready = false;
         for i in 0..max_attempts {
@@ -158,16 +431,167 @@ impl AudioBackend {
             {
                 let mut state = self.playback_state.lock().await;
                 state.progress = 0.0;
+                state.time_pos = 0.0;
+                state.duration = 0.0;
                 state.pause_state = PauseState::Playing;
+                state.eof_reached = false;
+                state.paused_for_cache = false;
+                state.cache_buffering = 100;
+                state.audio_bitrate = None;
+                state.audio_codec = None;
+                state.chapters.clear();
+                state.chapter = None;
+                state.loudness = None;
+                state.fade_target = fade_in.map(|_| volume);
             }
 
             // 3. 启动 IPC 监听任务
-            let handle =
-                mpv::spawn_ipc_task(self.socket_path.clone(), Arc::clone(&self.playback_state));
+            let handle = mpv::spawn_ipc_task(
+                self.socket_path.clone(),
+                Arc::clone(&self.playback_state),
+                self.playback_ended.clone(),
+            );
             *ipc_task_lock = Some(handle);
         }
+        log_fn(LoadStage::Buffering.log_line());
+
+        // 淡入新实例；IPC 稍后才就绪时由淡入任务自行重试连接。
+        // 切歌时 quit / fade_out_current 会 abort 淡入任务
+        if let Some(duration) = fade_in {
+            let task = mpv::spawn_fade_in(
+                self.socket_path.clone(),
+                Arc::clone(&self.playback_state),
+                volume,
+                duration,
+            );
+            self.fade_tasks.lock().await.push(task);
+        }
+
+        Ok(info)
+    }
+
+    /// 演练模式下代替 mpv：重置播放状态并启动模拟播放任务（与 IPC 任务一样由 quit 结束）
+    async fn start_fake_playback(&self) {
+        let mut ipc_task_lock = self.ipc_task.lock().await;
+        if let Some(task) = ipc_task_lock.take() {
+            task.abort();
+        }
+        {
+            let mut state = self.playback_state.lock().await;
+            state.progress = 0.0;
+            state.time_pos = 0.0;
+            state.duration = 0.0;
+            state.pause_state = PauseState::Playing;
+            state.eof_reached = false;
+            state.paused_for_cache = false;
+            state.cache_buffering = 100;
+            state.audio_bitrate = None;
+            state.audio_codec = None;
+            state.chapters.clear();
+            state.chapter = None;
+            state.loudness = None;
+            state.fade_target = None;
+        }
+        *ipc_task_lock = Some(dry_run::spawn_fake_playback(
+            Arc::clone(&self.playback_state),
+            self.playback_ended.clone(),
+        ));
+    }
+
+    /// 把当前 mpv 实例交给后台淡出任务，不触发播放结束通知。
+    /// 之前尚未结束的渐变任务会被 abort（对应的旧实例随之结束）。
+    async fn fade_out_current(&self, duration: Duration) {
+        // 遵守锁定顺序 (ipc_task → playback_state → mpv_process → fade_tasks)
+        // 1. 先停掉旧实例的 IPC 监听，旧实例退出时不再通知播放结束
+        let mut ipc_task_lock = self.ipc_task.lock().await;
+        if let Some(task) = ipc_task_lock.take() {
+            task.abort();
+        }
+
+        // 2. 读取当前音量作为淡出起点
+        let volume = self.playback_state.lock().await.volume;
+
+        // 3. 取出旧进程，交给淡出任务持有
+        let child = self.mpv_process.lock().await.take();
+
+        // 4. 清理之前的渐变任务，登记新的淡出任务
+        let mut fade_tasks = self.fade_tasks.lock().await;
+        for task in fade_tasks.drain(..) {
+            task.abort();
+        }
+        let fade_out = mpv::spawn_fade_out(&self.socket_path, child, volume, duration).await;
+        fade_tasks.push(fade_out);
+    }
+
+    // ── 预取 ──────────────────────────────────────────────────────────────────
+
+    /// 提前解析歌曲的音频流并写入 URL 缓存，让切歌时可以直接命中缓存。
+    /// 已有有效缓存时不调用 yt-dlp，返回 Ok(false)；完成解析返回 Ok(true)。
+    pub async fn prefetch_url<F>(&self, keyword: &str, source: &str, log_fn: F) -> Result<bool>
+    where
+        F: FnMut(String) + Send,
+    {
+        let config = self.config().await;
+        let cached = self
+            .source
+            .cached(&config, keyword, source)
+            .await
+            .is_some_and(|c| is_cache_valid(&config, c.cached_at));
+        if cached {
+            return Ok(false);
+        }
+        let mut log_fn = log_fn;
+        self.source
+            .resolve_url(&config, keyword, source, &mut log_fn)
+            .await?;
+        Ok(true)
+    }
 
-        Ok(out_local_path)
+    /// 当前歌曲的播放地址是否仍可直接复用：本地文件存在，或缓存的地址尚未超过 `url_cache_ttl`
+    pub async fn stream_still_valid(
+        &self,
+        keyword: &str,
+        source: &str,
+        local_path: Option<&str>,
+    ) -> bool {
+        if local_path.is_some_and(|path| std::path::Path::new(path).exists()) {
+            return true;
+        }
+        let config = self.config().await;
+        self.source
+            .cached(&config, keyword, source)
+            .await
+            .is_some_and(|c| is_cache_valid(&config, c.cached_at))
+    }
+
+    // ── 保存到本地 ────────────────────────────────────────────────────────────
+
+    /// 把歌曲保存到下载目录，返回保存路径。优先复用已有的离线缓存文件（包括 URL 缓存中记录的），
+    /// 没有时再用原始查询让 yt-dlp 下载。
+    pub async fn download<F>(
+        &self,
+        keyword: &str,
+        source: &str,
+        local_path: Option<String>,
+        log_fn: F,
+    ) -> Result<std::path::PathBuf>
+    where
+        F: FnMut(String),
+    {
+        if self.dry_run {
+            anyhow::bail!("演练模式下不下载");
+        }
+        let config = self.config().await;
+        let cached_path = self
+            .source
+            .cached(&config, keyword, source)
+            .await
+            .and_then(|c| c.local_path);
+        let local_file = local_path
+            .into_iter()
+            .chain(cached_path)
+            .find(|p| std::path::Path::new(p).exists());
+        ytdlp::download_track(&config, keyword, source, local_file.as_deref(), log_fn).await
     }
 
     // ── 播放状态查询 ──────────────────────────────────────────────────────────
@@ -190,16 +614,173 @@ impl AudioBackend {
         self.playback_state.lock().await.volume
     }
 
+    /// 当前静音状态与静音前的音量
+    pub async fn get_mute(&self) -> (bool, u8) {
+        let state = self.playback_state.lock().await;
+        (state.muted, state.pre_mute_volume)
+    }
+
+    /// 取走 mpv 上报的错误日志
+    pub async fn take_mpv_errors(&self) -> Vec<String> {
+        std::mem::take(&mut self.playback_state.lock().await.mpv_errors)
+    }
+
+    /// 设置下一次启动 mpv 时使用的音量（用于恢复会话）
+    pub async fn set_initial_volume(&self, volume: u8) {
+        let mut state = self.playback_state.lock().await;
+        state.volume = volume.min(state.max_volume);
+    }
+
+    /// 订阅播放结束事件：每当 mpv 自行退出，接收端会被标记为 changed
+    pub fn subscribe_playback_ended(&self) -> watch::Receiver<()> {
+        self.playback_ended.subscribe()
+    }
+
+    /// 当前播放位置（秒）
+    pub async fn get_time_pos(&self) -> f64 {
+        self.playback_state.lock().await.time_pos
+    }
+
+    /// 当前曲目总时长（秒），未知时为 0
+    pub async fn get_duration(&self) -> f64 {
+        self.playback_state.lock().await.duration
+    }
+
+    /// 当前音频的编码名和码率（bit/s），mpv 尚未上报时为 None
+    pub async fn get_audio_quality(&self) -> (Option<String>, Option<u32>) {
+        let state = self.playback_state.lock().await;
+        (state.audio_codec.clone(), state.audio_bitrate)
+    }
+
+    /// 是否有淡入 / 淡出正在进行，此时 mpv 回报的音量只是渐变中的中间值
+    pub async fn is_fading(&self) -> bool {
+        self.fade_tasks
+            .lock()
+            .await
+            .iter()
+            .any(|task| !task.is_finished())
+    }
+
+    /// 当前曲目的章节列表和当前章节序号
+    pub async fn get_chapters(&self) -> (Vec<Chapter>, Option<usize>) {
+        let state = self.playback_state.lock().await;
+        (state.chapters.clone(), state.chapter)
+    }
+
+    /// 当前瞬时响度（LUFS），没有电平数据时为 None
+    pub async fn get_loudness(&self) -> Option<f64> {
+        self.playback_state.lock().await.loudness
+    }
+
+    /// 正在等待缓冲时返回缓冲填充百分比，否则返回 None
+    pub async fn get_buffering(&self) -> Option<u8> {
+        let state = self.playback_state.lock().await;
+        state.paused_for_cache.then_some(state.cache_buffering)
+    }
+
     // ── mpv IPC 命令 ──────────────────────────────────────────────────────────
 
     pub async fn send_command(&self, args: Vec<&str>) -> Result<()> {
+        if self.dry_run {
+            dry_run::simulate_command(&self.playback_state, &args).await;
+            return Ok(());
+        }
         mpv::send_command(&self.socket_path, args).await
     }
 
-    /// 调整音量。delta 为正数增大，负数减小；范围 0–130。
-    pub async fn change_volume(&self, delta: i32) -> Result<()> {
-        let delta_str = delta.to_string();
-        self.send_command(vec!["add", "volume", &delta_str]).await
+    /// 调整音量。delta 为正数增大，负数减小；结果限制在 0–`max_volume`，返回调整后的音量。
+    pub async fn change_volume(&self, delta: i32) -> Result<u8> {
+        let current = self.get_volume().await as i32;
+        let target = (current + delta).clamp(0, self.max_volume().await as i32) as u8;
+        let target_str = target.to_string();
+        self.send_command(vec!["set", "volume", &target_str])
+            .await?;
+        self.playback_state.lock().await.volume = target;
+        Ok(target)
+    }
+
+    /// 设置为指定音量（超出 `max_volume` 时截断），返回设置后的音量。
+    /// 先乐观地写入状态，实际生效的音量随后由 IPC 的 volume 观察者修正。
+    pub async fn set_volume(&self, volume: u8) -> Result<u8> {
+        let target = volume.min(self.max_volume().await);
+        let target_str = target.to_string();
+        self.send_command(vec!["set_property", "volume", &target_str])
+            .await?;
+        self.playback_state.lock().await.volume = target;
+        Ok(target)
+    }
+
+    /// 切换静音。静音时记住当前音量，取消静音时恢复该音量；返回切换后的 (是否静音, 音量)。
+    pub async fn toggle_mute(&self) -> Result<(bool, u8)> {
+        let (muted, volume, pre_mute_volume) = {
+            let state = self.playback_state.lock().await;
+            (
+                state.muted,
+                state.volume,
+                state.pre_mute_volume.min(state.max_volume),
+            )
+        };
+        if muted {
+            let volume_str = pre_mute_volume.to_string();
+            self.send_command(vec!["set_property", "volume", &volume_str])
+                .await?;
+            self.send_command(vec!["set_property", "mute", "no"])
+                .await?;
+            let mut state = self.playback_state.lock().await;
+            state.muted = false;
+            state.volume = pre_mute_volume;
+            Ok((false, pre_mute_volume))
+        } else {
+            self.send_command(vec!["set_property", "mute", "yes"])
+                .await?;
+            let mut state = self.playback_state.lock().await;
+            state.muted = true;
+            state.pre_mute_volume = volume;
+            Ok((true, volume))
+        }
+    }
+
+    /// 切换均衡器预设：记录下来供之后启动的实例使用，并通过 `af` 命令替换当前实例的滤镜。
+    /// 没有正在运行的 mpv 时返回连接错误，预设仍会在下一首生效
+    pub async fn set_eq_preset(&self, preset: EqPreset) -> Result<()> {
+        self.playback_state.lock().await.eq_preset = preset;
+        self.send_command(vec!["af", "remove", EQ_FILTER_LABEL])
+            .await?;
+        if let Some(filter) = preset.af_filter() {
+            let entry = format!("{}:{}", EQ_FILTER_LABEL, filter);
+            self.send_command(vec!["af", "add", &entry]).await?;
+        }
+        Ok(())
+    }
+
+    /// 设置播完后是否停在结尾而不退出（单曲循环时开启，以便直接跳回开头重播）。
+    /// 记录下来供之后启动的实例使用；与当前设置相同时不发送命令，可以每个 tick 调用
+    pub async fn set_keep_open(&self, keep_open: bool) {
+        {
+            let mut state = self.playback_state.lock().await;
+            if state.keep_open == keep_open {
+                return;
+            }
+            state.keep_open = keep_open;
+        }
+        let value = if keep_open { "yes" } else { "no" };
+        let _ = self
+            .send_command(vec!["set_property", "keep-open", value])
+            .await;
+    }
+
+    /// mpv 是否已播完并停在结尾（仅在开启 `keep-open` 时出现）
+    pub async fn is_eof_reached(&self) -> bool {
+        self.playback_state.lock().await.eof_reached
+    }
+
+    /// 让停在结尾的实例跳回开头继续播放，不重新解析地址、不重启 mpv
+    pub async fn rewind(&self) -> Result<()> {
+        self.send_command(vec!["seek", "0", "absolute"]).await?;
+        self.send_command(vec!["set_property", "pause", "no"])
+            .await?;
+        self.playback_state.lock().await.eof_reached = false;
+        Ok(())
     }
 
     pub async fn seek(&self, seconds: i32) -> Result<()> {
@@ -208,6 +789,20 @@ impl AudioBackend {
             .await
     }
 
+    /// 跳转到第 `index` 章（从 0 开始）
+    pub async fn set_chapter(&self, index: usize) -> Result<()> {
+        let index_str = index.to_string();
+        self.send_command(vec!["set_property", "chapter", &index_str])
+            .await
+    }
+
+    /// 跳转到绝对时间点（秒）
+    pub async fn seek_absolute(&self, seconds: u64) -> Result<()> {
+        let seconds_str = seconds.to_string();
+        self.send_command(vec!["seek", &seconds_str, "absolute"])
+            .await
+    }
+
     // ── 退出 ──────────────────────────────────────────────────────────────────
 
     pub async fn quit(&self) {
@@ -233,10 +828,17 @@ impl AudioBackend {
         mpv::cleanup_ipc_file(&self.socket_path);
 
         // 4. 如果进程还在，通过进程句柄杀掉并等待结束
-        let mut process_lock = self.mpv_process.lock().await;
-        if let Some(mut child) = process_lock.take() {
-            let _ = child.kill().await;
-            let _ = child.wait().await;
+        {
+            let mut process_lock = self.mpv_process.lock().await;
+            if let Some(mut child) = process_lock.take() {
+                let _ = child.kill().await;
+                let _ = child.wait().await;
+            }
+        }
+
+        // 5. 结束仍在淡入淡出的任务（淡出中的旧实例随之结束）
+        for task in self.fade_tasks.lock().await.drain(..) {
+            task.abort();
         }
     }
 }
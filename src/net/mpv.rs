@@ -1,19 +1,83 @@
+use crate::config::EqPreset;
 use anyhow::{Context, Result};
 use serde_json::Value;
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::sync::Mutex;
+use tokio::sync::{watch, Mutex};
 use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
 
 // ── 播放状态 ──────────────────────────────────────────────────────────────────
 
+/// mpv 默认的最大音量（`volume-max`）
+pub const VOLUME_MAX: u8 = 130;
+
 pub struct PlaybackState {
     pub progress: f64,
+    /// 当前播放位置（秒，`time-pos`），用于同步歌词
+    pub time_pos: f64,
+    /// 当前曲目总时长（秒，`duration`），未知时为 0
+    pub duration: f64,
     pub pause_state: PauseState,
-    /// 当前音量 (0–130)，默认 100
+    /// 当前音量 (0–max_volume)，默认 100
     pub volume: u8,
+    /// 音量上限（`playback.max_volume`），mpv 回报的音量超出时截断
+    pub max_volume: u8,
+    /// 是否静音（`mute`），切歌时保持不变
+    pub muted: bool,
+    /// 静音前的音量，取消静音时恢复
+    pub pre_mute_volume: u8,
+    /// 当前均衡器预设，切歌时保持不变
+    pub eq_preset: EqPreset,
+    /// 播完后是否停在结尾而不退出（`keep-open`，单曲循环时开启），切歌时保持不变
+    pub keep_open: bool,
+    /// 开启 `keep-open` 时是否已停在结尾（`eof-reached`），新曲目开始时重置
+    pub eof_reached: bool,
+    /// mpv 是否因缓存不足而暂停（`paused-for-cache`）
+    pub paused_for_cache: bool,
+    /// 缓冲填充百分比（`cache-buffering-state`，0–100），新曲目开始时重置为 100
+    pub cache_buffering: u8,
+    /// 当前音频码率（bit/s，`audio-bitrate`），播放初期尚未上报时为 None，新曲目开始时重置
+    pub audio_bitrate: Option<u32>,
+    /// 当前音频编码名（`audio-codec-name`，如 opus、aac），新曲目开始时重置
+    pub audio_codec: Option<String>,
+    /// 淡入进行中的目标音量；淡入期间 IPC 回报的中间音量不写入 `volume`，切歌打断淡入时音量不会丢失
+    pub fade_target: Option<u8>,
+    /// mpv 上报的错误日志（如音频设备无法打开），等待上层取走并显示
+    pub mpv_errors: Vec<String>,
+    /// 章节列表（`chapter-list`），没有章节时为空，新曲目开始时重置
+    pub chapters: Vec<Chapter>,
+    /// 当前章节序号（`chapter`），位于第一章之前或没有章节时为 None
+    pub chapter: Option<usize>,
+    /// 瞬时响度（LUFS，来自电平表滤镜的 `lavfi.r128.M`），未启用 `ui.level_meter` 或尚无数据时为 None，
+    /// 新曲目开始时重置
+    pub loudness: Option<f64>,
+}
+
+/// 一个章节：标题与起始时间（秒）
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    pub time: f64,
+}
+
+impl Chapter {
+    /// 显示用的章节名，没有标题时为 `第 N 章`（`index` 从 0 开始）
+    pub fn label(&self, index: usize) -> String {
+        if self.title.is_empty() {
+            format!("第 {} 章", index + 1)
+        } else {
+            self.title.clone()
+        }
+    }
 }
 
+/// 最多暂存的 mpv 错误日志条数，超出时丢弃最旧的
+const MPV_ERRORS_MAX: usize = 20;
+
+/// 电平表滤镜（lavfi `ebur128`）在 mpv `af` 列表中的标签，IPC 通过 `af-metadata/<标签>` 读取响度
+pub const METER_FILTER_LABEL: &str = "maboroshi-meter";
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PauseState {
     Paused,
@@ -40,7 +104,6 @@ async fn connect_ipc(path: &str) -> std::io::Result<IpcStream> {
 #[cfg(windows)]
 async fn connect_ipc(path: &str) -> std::io::Result<IpcStream> {
     use tokio::net::windows::named_pipe::ClientOptions;
-    use tokio::time::{sleep, Duration};
 
     // Windows 下如果 named pipe 暂时处于 busy（mpv 还在建立），循环重试一小会儿。
     // ERROR_PIPE_BUSY = 231
@@ -101,8 +164,12 @@ pub async fn send_command(socket_path: &str, args: Vec<&str>) -> Result<()> {
 }
 
 /// 启动 IPC 监听任务，持续读取 mpv property-change 事件并更新 PlaybackState。
-/// 返回任务句柄。
-pub fn spawn_ipc_task(socket_path: String, state: Arc<Mutex<PlaybackState>>) -> JoinHandle<()> {
+/// mpv 退出（连接断开）时通过 `ended_tx` 通知播放已结束。返回任务句柄。
+pub fn spawn_ipc_task(
+    socket_path: String,
+    state: Arc<Mutex<PlaybackState>>,
+    ended_tx: watch::Sender<()>,
+) -> JoinHandle<()> {
     tokio::spawn(async move {
         if let Ok(stream) = connect_ipc(&socket_path).await {
             let (reader, mut writer) = tokio::io::split(stream);
@@ -114,16 +181,51 @@ pub fn spawn_ipc_task(socket_path: String, state: Arc<Mutex<PlaybackState>>) ->
             let observe_pause = serde_json::json!({ "command": ["observe_property", 2, "pause"] });
             let observe_volume =
                 serde_json::json!({ "command": ["observe_property", 3, "volume"] });
+            let observe_paused_for_cache =
+                serde_json::json!({ "command": ["observe_property", 4, "paused-for-cache"] });
+            let observe_buffering =
+                serde_json::json!({ "command": ["observe_property", 5, "cache-buffering-state"] });
+            let observe_time_pos =
+                serde_json::json!({ "command": ["observe_property", 6, "time-pos"] });
+            let observe_duration =
+                serde_json::json!({ "command": ["observe_property", 7, "duration"] });
+            let observe_mute = serde_json::json!({ "command": ["observe_property", 8, "mute"] });
+            let observe_bitrate =
+                serde_json::json!({ "command": ["observe_property", 9, "audio-bitrate"] });
+            let observe_codec =
+                serde_json::json!({ "command": ["observe_property", 10, "audio-codec-name"] });
+            let observe_chapter_list =
+                serde_json::json!({ "command": ["observe_property", 11, "chapter-list"] });
+            let observe_chapter =
+                serde_json::json!({ "command": ["observe_property", 12, "chapter"] });
+            let meter_property = format!("af-metadata/{}", METER_FILTER_LABEL);
+            let observe_meter =
+                serde_json::json!({ "command": ["observe_property", 13, meter_property] });
+            let observe_eof =
+                serde_json::json!({ "command": ["observe_property", 14, "eof-reached"] });
 
-            let _ = writer
-                .write_all(format!("{}\n", observe_percent).as_bytes())
-                .await;
-            let _ = writer
-                .write_all(format!("{}\n", observe_pause).as_bytes())
-                .await;
-            let _ = writer
-                .write_all(format!("{}\n", observe_volume).as_bytes())
-                .await;
+            // 订阅 error 级别的日志，让音频设备错误等问题能显示在日志面板中
+            let request_logs = serde_json::json!({ "command": ["request_log_messages", "error"] });
+
+            for observe in [
+                observe_percent,
+                observe_pause,
+                observe_volume,
+                observe_paused_for_cache,
+                observe_buffering,
+                observe_time_pos,
+                observe_duration,
+                observe_mute,
+                observe_bitrate,
+                observe_codec,
+                observe_chapter_list,
+                observe_chapter,
+                observe_meter,
+                observe_eof,
+                request_logs,
+            ] {
+                let _ = writer.write_all(format!("{}\n", observe).as_bytes()).await;
+            }
 
             let mut line = String::new();
             while let Ok(n) = buf_reader.read_line(&mut line).await {
@@ -132,7 +234,17 @@ pub fn spawn_ipc_task(socket_path: String, state: Arc<Mutex<PlaybackState>>) ->
                 }
 
                 if let Ok(json) = serde_json::from_str::<Value>(&line) {
-                    if json["event"] == "property-change" {
+                    if json["event"] == "log-message" {
+                        let text = json["text"].as_str().unwrap_or("").trim();
+                        if !text.is_empty() {
+                            let prefix = json["prefix"].as_str().unwrap_or("mpv");
+                            let mut state = state.lock().await;
+                            state.mpv_errors.push(format!("[mpv] {}: {}", prefix, text));
+                            if state.mpv_errors.len() > MPV_ERRORS_MAX {
+                                state.mpv_errors.remove(0);
+                            }
+                        }
+                    } else if json["event"] == "property-change" {
                         let mut state = state.lock().await;
                         if json["name"] == "percent-pos" {
                             if let Some(val) = json["data"].as_f64() {
@@ -146,10 +258,62 @@ pub fn spawn_ipc_task(socket_path: String, state: Arc<Mutex<PlaybackState>>) ->
                                     PauseState::Playing
                                 };
                             }
-                        } else if json["name"] == "volume" {
+                        } else if json["name"] == "volume" && state.fade_target.is_none() {
+                            if let Some(val) = json["data"].as_f64() {
+                                state.volume = val.clamp(0.0, state.max_volume as f64) as u8;
+                            }
+                        } else if json["name"] == "paused-for-cache" {
+                            if let Some(val) = json["data"].as_bool() {
+                                state.paused_for_cache = val;
+                            }
+                        } else if json["name"] == "cache-buffering-state" {
+                            if let Some(val) = json["data"].as_f64() {
+                                state.cache_buffering = val.clamp(0.0, 100.0) as u8;
+                            }
+                        } else if json["name"] == "time-pos" {
+                            if let Some(val) = json["data"].as_f64() {
+                                state.time_pos = val.max(0.0);
+                            }
+                        } else if json["name"] == "duration" {
                             if let Some(val) = json["data"].as_f64() {
-                                state.volume = val.clamp(0.0, 130.0) as u8;
+                                state.duration = val.max(0.0);
                             }
+                        } else if json["name"] == "mute" {
+                            if let Some(val) = json["data"].as_bool() {
+                                state.muted = val;
+                            }
+                        } else if json["name"] == "audio-bitrate" {
+                            // 属性暂不可用时 data 为 null，保留上一次的值
+                            if let Some(val) = json["data"].as_f64().filter(|v| *v > 0.0) {
+                                state.audio_bitrate = Some(val as u32);
+                            }
+                        } else if json["name"] == "audio-codec-name" {
+                            if let Some(val) = json["data"].as_str().filter(|v| !v.is_empty()) {
+                                state.audio_codec = Some(val.to_string());
+                            }
+                        } else if json["name"] == "chapter-list" {
+                            state.chapters = json["data"]
+                                .as_array()
+                                .map(|list| list.iter().map(parse_chapter).collect())
+                                .unwrap_or_default();
+                        } else if json["name"] == "chapter" {
+                            // 第一章之前为 -1，没有章节时为 null
+                            state.chapter =
+                                json["data"].as_i64().and_then(|v| usize::try_from(v).ok());
+                        } else if json["name"] == "eof-reached" {
+                            // keep-open 时播完不退出，同样通知播放结束，让上层立即处理
+                            if let Some(val) = json["data"].as_bool() {
+                                state.eof_reached = val;
+                                if val {
+                                    ended_tx.send_replace(());
+                                }
+                            }
+                        } else if json["id"] == 13 {
+                            // 元数据的值都是字符串；没有启用滤镜时 data 为 null
+                            state.loudness = json["data"]["lavfi.r128.M"]
+                                .as_str()
+                                .and_then(|v| v.trim().parse::<f64>().ok())
+                                .filter(|v| v.is_finite());
                         }
                     }
                 }
@@ -157,8 +321,119 @@ pub fn spawn_ipc_task(socket_path: String, state: Arc<Mutex<PlaybackState>>) ->
             }
         }
 
-        // 监听退出或报错后，将状态重置为 Stopped
-        let mut state = state.lock().await;
-        state.pause_state = PauseState::Stopped;
+        // 监听退出或报错后，将状态重置为 Stopped，再通知等待方
+        state.lock().await.pause_state = PauseState::Stopped;
+        ended_tx.send_replace(());
     })
 }
+
+/// 解析 `chapter-list` 中的一项；标题缺失时留空，由界面显示序号
+fn parse_chapter(value: &Value) -> Chapter {
+    Chapter {
+        title: value["title"].as_str().unwrap_or("").trim().to_string(),
+        time: value["time"].as_f64().unwrap_or(0.0),
+    }
+}
+
+/// 淡入 / 淡出时音量渐变的步数
+const FADE_STEPS: u32 = 20;
+
+/// 通过已建立的 IPC 连接，在 `duration` 内把音量从 `from` 线性渐变到 `to`。连接断开时提前返回。
+async fn ramp_volume(stream: &mut IpcStream, from: u8, to: u8, duration: Duration) {
+    let step_delay = duration / FADE_STEPS;
+    for step in 1..=FADE_STEPS {
+        let volume = from as f64 + (to as f64 - from as f64) * step as f64 / FADE_STEPS as f64;
+        let cmd = serde_json::json!({ "command": ["set_property", "volume", volume.round()] });
+        if stream
+            .write_all(format!("{}\n", cmd).as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+        sleep(step_delay).await;
+    }
+}
+
+/// 接管即将被替换的 mpv 实例：先建立一条专用 IPC 连接，再清理 IPC 端点，
+/// 让新实例可以复用同一路径；随后在后台把旧实例的音量渐变到 0 并结束它。
+/// 任务被 abort 时 `child` 随之 drop，借助 `kill_on_drop` 立即结束旧实例。
+pub async fn spawn_fade_out(
+    socket_path: &str,
+    child: Option<tokio::process::Child>,
+    from_volume: u8,
+    duration: Duration,
+) -> JoinHandle<()> {
+    let stream = connect_ipc(socket_path).await.ok();
+    cleanup_ipc_file(socket_path);
+    tokio::spawn(async move {
+        if let Some(mut stream) = stream {
+            ramp_volume(&mut stream, from_volume, 0, duration).await;
+            let quit = serde_json::json!({ "command": ["quit"] });
+            let _ = stream.write_all(format!("{}\n", quit).as_bytes()).await;
+        }
+        if let Some(mut child) = child {
+            let _ = child.kill().await;
+            let _ = child.wait().await;
+        }
+    })
+}
+
+/// 新实例以 0 音量启动后，在后台把音量渐变到 `to_volume`。
+/// IPC 端点尚未就绪时每 100ms 重试一次连接，最多等待 5 秒。
+/// 结束时清除 `fade_target`；淡入期间音量被调整过时，最后再设置为 `PlaybackState.volume`。
+pub fn spawn_fade_in(
+    socket_path: String,
+    state: Arc<Mutex<PlaybackState>>,
+    to_volume: u8,
+    duration: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut stream = None;
+        for _ in 0..50 {
+            if let Ok(s) = connect_ipc(&socket_path).await {
+                stream = Some(s);
+                break;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        if let Some(stream) = stream.as_mut() {
+            ramp_volume(stream, 0, to_volume, duration).await;
+        }
+
+        let target = {
+            let mut state = state.lock().await;
+            state.fade_target = None;
+            state.volume
+        };
+        if let Some(stream) = stream.as_mut() {
+            if target != to_volume {
+                let cmd = serde_json::json!({ "command": ["set_property", "volume", target] });
+                let _ = stream.write_all(format!("{}\n", cmd).as_bytes()).await;
+            }
+        }
+    })
+}
+
+/// 运行 `mpv --audio-device=help`，返回可用的音频输出设备 (名称, 描述) 列表
+pub async fn list_audio_devices() -> Result<Vec<(String, String)>> {
+    let output = tokio::process::Command::new("mpv")
+        .env("PATH", super::ytdlp::get_extended_path())
+        .arg("--audio-device=help")
+        .output()
+        .await
+        .context("无法运行 mpv")?;
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    // 每个设备一行，例如：  'pulse/alsa_output.pci-0000' (Built-in Audio)
+    let devices = text
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix('\'')?;
+            let (name, desc) = rest.split_once('\'')?;
+            let desc = desc.trim().trim_start_matches('(').trim_end_matches(')');
+            Some((name.to_string(), desc.to_string()))
+        })
+        .collect();
+    Ok(devices)
+}
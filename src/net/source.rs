@@ -0,0 +1,143 @@
+//! 音乐来源后端：负责搜索歌曲和把歌曲解析为 mpv 可播放的地址。
+//! 目前只有基于 yt-dlp 的实现，按 `search.backend` 选择，后续可扩展本地曲库、在线音乐 API 等。
+
+use super::ytdlp::{self, CachedSong, StreamInfo, UrlCache};
+use super::SearchResult;
+use crate::config::Config;
+use anyhow::Result;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::SystemTime;
+use tokio::sync::Mutex;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// 日志回调：后端把进度和 stderr 等信息逐行交给调用方
+pub type LogFn<'a> = dyn FnMut(String) + Send + 'a;
+
+/// 搜索结果回调：每得到一条结果立即交给调用方，用于边搜索边显示
+pub type ResultFn<'a> = dyn FnMut(SearchResult) + Send + 'a;
+
+pub trait MusicSource: Send + Sync {
+    /// 按关键词（或 URL）搜索第 `page` 页（从 1 开始）
+    fn search<'a>(
+        &'a self,
+        config: &'a Config,
+        keyword: &'a str,
+        page: usize,
+        log_fn: &'a mut LogFn<'_>,
+        on_result: &'a mut ResultFn<'_>,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>>>;
+
+    /// 把 `source` 搜索源下的歌曲解析为可播放的地址（网络直链或本地文件）
+    fn resolve_url<'a>(
+        &'a self,
+        config: &'a Config,
+        keyword: &'a str,
+        source: &'a str,
+        log_fn: &'a mut LogFn<'_>,
+    ) -> BoxFuture<'a, Result<StreamInfo>>;
+
+    /// 不发起解析，返回之前缓存的解析结果（可能已过期，由调用方判断）；没有缓存时返回 None
+    fn cached<'a>(
+        &'a self,
+        config: &'a Config,
+        keyword: &'a str,
+        source: &'a str,
+    ) -> BoxFuture<'a, Option<CachedSong>>;
+
+    /// 移除缓存的解析结果，下次解析时重新获取地址；返回是否确实有缓存被移除
+    fn evict<'a>(
+        &'a self,
+        config: &'a Config,
+        keyword: &'a str,
+        source: &'a str,
+    ) -> BoxFuture<'a, bool>;
+}
+
+/// 缓存的解析结果是否仍在 `cache.url_cache_ttl` 之内
+pub fn is_cache_valid(config: &Config, cached_at: SystemTime) -> bool {
+    if let Ok(elapsed) = SystemTime::now().duration_since(cached_at) {
+        elapsed.as_secs() < config.cache.url_cache_ttl
+    } else {
+        false
+    }
+}
+
+/// 按 `search.backend` 创建音乐来源；未知的后端名返回错误说明
+pub fn create_source(backend: &str) -> Result<Box<dyn MusicSource>, String> {
+    match backend.trim().to_lowercase().as_str() {
+        "" | "yt-dlp" | "ytdlp" => Ok(Box::new(YtDlpSource::default())),
+        other => Err(format!("未知的 search.backend: {}，已使用 yt-dlp", other)),
+    }
+}
+
+// ── yt-dlp ────────────────────────────────────────────────────────────────────
+
+/// 通过 yt-dlp 搜索和解析，解析结果保存在内存 URL 缓存中
+pub struct YtDlpSource {
+    cache: Mutex<UrlCache>,
+}
+
+impl Default for YtDlpSource {
+    fn default() -> Self {
+        // 容量由写入时的 `cache.url_cache_size` 控制，重载配置后无需重建缓存
+        Self {
+            cache: Mutex::new(UrlCache::unbounded()),
+        }
+    }
+}
+
+impl MusicSource for YtDlpSource {
+    fn search<'a>(
+        &'a self,
+        config: &'a Config,
+        keyword: &'a str,
+        page: usize,
+        log_fn: &'a mut LogFn<'_>,
+        on_result: &'a mut ResultFn<'_>,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>>> {
+        Box::pin(ytdlp::search(config, keyword, page, log_fn, on_result))
+    }
+
+    fn resolve_url<'a>(
+        &'a self,
+        config: &'a Config,
+        keyword: &'a str,
+        source: &'a str,
+        log_fn: &'a mut LogFn<'_>,
+    ) -> BoxFuture<'a, Result<StreamInfo>> {
+        Box::pin(ytdlp::fetch_stream_url(
+            config,
+            &self.cache,
+            keyword,
+            source,
+            |cached_at| is_cache_valid(config, cached_at),
+            log_fn,
+        ))
+    }
+
+    fn cached<'a>(
+        &'a self,
+        config: &'a Config,
+        keyword: &'a str,
+        source: &'a str,
+    ) -> BoxFuture<'a, Option<CachedSong>> {
+        Box::pin(async move {
+            let query = ytdlp::stream_query(config, keyword, source);
+            self.cache.lock().await.get(&query).cloned()
+        })
+    }
+
+    fn evict<'a>(
+        &'a self,
+        config: &'a Config,
+        keyword: &'a str,
+        source: &'a str,
+    ) -> BoxFuture<'a, bool> {
+        Box::pin(async move {
+            let query = ytdlp::stream_query(config, keyword, source);
+            self.cache.lock().await.pop(&query).is_some()
+        })
+    }
+}
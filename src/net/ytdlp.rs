@@ -1,16 +1,21 @@
-use crate::config::Config;
+use crate::config::{expand_home, search_prefix, Config, SearchConfig};
 use anyhow::Result;
+use lru::LruCache;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::ffi::OsString;
 use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 use tokio::process::Command;
-use tokio::time::timeout;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio::time::{timeout, timeout_at, Instant};
 
 #[derive(Clone)]
 pub struct CachedSong {
     pub url: String,
     pub local_path: Option<String>,
+    pub webpage_url: Option<String>,
+    pub thumbnail: Option<String>,
     pub cached_at: SystemTime,
 }
 
@@ -18,16 +23,125 @@ pub struct CachedSong {
 pub struct StreamInfo {
     pub url: String,
     pub local_path: Option<String>,
+    /// 歌曲的原始页面地址（YouTube / Bilibili 的观看链接），用于分享
+    pub webpage_url: Option<String>,
+    /// 缩略图地址，`video_mode = "art"` 时作为封面显示
+    pub thumbnail: Option<String>,
 }
 
-pub type UrlCache = HashMap<String, CachedSong>;
+/// 内存 URL 缓存：按最近使用排序，命中时更新顺序，超出 `url_cache_size` 时淘汰最久未用的条目
+pub type UrlCache = LruCache<String, CachedSong>;
 
-#[derive(Clone, Debug)]
+/// 写入 URL 缓存，超出 `capacity` 时依次淘汰最久未用的条目（每次 O(1)）。
+/// 容量以调用时的配置为准，重载配置调小 `url_cache_size` 后下次写入即收缩
+fn insert_cached(cache: &mut UrlCache, query: String, song: CachedSong, capacity: usize) {
+    cache.put(query, song);
+    while cache.len() > capacity {
+        cache.pop_lru();
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
 pub struct SearchResult {
     pub title: String,
+    /// 时长（秒）
+    pub duration: Option<u64>,
+    /// 上传者 / 频道名
+    pub uploader: Option<String>,
+    pub view_count: Option<u64>,
+    /// 结果来自的搜索源（如 `yt`、`bili`），播放时按此源解析
+    pub source: String,
+    /// 原始页面地址（观看链接）
+    pub webpage_url: Option<String>,
+}
+
+impl SearchResult {
+    /// 从 yt-dlp `--dump-json` 的单行输出解析搜索结果，缺失的元数据字段保持为 None
+    fn from_json(json: &Value, source: &str) -> Option<Self> {
+        let title = json["title"].as_str()?.to_string();
+        let uploader = json["uploader"]
+            .as_str()
+            .or_else(|| json["channel"].as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+        // --flat-playlist 的条目（`_type` 为 url）没有 webpage_url，其 url 字段就是观看链接
+        let webpage_url = json["webpage_url"]
+            .as_str()
+            .or_else(|| {
+                (json["_type"].as_str() == Some("url"))
+                    .then(|| json["url"].as_str())
+                    .flatten()
+            })
+            .map(str::to_string);
+        Some(Self {
+            title,
+            duration: json["duration"].as_f64().map(|d| d.max(0.0) as u64),
+            uploader,
+            view_count: json["view_count"].as_u64(),
+            source: source.to_string(),
+            webpage_url,
+        })
+    }
 }
 
 const YTDLP_STDERR_LOG_MAX_LINES: usize = 6;
+
+/// yt-dlp 以非零状态退出时的错误。日志里只保留前几行 stderr，这里保存完整内容供界面展开查看。
+#[derive(Debug)]
+pub struct YtDlpError {
+    message: String,
+    stderr: String,
+    /// stderr 中出现了歌曲不可用的标记（下架、地区限制、私有），重试也不会成功
+    unavailable: bool,
+}
+
+impl YtDlpError {
+    pub(super) fn new(message: String, stderr: &[u8]) -> Self {
+        let stderr = String::from_utf8_lossy(stderr).trim_end().to_string();
+        Self {
+            message,
+            unavailable: find_unavailable_marker(&stderr).is_some(),
+            stderr,
+        }
+    }
+}
+
+/// yt-dlp stderr 中表示歌曲不可用的标记
+const UNAVAILABLE_MARKERS: [&str; 4] = [
+    "Video unavailable",
+    "This video is not available",
+    "Private video",
+    // 地区限制：The uploader has not made this video available in your country
+    "available in your country",
+];
+
+/// 在 yt-dlp stderr 中查找歌曲不可用的标记，返回命中的标记
+fn find_unavailable_marker(stderr: &str) -> Option<&'static str> {
+    UNAVAILABLE_MARKERS
+        .into_iter()
+        .find(|marker| stderr.contains(marker))
+}
+
+impl std::fmt::Display for YtDlpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for YtDlpError {}
+
+/// 错误是否因为歌曲不可用（下架、地区限制、私有）。这类错误不值得重试，应直接跳到下一首
+pub fn is_unavailable(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<YtDlpError>()
+        .is_some_and(|e| e.unavailable)
+}
+
+/// 取出错误中附带的 yt-dlp stderr；不是 yt-dlp 报错或 stderr 为空时返回 None
+pub fn error_detail(e: &anyhow::Error) -> Option<String> {
+    e.downcast_ref::<YtDlpError>()
+        .map(|e| e.stderr.clone())
+        .filter(|s| !s.is_empty())
+}
 /// 在计算分页范围时额外预留的搜索结果数，避免因 yt-dlp 返回少于预期数量而误判为最后一页
 const SEARCH_RESULT_BUFFER: usize = 50;
 
@@ -54,17 +168,60 @@ pub fn build_ytdlp_command(config: &Config, path: &str) -> Command {
     // 当超时或上层任务被取消时，确保子进程不会残留。
     cmd.kill_on_drop(true);
     cmd.env("PATH", path);
-    // 空字符串表示不使用 cookies（例如 Windows 下 Chrome 因 App-Bound Encryption 读不到 cookie）
-    if !config.search.cookies_browser.is_empty() {
-        cmd.arg("--cookies-from-browser")
-            .arg(&config.search.cookies_browser);
+    // 配置有误时不带 cookies 参数，错误已在启动 / 重新加载配置时写入日志
+    cmd.args(cookie_args(&config.search).unwrap_or_default());
+    cmd
+}
+
+/// 按 `cookies_mode` 构建 yt-dlp 的 cookies 参数。
+/// `file` 模式下 cookies 文件不存在或不可读时返回错误，而不是交给 yt-dlp 报出难懂的失败。
+pub fn cookie_args(config: &SearchConfig) -> Result<Vec<OsString>, String> {
+    let browser = config.cookies_browser.trim();
+    let file = config.cookies_file.trim();
+    let mut args: Vec<OsString> = Vec::new();
+    match config.cookies_mode.trim().to_lowercase().as_str() {
+        // 兼容旧配置：空字符串表示不使用对应来源
+        // （例如 Windows 下 Chrome 因 App-Bound Encryption 读不到 cookie）
+        "" => {
+            if !browser.is_empty() {
+                args.extend(["--cookies-from-browser".into(), browser.into()]);
+            }
+            if !file.is_empty() {
+                args.extend(["--cookies".into(), readable_cookies_file(file)?.into()]);
+            }
+        }
+        "browser" => {
+            if browser.is_empty() {
+                return Err("cookies_mode = \"browser\" 但 cookies_browser 为空".to_string());
+            }
+            args.extend(["--cookies-from-browser".into(), browser.into()]);
+        }
+        "file" => {
+            if file.is_empty() {
+                return Err("cookies_mode = \"file\" 但 cookies_file 为空".to_string());
+            }
+            args.extend(["--cookies".into(), readable_cookies_file(file)?.into()]);
+        }
+        "none" => {}
+        other => {
+            return Err(format!(
+                "未知的 cookies_mode: {}（可选 browser / file / none）",
+                other
+            ))
+        }
     }
-    // 预先导出的 cookies.txt 文件；可与 cookies_browser 同时使用
-    if !config.search.cookies_file.is_empty() {
-        cmd.arg("--cookies")
-            .arg(expand_home(&config.search.cookies_file));
+    Ok(args)
+}
+
+/// 展开并检查 cookies 文件确实存在且可读
+fn readable_cookies_file(file: &str) -> Result<PathBuf, String> {
+    let path = expand_home(file);
+    if !path.is_file() {
+        return Err(format!("cookies 文件不存在: {}", path.display()));
     }
-    cmd
+    std::fs::File::open(&path)
+        .map_err(|e| format!("cookies 文件无法读取: {} ({})", path.display(), e))?;
+    Ok(path)
 }
 
 pub fn log_ytdlp_stderr<F>(stderr: &[u8], log_fn: &mut F)
@@ -92,18 +249,40 @@ where
 }
 
 /// 判断用户输入的关键字是否已经是一个 URL（而非普通搜索词）
-fn is_url(keyword: &str) -> bool {
+pub(super) fn is_url(keyword: &str) -> bool {
     keyword.starts_with("http://") || keyword.starts_with("https://")
 }
 
-/// 展开 `~` 为 home 目录的绝对路径（Unix 读 HOME，Windows 读 USERPROFILE）
-fn expand_home(path: &str) -> PathBuf {
-    if let Some(rest) = path.strip_prefix('~') {
-        let rest = rest.strip_prefix(['/', '\\']).unwrap_or(rest);
-        crate::config::home_dir().join(rest)
+/// 按 URL 的域名判断它属于哪个搜索源（YouTube → `yt`，Bilibili → `bili`），
+/// 配置中已有指向同一站点的源名时沿用配置中的写法；无法识别的站点归到主搜索源
+pub(super) fn source_for_url(config: &Config, url: &str) -> String {
+    let host = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or("")
+        .rsplit('@')
+        .next()
+        .unwrap_or("")
+        .split(':')
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+    let matches_domain = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
+    let canonical = if ["youtube.com", "youtu.be"].into_iter().any(matches_domain) {
+        "yt"
+    } else if ["bilibili.com", "b23.tv"].into_iter().any(matches_domain) {
+        "bili"
     } else {
-        PathBuf::from(path)
-    }
+        return config.search_sources().remove(0);
+    };
+    let prefix = search_prefix(canonical);
+    config
+        .search_sources()
+        .into_iter()
+        .find(|s| search_prefix(s) == prefix)
+        .unwrap_or_else(|| canonical.to_string())
 }
 
 /// 确保本地缓存目录存在。如果创建失败，返回 None（降级为网络流）。
@@ -117,113 +296,406 @@ fn ensure_cache_dir(cache_dir: &str) -> Option<PathBuf> {
 }
 
 /// 执行 yt-dlp 搜索，返回标题列表。
-/// - 如果 keyword 已是 URL，直接解析为播放列表/单曲，不使用搜索前缀。
+/// - 如果 keyword 已是 URL，直接解析为播放列表/单曲，不使用搜索前缀；
+///   播放列表同样按 `max_results` 分页，只列出第 `page` 页的条目。
 /// - 否则按分页搜索模式执行。
-pub async fn search<F>(
+///
+/// yt-dlp 每输出一条结果就交给 `on_result`，调用方可以边搜索边显示；
+/// 多个源并发时到达顺序与最终返回的合并顺序不同。
+pub async fn search<F, R>(
     config: &Config,
     keyword: &str,
     page: usize,
     mut log_fn: F,
+    mut on_result: R,
 ) -> Result<Vec<SearchResult>>
 where
     F: FnMut(String),
+    R: FnMut(SearchResult),
 {
     let path = get_extended_path();
 
     // ── URL 模式：直接解析播放列表或单曲 ─────────────────────────────────────
     if is_url(keyword) {
-        log_fn(format!("检测到 URL，直接解析播放列表: {}", keyword));
+        // URL 不经过搜索前缀，结果按 URL 的域名归到对应的搜索源
+        let source = source_for_url(config, keyword);
+        log_fn(format!(
+            "检测到 URL，直接解析播放列表: {} (第 {} 页, 源 {})",
+            keyword, page, source
+        ));
         let mut yt_cmd = build_ytdlp_command(config, &path);
-        yt_cmd.args(["--dump-json", "--flat-playlist", "--yes-playlist", keyword]);
-        let search_timeout = config.search.timeout;
-        let yt_output = match timeout(Duration::from_secs(search_timeout), yt_cmd.output()).await {
-            Ok(Ok(output)) => {
-                log_fn(format!("yt-dlp 执行完成，退出码: {}", output.status));
-                log_ytdlp_stderr(&output.stderr, &mut log_fn);
-                if !output.status.success() {
-                    return Err(anyhow::anyhow!("yt-dlp 解析 URL 失败: {}", output.status));
-                }
-                output
+        yt_cmd.args(url_search_args(config, keyword, page));
+        let list_timeout = config.search.list_timeout_secs();
+        let deadline = Instant::now() + Duration::from_secs(list_timeout);
+        let results = stream_dump_json(
+            yt_cmd,
+            &source,
+            deadline,
+            list_timeout,
+            "yt-dlp 解析 URL 失败",
+            &mut log_fn,
+            &mut on_result,
+        )
+        .await?;
+        log_fn(format!("解析到 {} 首歌曲", results.len()));
+        return Ok(results);
+    }
+
+    // ── 关键词搜索模式 ────────────────────────────────────────────────────────
+    log_fn(format!("开始搜索: {} (第 {} 页)", keyword, page));
+
+    let sources = config.search_sources();
+    let search_timeout = config.search.list_timeout_secs();
+    // 所有源共享同一个截止时间，整体耗时不超过 search.list_timeout
+    let deadline = Instant::now() + Duration::from_secs(search_timeout);
+
+    if let [source] = sources.as_slice() {
+        log_fn("等待 yt-dlp 响应...".to_string());
+        let results = search_source(
+            config,
+            source,
+            keyword,
+            page,
+            deadline,
+            &mut log_fn,
+            &mut on_result,
+        )
+        .await?;
+        log_fn(format!("找到 {} 个结果", results.len()));
+        return Ok(results);
+    }
+
+    // 多个源并发搜索：各自的日志先缓存，完成后按源的顺序统一输出，避免交错
+    log_fn(format!(
+        "并发搜索 {} 个源: {}",
+        sources.len(),
+        sources.join(", ")
+    ));
+    // 各个源的结果经由 channel 实时转交给 on_result
+    let (result_tx, mut result_rx) = mpsc::unbounded_channel();
+    let mut tasks = JoinSet::new();
+    for (idx, source) in sources.iter().enumerate() {
+        let config = config.clone();
+        let source = source.clone();
+        let keyword = keyword.to_string();
+        let result_tx = result_tx.clone();
+        tasks.spawn(async move {
+            let mut logs = Vec::new();
+            let result = search_source(
+                &config,
+                &source,
+                &keyword,
+                page,
+                deadline,
+                &mut |log| logs.push(log),
+                &mut |result| {
+                    let _ = result_tx.send(result);
+                },
+            )
+            .await;
+            (idx, source, logs, result)
+        });
+    }
+    drop(result_tx);
+
+    let mut outcomes = Vec::with_capacity(sources.len());
+    loop {
+        tokio::select! {
+            Some(result) = result_rx.recv() => on_result(result),
+            joined = tasks.join_next() => match joined {
+                Some(Ok(outcome)) => outcomes.push(outcome),
+                Some(Err(_)) => {}
+                None => break,
+            },
+        }
+    }
+    while let Ok(result) = result_rx.try_recv() {
+        on_result(result);
+    }
+    outcomes.sort_by_key(|(idx, ..)| *idx);
+
+    let mut per_source = Vec::new();
+    let mut errors = Vec::new();
+    let mut details = Vec::new();
+    for (_, source, logs, result) in outcomes {
+        for log in logs {
+            log_fn(format!("[{}] {}", source, log));
+        }
+        match result {
+            Ok(results) => {
+                log_fn(format!("[{}] 找到 {} 个结果", source, results.len()));
+                per_source.push(results);
             }
-            Ok(Err(e)) => return Err(e.into()),
-            Err(_) => {
-                log_fn(format!("yt-dlp 超时（{}秒）", search_timeout));
-                return Err(anyhow::anyhow!("yt-dlp 超时"));
+            Err(e) => {
+                log_fn(format!("⚠ [{}] 搜索失败: {}", source, e));
+                errors.push(format!("{}: {}", source, e));
+                if let Some(detail) = error_detail(&e) {
+                    details.push(format!("── {} ──\n{}", source, detail));
+                }
             }
-        };
+        }
+    }
 
-        let output_str = String::from_utf8_lossy(&yt_output.stdout);
-        let mut results = Vec::new();
-        for line in output_str.lines() {
-            if let Ok(json) = serde_json::from_str::<Value>(line) {
-                if let Some(title) = json["title"].as_str() {
-                    results.push(SearchResult {
-                        title: title.to_string(),
-                    });
-                }
+    // 所有源都失败才视为搜索失败；否则只返回成功的部分
+    if per_source.is_empty() {
+        return Err(YtDlpError {
+            message: format!("所有搜索源均失败（{}）", errors.join("; ")),
+            stderr: details.join("\n"),
+            unavailable: false,
+        }
+        .into());
+    }
+
+    // 轮流从每个源取结果合并，让各个源的结果都能出现在列表前部
+    let total: usize = per_source.iter().map(Vec::len).sum();
+    let mut iters: Vec<_> = per_source.into_iter().map(Vec::into_iter).collect();
+    let mut results = Vec::with_capacity(total);
+    while results.len() < total {
+        for it in iters.iter_mut() {
+            if let Some(r) = it.next() {
+                results.push(r);
             }
         }
-        log_fn(format!("解析到 {} 首歌曲", results.len()));
-        return Ok(results);
     }
 
-    // ── 关键词搜索模式 ────────────────────────────────────────────────────────
-    log_fn(format!("开始搜索: {} (第 {} 页)", keyword, page));
+    log_fn(format!("找到 {} 个结果", results.len()));
+    Ok(results)
+}
 
-    let search_prefix = config.get_search_prefix();
+/// 在单个源上执行分页关键词搜索，超过 `deadline` 视为超时
+async fn search_source<F, R>(
+    config: &Config,
+    source: &str,
+    keyword: &str,
+    page: usize,
+    deadline: Instant,
+    log_fn: &mut F,
+    on_result: &mut R,
+) -> Result<Vec<SearchResult>>
+where
+    F: FnMut(String),
+    R: FnMut(SearchResult),
+{
+    log_fn(format!("搜索前缀: {}", config.search.prefix_for(source)));
+    let path = get_extended_path();
+    let mut yt_cmd = build_ytdlp_command(config, &path);
+    yt_cmd.args(keyword_search_args(config, source, keyword, page));
+
+    stream_dump_json(
+        yt_cmd,
+        source,
+        deadline,
+        config.search.list_timeout_secs(),
+        "yt-dlp 搜索失败",
+        log_fn,
+        on_result,
+    )
+    .await
+}
+
+/// 解析播放列表 / 单曲 URL 的 yt-dlp 参数，按 `max_results` 分页
+pub fn url_search_args(config: &Config, url: &str, page: usize) -> Vec<String> {
+    let page_size = config.search.max_results.max(1);
+    let first = (page.max(1) - 1) * page_size + 1;
+    vec![
+        "--dump-json".to_string(),
+        "--flat-playlist".to_string(),
+        "--yes-playlist".to_string(),
+        "--playlist-items".to_string(),
+        format!("{}:{}", first, first + page_size - 1),
+        url.to_string(),
+    ]
+}
+
+/// 在单个源上分页搜索关键词的 yt-dlp 参数
+pub fn keyword_search_args(
+    config: &Config,
+    source: &str,
+    keyword: &str,
+    page: usize,
+) -> Vec<String> {
     let per_page = config.search.max_results;
     let start_index = (page - 1) * per_page + 1;
     let end_index = page * per_page;
-
     // 为搜索结果预留 buffer 位置
     let search_count = end_index + SEARCH_RESULT_BUFFER;
-
-    let mut yt_cmd = build_ytdlp_command(config, &path);
-    yt_cmd.args([
+    vec![
         "--dump-json".to_string(),
         "--flat-playlist".to_string(),
         "--playlist-items".to_string(),
         format!("{}-{}", start_index, end_index),
-        format!("{}{}:{}", search_prefix, search_count, keyword),
-    ]);
-    let yt_task = yt_cmd.output();
+        format!(
+            "{}{}:{}",
+            config.search.prefix_for(source),
+            search_count,
+            keyword
+        ),
+    ]
+}
 
-    log_fn("等待 yt-dlp 响应...".to_string());
-    let search_timeout = config.search.timeout;
-    let yt_output = match timeout(Duration::from_secs(search_timeout), yt_task).await {
-        Ok(Ok(output)) => {
-            log_fn(format!("yt-dlp 执行完成，退出码: {}", output.status));
-            log_ytdlp_stderr(&output.stderr, &mut log_fn);
-            if !output.status.success() {
-                return Err(anyhow::anyhow!("yt-dlp 搜索失败: {}", output.status));
+/// 解析音频流的 yt-dlp 参数
+pub fn resolve_args(config: &Config, keyword: &str, source: &str) -> Vec<String> {
+    vec![
+        "--dump-json".to_string(),
+        "-f".to_string(),
+        config.search.audio_format_for(source).to_string(),
+        stream_query(config, keyword, source),
+    ]
+}
+
+/// 完整的 yt-dlp 命令行（含 cookies 参数），用于日志显示；含空白的参数加引号
+pub fn ytdlp_command_line(config: &Config, args: &[String]) -> String {
+    let cookies = cookie_args(&config.search).unwrap_or_default();
+    let args = cookies
+        .iter()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .chain(args.iter().cloned());
+    command_line("yt-dlp", args)
+}
+
+/// 把程序名和参数拼成便于复制的命令行；含空白或引号的参数用单引号括起
+pub fn command_line(program: &str, args: impl IntoIterator<Item = String>) -> String {
+    let mut line = program.to_string();
+    for arg in args {
+        line.push(' ');
+        if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '\'' || c == '"') {
+            line.push_str(&format!("'{}'", arg.replace('\'', "'\\''")));
+        } else {
+            line.push_str(&arg);
+        }
+    }
+    line
+}
+
+/// 运行 yt-dlp 并收集全部输出。stderr 逐行读取，一旦出现歌曲不可用的标记就立即结束 yt-dlp
+/// 并返回错误，不必等它跑完或超时。
+async fn output_unless_unavailable(mut cmd: Command) -> Result<std::process::Output> {
+    use std::process::Stdio;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+
+    cmd.stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = cmd.spawn()?;
+
+    // stdout 在后台读完，避免管道写满后 yt-dlp 阻塞
+    let stdout = child.stdout.take();
+    let stdout_task = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        if let Some(mut stdout) = stdout {
+            let _ = stdout.read_to_end(&mut buf).await;
+        }
+        buf
+    });
+
+    let mut stderr = Vec::new();
+    if let Some(pipe) = child.stderr.take() {
+        let mut lines = BufReader::new(pipe).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            stderr.extend_from_slice(line.as_bytes());
+            stderr.push(b'\n');
+            if let Some(marker) = find_unavailable_marker(&line) {
+                stdout_task.abort();
+                let _ = child.kill().await;
+                return Err(YtDlpError::new(format!("歌曲不可用: {}", marker), &stderr).into());
             }
-            output
         }
-        Ok(Err(e)) => {
+    }
+
+    let status = child.wait().await?;
+    let stdout = stdout_task.await.unwrap_or_default();
+    Ok(std::process::Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// 运行 `--dump-json` 形式的 yt-dlp 命令，逐行读取 stdout：每解析出一条结果立即交给 `on_result`，
+/// 不必等进程结束。超过 `deadline` 视为超时，子进程随 `kill_on_drop` 结束。
+async fn stream_dump_json<F, R>(
+    mut cmd: Command,
+    source: &str,
+    deadline: Instant,
+    timeout_secs: u64,
+    failure: &str,
+    log_fn: &mut F,
+    on_result: &mut R,
+) -> Result<Vec<SearchResult>>
+where
+    F: FnMut(String),
+    R: FnMut(SearchResult),
+{
+    use std::process::Stdio;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
             log_fn(format!("yt-dlp 执行失败: {}", e));
             return Err(e.into());
         }
-        Err(_) => {
-            log_fn(format!("yt-dlp 超时（{}秒）", search_timeout));
-            return Err(anyhow::anyhow!("yt-dlp 超时"));
-        }
     };
 
-    let output_str = String::from_utf8_lossy(&yt_output.stdout);
+    // stderr 在后台读完，避免管道写满后 yt-dlp 阻塞
+    let stderr = child.stderr.take();
+    let stderr_task = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_end(&mut buf).await;
+        }
+        buf
+    });
+
+    let stdout = child.stdout.take();
     let mut results = Vec::new();
+    let run = async {
+        if let Some(stdout) = stdout {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Ok(json) = serde_json::from_str::<Value>(&line) {
+                    if let Some(result) = SearchResult::from_json(&json, source) {
+                        on_result(result.clone());
+                        results.push(result);
+                    }
+                }
+            }
+        }
+        child.wait().await
+    };
 
-    for line in output_str.lines() {
-        if let Ok(json) = serde_json::from_str::<Value>(line) {
-            if let Some(title) = json["title"].as_str() {
-                results.push(SearchResult {
-                    title: title.to_string(),
-                });
+    match timeout_at(deadline, run).await {
+        Ok(Ok(status)) => {
+            let stderr = stderr_task.await.unwrap_or_default();
+            log_fn(format!("yt-dlp 执行完成，退出码: {}", status));
+            log_ytdlp_stderr(&stderr, log_fn);
+            if !status.success() {
+                return Err(YtDlpError::new(format!("{}: {}", failure, status), &stderr).into());
             }
+            Ok(results)
+        }
+        Ok(Err(e)) => {
+            stderr_task.abort();
+            log_fn(format!("yt-dlp 执行失败: {}", e));
+            Err(e.into())
+        }
+        Err(_) => {
+            stderr_task.abort();
+            log_fn(format!("yt-dlp 超时（{}秒）", timeout_secs));
+            Err(anyhow::anyhow!("yt-dlp 超时"))
         }
     }
+}
 
-    log_fn(format!("找到 {} 个结果", results.len()));
-    Ok(results)
+/// 解析音频流时传给 yt-dlp 的查询：URL 直接使用，否则加搜索前缀取第一条结果。
+/// 同时作为内存 URL 缓存的键，不同源的同名歌曲不会互相命中。
+pub fn stream_query(config: &Config, keyword: &str, source: &str) -> String {
+    if is_url(keyword) {
+        keyword.to_string()
+    } else {
+        format!("{}1:{}", config.search.prefix_for(source), keyword)
+    }
 }
 
 /// 通过 yt-dlp 获取音频流 URL（带内存 URL 缓存 + 本地文件缓存）。
@@ -236,6 +708,7 @@ pub async fn fetch_stream_url<F>(
     config: &Config,
     cache: &tokio::sync::Mutex<UrlCache>,
     keyword: &str,
+    source: &str,
     is_cache_valid: impl Fn(SystemTime) -> bool,
     mut log_fn: F,
 ) -> Result<StreamInfo>
@@ -250,14 +723,19 @@ where
     //   a. 先检查内存 URL 缓存（最快）
     //   b. 内存未命中时，用 --dump-json 得到 id/url/ext，一次搞定
 
+    let query = stream_query(config, keyword, source);
+
     // a. 检查内存 URL 缓存
     if let Some(cached_info) = {
-        let cache_guard = cache.lock().await;
-        cache_guard.get(keyword).and_then(|c| {
+        // get 会把命中的条目移到最近使用，常听的歌不会被淘汰
+        let mut cache_guard = cache.lock().await;
+        cache_guard.get(&query).and_then(|c| {
             if is_cache_valid(c.cached_at) {
                 Some(StreamInfo {
                     url: c.url.clone(),
                     local_path: c.local_path.clone(),
+                    webpage_url: c.webpage_url.clone(),
+                    thumbnail: c.thumbnail.clone(),
                 })
             } else {
                 None
@@ -271,41 +749,38 @@ where
 
     // b. 执行 yt-dlp --dump-json 获取完整元数据（包含 url、id、ext）
     log_fn(format!("开始解析音频信息: {}", keyword));
-    let search_prefix = config.get_search_prefix();
 
-    // 如果 keyword 本身是 URL，直接使用；否则加搜索前缀取第一条结果
-    let query = if is_url(keyword) {
-        keyword.to_string()
-    } else {
-        format!("{}1:{}", search_prefix, keyword)
-    };
+    let audio_format = config.search.audio_format_for(source).to_string();
+    log_fn(format!("音频格式: -f {}", audio_format));
 
     let mut yt_cmd = build_ytdlp_command(config, &path);
-    yt_cmd.args([
-        "--dump-json".to_string(),
-        "-f".to_string(),
-        "bestaudio".to_string(),
-        query,
-    ]);
-    let yt_task = yt_cmd.output();
+    yt_cmd.args(resolve_args(config, keyword, source));
+    let yt_task = output_unless_unavailable(yt_cmd);
 
     log_fn("等待 yt-dlp 响应...".to_string());
-    let search_timeout = config.search.timeout;
-    let yt_output = match timeout(Duration::from_secs(search_timeout), yt_task).await {
+    let resolve_timeout = config.search.resolve_timeout_secs();
+    let yt_output = match timeout(Duration::from_secs(resolve_timeout), yt_task).await {
         Ok(Ok(output)) => {
             log_fn("yt-dlp 执行完成".to_string());
             log_ytdlp_stderr(&output.stderr, &mut log_fn);
             if !output.status.success() {
-                return Err(anyhow::anyhow!("yt-dlp 获取音频流失败: {}", output.status));
+                return Err(YtDlpError::new(
+                    format!("yt-dlp 获取音频流失败: {}", output.status),
+                    &output.stderr,
+                )
+                .into());
             }
             output
         }
         Ok(Err(e)) => {
             log_fn(format!("yt-dlp 执行失败: {}", e));
-            return Err(e.into());
+            return Err(e);
         }
         Err(_) => {
-            log_fn(format!("yt-dlp 超时（{}秒）", search_timeout));
+            log_fn(format!(
+                "yt-dlp 解析超时（{}秒，search.resolve_timeout）",
+                resolve_timeout
+            ));
             return Err(anyhow::anyhow!("yt-dlp 超时"));
         }
     };
@@ -328,6 +803,8 @@ where
 
     let video_id = meta["id"].as_str().unwrap_or("").to_string();
     let ext = meta["ext"].as_str().unwrap_or("m4a").to_string();
+    let webpage_url = meta["webpage_url"].as_str().map(str::to_string);
+    let thumbnail = meta["thumbnail"].as_str().map(str::to_string);
 
     log_fn(format!(
         "获取到 URL: {}...",
@@ -354,17 +831,23 @@ where
 
         // 将本地路径也记入内存 URL 缓存，避免下次再调用 yt-dlp
         let mut cache_guard = cache.lock().await;
-        cache_guard.insert(
-            keyword.to_string(),
+        insert_cached(
+            &mut cache_guard,
+            query.clone(),
             CachedSong {
                 url: local_url.clone(),
                 local_path: Some(local_url.clone()),
+                webpage_url: webpage_url.clone(),
+                thumbnail: thumbnail.clone(),
                 cached_at: SystemTime::now(),
             },
+            config.cache.url_cache_size,
         );
         return Ok(StreamInfo {
             url: local_url.clone(),
             local_path: Some(local_url),
+            webpage_url,
+            thumbnail,
         });
     }
 
@@ -392,7 +875,7 @@ where
 
                 tokio::spawn(async move {
                     let mut cmd = build_ytdlp_command(&config_clone, &path_clone);
-                    cmd.args(["-f", "bestaudio", "-o", &output_template, &yt_url]);
+                    cmd.args(["-f", &audio_format, "-o", &output_template, &yt_url]);
                     let _ = cmd.output().await;
                 });
                 log_fn(format!("↓ 后台缓存音频: {}.{}", video_id, ext));
@@ -404,31 +887,230 @@ where
     {
         let mut cache_guard = cache.lock().await;
         if cache_guard
-            .get(keyword)
+            .peek(&query)
             .is_none_or(|c| !is_cache_valid(c.cached_at))
         {
-            cache_guard.insert(
-                keyword.to_string(),
+            insert_cached(
+                &mut cache_guard,
+                query.clone(),
                 CachedSong {
                     url: stream_url.clone(),
                     local_path: generated_local_path.clone(),
+                    webpage_url: webpage_url.clone(),
+                    thumbnail: thumbnail.clone(),
                     cached_at: SystemTime::now(),
                 },
+                config.cache.url_cache_size,
             );
-            if cache_guard.len() > config.cache.url_cache_size {
-                if let Some(oldest_key) = cache_guard
-                    .iter()
-                    .min_by_key(|(_, v)| v.cached_at)
-                    .map(|(k, _)| k.clone())
-                {
-                    cache_guard.remove(&oldest_key);
-                }
-            }
         }
     }
     log_fn("✓ 已缓存 URL".to_string());
     Ok(StreamInfo {
         url: stream_url,
         local_path: generated_local_path,
+        webpage_url,
+        thumbnail,
     })
 }
+
+// ── 保存到本地 ────────────────────────────────────────────────────────────────
+
+/// 每隔多少百分比在日志中报告一次下载进度
+const DOWNLOAD_PROGRESS_STEP: f64 = 25.0;
+
+/// 替换文件名中不允许出现的字符，并限制长度
+fn sanitize_filename(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(120)
+        .collect();
+    let cleaned = cleaned.trim().trim_matches('.').to_string();
+    if cleaned.is_empty() {
+        "maboroshi".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// 为文件名主干挑选一个不冲突的名字：目录中已有同名文件（任意扩展名）时依次追加 ` (2)`、` (3)`…
+fn unique_stem(dir: &std::path::Path, stem: &str) -> String {
+    let taken = |candidate: &str| {
+        std::fs::read_dir(dir).is_ok_and(|entries| {
+            entries
+                .flatten()
+                .any(|entry| entry.path().file_stem().and_then(|s| s.to_str()) == Some(candidate))
+        })
+    };
+    if !taken(stem) {
+        return stem.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} ({})", stem, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| stem.to_string())
+}
+
+/// 从 yt-dlp `--newline` 模式的进度行中解析百分比，例如 `[download]  42.3% of 3.50MiB ...`
+fn parse_download_progress(line: &str) -> Option<f64> {
+    let rest = line.trim_start().strip_prefix("[download]")?;
+    rest.split_whitespace()
+        .find_map(|token| token.strip_suffix('%'))
+        .and_then(|pct| pct.parse::<f64>().ok())
+}
+
+/// 把歌曲保存到 `paths.download_dir`，返回最终保存的路径。
+/// 有离线缓存文件时直接复制；否则用 yt-dlp 下载 bestaudio，进度按行解析后写入日志。
+pub async fn download_track<F>(
+    config: &Config,
+    keyword: &str,
+    source: &str,
+    local_file: Option<&str>,
+    mut log_fn: F,
+) -> Result<PathBuf>
+where
+    F: FnMut(String),
+{
+    use std::process::Stdio;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let dir = expand_home(&config.paths.download_dir);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| anyhow::anyhow!("无法创建下载目录 {}: {}", dir.display(), e))?;
+    let stem = unique_stem(&dir, &sanitize_filename(keyword));
+
+    // a. 已有离线缓存文件：直接复制，不再走网络
+    if let Some(local_file) = local_file {
+        let ext = std::path::Path::new(local_file)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("m4a");
+        let target = dir.join(format!("{}.{}", stem, ext));
+        log_fn(format!("✓ 使用本地缓存文件: {}", local_file));
+        tokio::fs::copy(local_file, &target)
+            .await
+            .map_err(|e| anyhow::anyhow!("复制缓存文件失败: {}", e))?;
+        return Ok(target);
+    }
+
+    // b. 用 yt-dlp 下载，`--print after_move:filepath` 在 stdout 输出最终路径
+    let output_template = dir
+        .join(format!("{}.%(ext)s", stem))
+        .to_string_lossy()
+        .to_string();
+    let mut cmd = build_ytdlp_command(config, &get_extended_path());
+    cmd.args([
+        "-f",
+        "bestaudio",
+        "--no-playlist",
+        "--no-overwrites",
+        "--newline",
+        "--progress",
+        "--print",
+        "after_move:filepath",
+        "-o",
+        &output_template,
+        &stream_query(config, keyword, source),
+    ])
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+
+    let mut child = cmd.spawn()?;
+    let mut stdout = child
+        .stdout
+        .take()
+        .map(|s| BufReader::new(s).lines())
+        .ok_or_else(|| anyhow::anyhow!("无法读取 yt-dlp 输出"))?;
+    let mut stderr = child
+        .stderr
+        .take()
+        .map(|s| BufReader::new(s).lines())
+        .ok_or_else(|| anyhow::anyhow!("无法读取 yt-dlp 输出"))?;
+
+    let mut saved_path = None;
+    let mut stderr_text = String::new();
+    let mut next_report = DOWNLOAD_PROGRESS_STEP;
+    let (mut stdout_done, mut stderr_done) = (false, false);
+    while !(stdout_done && stderr_done) {
+        // quiet 模式下进度行可能出现在任意一路输出中
+        let (line, from_stdout) = tokio::select! {
+            line = stdout.next_line(), if !stdout_done => match line {
+                Ok(Some(line)) => (line, true),
+                _ => {
+                    stdout_done = true;
+                    continue;
+                }
+            },
+            line = stderr.next_line(), if !stderr_done => match line {
+                Ok(Some(line)) => (line, false),
+                _ => {
+                    stderr_done = true;
+                    continue;
+                }
+            },
+        };
+        if let Some(pct) = parse_download_progress(&line) {
+            if pct >= next_report {
+                log_fn(format!("↓ 下载进度 {:.0}%", pct));
+                while next_report <= pct {
+                    next_report += DOWNLOAD_PROGRESS_STEP;
+                }
+            }
+        } else if from_stdout && !line.trim().is_empty() && !line.starts_with('[') {
+            saved_path = Some(PathBuf::from(line.trim()));
+        } else if !from_stdout {
+            stderr_text.push_str(&line);
+            stderr_text.push('\n');
+        }
+    }
+
+    let status = child.wait().await?;
+    if !status.success() {
+        log_ytdlp_stderr(stderr_text.as_bytes(), &mut log_fn);
+        return Err(YtDlpError::new(
+            format!("yt-dlp 下载失败: {}", status),
+            stderr_text.as_bytes(),
+        )
+        .into());
+    }
+    saved_path.ok_or_else(|| anyhow::anyhow!("yt-dlp 未返回保存路径"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ytdlp_error(stderr: &str) -> anyhow::Error {
+        YtDlpError::new("yt-dlp 执行失败".to_string(), stderr.as_bytes()).into()
+    }
+
+    #[test]
+    fn unavailable_songs_are_detected() {
+        for stderr in [
+            "ERROR: [youtube] abc: Video unavailable. This video has been removed by the uploader",
+            "ERROR: [youtube] abc: Private video. Sign in if you've been granted access to this video",
+            "ERROR: [youtube] abc: The uploader has not made this video available in your country",
+            "ERROR: [BiliBili] abc: This video is not available from your location due to geo restriction",
+        ] {
+            assert!(is_unavailable(&ytdlp_error(stderr)), "{}", stderr);
+        }
+    }
+
+    #[test]
+    fn transient_errors_are_retried() {
+        for stderr in [
+            "ERROR: [youtube] abc: Unable to download API page: <urlopen error timed out>",
+            "ERROR: [youtube] abc: Unable to download webpage: HTTP Error 503: Service Unavailable",
+            "ERROR: [youtube] abc: HTTP Error 500: Internal Server Error",
+            "",
+        ] {
+            assert!(!is_unavailable(&ytdlp_error(stderr)), "{}", stderr);
+        }
+        // 不是 yt-dlp 报错（如超时）时也应重试
+        assert!(!is_unavailable(&anyhow::anyhow!("解析超时")));
+    }
+}
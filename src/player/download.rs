@@ -0,0 +1,54 @@
+use super::LOG_CHANNEL_CAPACITY;
+use crate::app::{App, PlayerStatus};
+use crate::net::AudioBackend;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+/// 在后台把当前歌曲保存到下载目录，进度和最终路径写入日志，不阻塞界面。
+pub async fn download_current(audio: &Arc<AudioBackend>, app: &Arc<Mutex<App>>) {
+    let (song, source, local_path) = {
+        let mut app_lock = app.lock().await;
+        if !matches!(
+            app_lock.status,
+            PlayerStatus::Playing | PlayerStatus::Paused
+        ) || app_lock.current_song.is_empty()
+        {
+            app_lock.add_log("当前没有正在播放的歌曲".to_string());
+            return;
+        }
+        let song = app_lock.current_song.clone();
+        app_lock.add_log(format!("↓ 开始保存: {}", song));
+        (
+            song,
+            app_lock.current_source.clone(),
+            app_lock.current_local_path.clone(),
+        )
+    };
+
+    let audio_c = Arc::clone(audio);
+    let app_c = Arc::clone(app);
+    tokio::spawn(async move {
+        // 下载日志全部转发到日志面板；等转发结束后再写最终结果，保证顺序
+        let (log_tx, mut log_rx) = mpsc::channel::<String>(LOG_CHANNEL_CAPACITY);
+        let forward_app = Arc::clone(&app_c);
+        let forwarder = tokio::spawn(async move {
+            while let Some(log) = log_rx.recv().await {
+                forward_app.lock().await.add_log(log);
+            }
+        });
+
+        let result = audio_c
+            .download(&song, &source, local_path, |log| {
+                let _ = log_tx.try_send(log);
+            })
+            .await;
+        drop(log_tx);
+        let _ = forwarder.await;
+
+        let mut app_lock = app_c.lock().await;
+        match result {
+            Ok(path) => app_lock.add_log(format!("✓ 已保存到: {}", path.display())),
+            Err(e) => app_lock.add_log(format!("保存失败: {}", e)),
+        }
+    });
+}
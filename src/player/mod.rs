@@ -1,23 +1,42 @@
+mod download;
 mod playlist;
 mod volume;
 
-use crate::app::{App, PlayerStatus};
-use crate::config::Config;
-use crate::net::{AudioBackend, PauseState};
+use crate::app::{App, PlayMode, PlaybackContext, PlayerStatus};
+use crate::clipboard;
+use crate::config::{Config, QueueEndAction};
+use crate::cover;
+use crate::lyrics;
+use crate::net::{
+    self, AudioBackend, LoadStage, PauseState, PlayOptions, SearchResult, StreamInfo,
+};
+use crate::ui::format_duration;
+use anyhow::Result;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc::Sender;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, watch, Mutex};
 use tokio::task::JoinHandle;
 
 const LOG_CHANNEL_CAPACITY: usize = 256;
+/// 播放进度超过该比例后开始预取下一首
+const PREFETCH_PROGRESS: f64 = 0.8;
+/// 启动失败重试的初始等待时间，之后每次翻倍
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
 
-/// 创建一个日志通道：只把 yt-dlp 的 stderr 行（以 `[yt-dlp]` 开头）转发到 App 日志面板，
-/// 其余内部日志静默丢弃，避免刷屏。
+/// 创建一个日志通道：只把 yt-dlp 的 stderr 行（以 `[yt-dlp]` 开头）和结果过滤提示转发到 App 日志面板，
+/// 加载阶段日志转为界面上的加载指示，其余内部日志静默丢弃，避免刷屏。
 pub(crate) fn spawn_log_forwarder(app: Arc<Mutex<App>>) -> Sender<String> {
     let (tx, mut rx) = mpsc::channel::<String>(LOG_CHANNEL_CAPACITY);
     tokio::spawn(async move {
         while let Some(log) = rx.recv().await {
-            if log.starts_with("[yt-dlp]") {
+            if let Some(stage) = LoadStage::from_log_line(&log) {
+                let mut a = app.lock().await;
+                if matches!(a.status, PlayerStatus::Searching) && a.load_stage.is_some() {
+                    a.load_stage = Some(stage);
+                }
+            } else if log.starts_with("[yt-dlp]") || log.starts_with(net::EXCLUDED_LOG_PREFIX) {
                 let mut a = app.lock().await;
                 a.add_log(log);
             }
@@ -26,23 +45,148 @@ pub(crate) fn spawn_log_forwarder(app: Arc<Mutex<App>>) -> Sender<String> {
     tx
 }
 
+/// 记录播放失败并进入对应的错误状态：歌曲不可用时单独提示，其余按播放失败处理
+fn report_play_error(app: &mut App, e: anyhow::Error) {
+    let detail = net::error_detail(&e);
+    if net::is_unavailable(&e) {
+        app.add_log(format!("⛔ {}（已下架、地区限制或私有），跳到下一首", e));
+        app.set_unavailable_error(e.to_string(), detail);
+    } else {
+        app.add_log(format!("播放失败: {}", e));
+        app.set_playback_error(e.to_string(), detail);
+    }
+}
+
+/// 当前歌曲用于分享的链接：优先解析得到的页面地址，其次搜索结果中的观看链接，最后退回网络直链
+fn shareable_url(app: &App, song: &str, source: &str, info: &StreamInfo) -> Option<String> {
+    info.webpage_url
+        .clone()
+        .or_else(|| app.search_result_url(song, source))
+        .or_else(|| info.url.starts_with("http").then(|| info.url.clone()))
+}
+
+/// 一次播放请求：要播放的歌曲及其搜索源、本地缓存路径提示、起始位置，以及与上一首的淡入淡出时长
+struct PlayRequest<'a> {
+    song: &'a str,
+    source: &'a str,
+    local_path_hint: Option<String>,
+    start_secs: Option<u64>,
+    fade: Option<Duration>,
+}
+
+/// 启动播放，失败时按指数退避（1s、2s、4s…）重试同一首歌，最多 `max_retries` 次。
+/// 运行在 `active_task` 中，新的用户操作会 abort 整个重试循环；请求已过期时也不再重试。
+async fn play_with_retry(
+    audio: &AudioBackend,
+    app: &Arc<Mutex<App>>,
+    request_id: u64,
+    request: PlayRequest<'_>,
+    max_retries: u32,
+) -> Result<StreamInfo> {
+    let log_tx = spawn_log_forwarder(Arc::clone(app));
+    let mut attempt = 0;
+    loop {
+        // 只有第一次尝试与上一首淡入淡出，重试时上一首已经结束
+        let fade = request.fade.filter(|_| attempt == 0);
+        let result = audio
+            .search_and_play(
+                request.song,
+                request.source,
+                request.local_path_hint.clone(),
+                PlayOptions {
+                    start_secs: request.start_secs,
+                    fade,
+                    preview: false,
+                },
+                |log| {
+                    let _ = log_tx.try_send(log);
+                },
+            )
+            .await;
+        // 歌曲不可用（下架、地区限制、私有）时重试也没用，直接交给上层跳到下一首
+        let err = match result {
+            Ok(info) => return Ok(info),
+            Err(e) if attempt < max_retries && !net::is_unavailable(&e) => e,
+            Err(e) => return Err(e),
+        };
+
+        attempt += 1;
+        let delay = (RETRY_BASE_DELAY * 2u32.saturating_pow(attempt - 1)).min(RETRY_MAX_DELAY);
+        {
+            let mut a = app.lock().await;
+            if !a.is_active_request(request_id) {
+                return Err(err);
+            }
+            a.add_log(format!(
+                "⚠ 播放失败（{}），{} 秒后重试 ({}/{})",
+                err,
+                delay.as_secs(),
+                attempt,
+                max_retries
+            ));
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
 pub struct Player {
     audio: Arc<AudioBackend>,
     app: Arc<Mutex<App>>,
-    config: Config,
+    config: Mutex<Config>,
     active_task: Mutex<Option<JoinHandle<()>>>,
+    /// 下一首的预取任务，附带发起预取时正在播放的歌曲（每首歌只预取一次）
+    prefetch_task: Mutex<Option<(String, JoinHandle<()>)>>,
+    playback_ended: Mutex<watch::Receiver<()>>,
 }
 
 impl Player {
     pub fn new(audio: Arc<AudioBackend>, app: Arc<Mutex<App>>, config: Config) -> Self {
+        let playback_ended = Mutex::new(audio.subscribe_playback_ended());
         Self {
             audio,
             app,
-            config,
+            config: Mutex::new(config),
             active_task: Mutex::new(None),
+            prefetch_task: Mutex::new(None),
+            playback_ended,
         }
     }
 
+    /// 当前生效的配置
+    pub async fn config(&self) -> Config {
+        self.config.lock().await.clone()
+    }
+
+    /// 应用重新加载后的配置，之后的搜索、播放、快进和音量调节都会使用新值
+    pub async fn update_config(&self, config: Config) {
+        self.audio.update_config(config.clone()).await;
+        *self.config.lock().await = config;
+    }
+
+    /// 运行时修改搜索结果的排除关键词，只在本次运行内有效，重新加载配置后恢复为配置文件中的值
+    pub async fn set_exclude_patterns(&self, input: String) {
+        let patterns: Vec<String> = input
+            .split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(str::to_string)
+            .collect();
+        let mut config = self.config().await;
+        config.search.exclude_patterns = patterns.clone();
+        self.update_config(config).await;
+
+        let mut app_lock = self.app.lock().await;
+        if patterns.is_empty() {
+            app_lock.add_log("已清空搜索排除关键词".to_string());
+        } else {
+            app_lock.add_log(format!(
+                "搜索排除关键词: {}（本次运行有效，写入 search.exclude_patterns 可长期保留）",
+                patterns.join(", ")
+            ));
+        }
+        app_lock.exclude_patterns = patterns;
+    }
+
     async fn replace_active_task(&self, next: JoinHandle<()>) {
         let mut active_task = self.active_task.lock().await;
         if let Some(prev) = active_task.take() {
@@ -58,27 +202,140 @@ impl Player {
         }
     }
 
+    /// 用上一次的关键词重新搜索。复用 `search` 的流程，回到第 1 页并丢弃旧的分页缓存
+    pub async fn repeat_search(&self) {
+        let keyword = {
+            let mut app_lock = self.app.lock().await;
+            match app_lock.repeat_search_keyword() {
+                Some(keyword) => {
+                    app_lock.add_log(format!("重新搜索: {}", keyword));
+                    keyword
+                }
+                None => {
+                    app_lock.add_log("还没有搜索过，按 s 输入关键词".to_string());
+                    return;
+                }
+            }
+        };
+        self.search(keyword).await;
+    }
+
+    /// 取消正在进行的搜索、翻页或歌曲解析：abort 当前任务（yt-dlp 子进程随 `kill_on_drop` 结束），
+    /// 作废在途请求并恢复搜索前的状态
+    pub async fn cancel_search(&self) {
+        self.cancel_active_task().await;
+        let mut app_lock = self.app.lock().await;
+        // 换一个新的请求 ID，让已经返回的旧结果失效；同时复位翻页加载标记
+        app_lock.begin_async_request();
+        if matches!(app_lock.status, PlayerStatus::Searching) {
+            if app_lock.search_results.is_empty() {
+                app_lock.restore_status_after_search();
+            } else {
+                app_lock.status = PlayerStatus::SearchResults;
+            }
+        }
+        app_lock.add_log("搜索已取消".to_string());
+    }
+
+    /// 切歌时取消尚未完成的预取
+    async fn cancel_prefetch(&self) {
+        if let Some((_, task)) = self.prefetch_task.lock().await.take() {
+            task.abort();
+        }
+    }
+
+    /// 在后台解析下一首的音频流并写入缓存，切歌时即可直接命中。
+    /// 同一首歌播放期间只发起一次；下一首有本地文件或无法预测（如单曲循环）时跳过。
+    async fn prefetch_next(&self) {
+        let mut prefetch_task = self.prefetch_task.lock().await;
+        let (current, next, source) = {
+            let mut app_lock = self.app.lock().await;
+            if prefetch_task
+                .as_ref()
+                .is_some_and(|(song, _)| *song == app_lock.current_song)
+            {
+                return;
+            }
+            app_lock.plan_next_shuffle();
+            let Some((next, source, local_path)) = app_lock.peek_next_song() else {
+                return;
+            };
+            if local_path.is_some_and(|p| std::path::Path::new(&p).exists()) {
+                return;
+            }
+            (app_lock.current_song.clone(), next, source)
+        };
+
+        let audio_c = Arc::clone(&self.audio);
+        let app_c = Arc::clone(&self.app);
+        let task = tokio::spawn(async move {
+            match audio_c.prefetch_url(&next, &source, |_| {}).await {
+                Ok(true) => {
+                    app_c
+                        .lock()
+                        .await
+                        .add_log(format!("⇢ 已预取下一首: {}", next));
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    app_c
+                        .lock()
+                        .await
+                        .add_log(format!("⚠ 预取下一首失败: {}", e));
+                }
+            }
+        });
+        if let Some((_, prev)) = prefetch_task.replace((current, task)) {
+            prev.abort();
+        }
+    }
+
     pub async fn search(&self, keyword: String) {
         let mut app_lock = self.app.lock().await;
         app_lock.save_status_before_search();
         app_lock.status = PlayerStatus::Searching;
+        app_lock.load_stage = None;
         app_lock.clear_search_results();
         let request_id = app_lock.begin_async_request();
         drop(app_lock);
 
         let audio_c = Arc::clone(&self.audio);
         let app_c = Arc::clone(&self.app);
-        let page_size = self.config.search.max_results;
+        let page_size = self.config.lock().await.search.max_results;
         let keyword_clone = keyword.clone();
 
         let task = tokio::spawn(async move {
             let log_tx = spawn_log_forwarder(app_c.clone());
 
+            // 边搜索边把结果追加到列表，搜索完成后再用完整结果替换
+            let (result_tx, mut result_rx) = mpsc::unbounded_channel::<SearchResult>();
+            let app_f = app_c.clone();
+            let forwarder = tokio::spawn(async move {
+                while let Some(result) = result_rx.recv().await {
+                    let mut a = app_f.lock().await;
+                    if a.is_active_request(request_id)
+                        && matches!(a.status, PlayerStatus::Searching)
+                    {
+                        a.push_partial_search_result(result);
+                    }
+                }
+            });
+
             let result = audio_c
-                .search(&keyword, 1, |log| {
-                    let _ = log_tx.try_send(log);
-                })
+                .search_streaming(
+                    &keyword,
+                    1,
+                    |log| {
+                        let _ = log_tx.try_send(log);
+                    },
+                    |result| {
+                        let _ = result_tx.send(result);
+                    },
+                )
                 .await;
+            // 关闭结果通道后等转发任务处理完剩余的结果，避免它们在完整结果之后才追加
+            drop(result_tx);
+            let _ = forwarder.await;
 
             match result {
                 Ok(results) => {
@@ -95,6 +352,7 @@ impl Player {
                         a.total_pages = if count < page_size { 1 } else { usize::MAX };
                         a.cache_page(1, results.clone());
                         a.set_search_results(results, keyword_clone);
+                        playlist::prefetch_next_page(&audio_c, &app_c, &mut a, page_size);
                         a.add_log(format!("找到 {} 个结果，使用 ↑↓ 选择，Enter 播放", count));
                     }
                 }
@@ -103,7 +361,9 @@ impl Player {
                     if !a.is_active_request(request_id) {
                         return;
                     }
-                    a.status = PlayerStatus::Error(e.to_string());
+                    // 丢弃失败前已经显示的部分结果
+                    a.clear_search_results();
+                    a.set_search_error(e.to_string(), net::error_detail(&e));
                     a.add_log(format!("搜索错误: {}", e));
                 }
             }
@@ -113,48 +373,62 @@ impl Player {
     }
 
     pub async fn play_selected_result(&self) {
+        self.cancel_prefetch().await;
         let mut app_lock = self.app.lock().await;
 
         if let Some(result) = app_lock.get_selected_search_result() {
             let title = result.title.clone();
+            let source = result.source.clone();
+            let start_secs = app_lock.favorite_start_offset(&title, &source);
             let request_id = app_lock.begin_async_request();
-            app_lock.playing_from_search = true;
+            app_lock.playback_context = PlaybackContext::SearchResults;
+            app_lock.current_source = source.clone();
             drop(app_lock);
 
             let audio_c = Arc::clone(&self.audio);
             let app_c = Arc::clone(&self.app);
 
-            let task = tokio::spawn(async move {
-                let log_tx = spawn_log_forwarder(app_c.clone());
+            let max_retries = self.config.lock().await.network.max_retries;
 
+            let task = tokio::spawn(async move {
                 {
                     let mut a = app_c.lock().await;
                     if !a.is_active_request(request_id) {
                         return;
                     }
-                    a.status = PlayerStatus::Searching;
-                    a.current_song = title.clone();
-                    a.progress = 0.0;
+                    a.reset_track_state(&title);
                 }
 
-                let result = audio_c
-                    .search_and_play(&title, None, |log| {
-                        let _ = log_tx.try_send(log);
-                    })
-                    .await;
+                let result = play_with_retry(
+                    &audio_c,
+                    &app_c,
+                    request_id,
+                    PlayRequest {
+                        song: &title,
+                        source: &source,
+                        local_path_hint: None,
+                        start_secs,
+                        fade: None,
+                    },
+                    max_retries,
+                )
+                .await;
 
                 match result {
-                    Ok(out_local_path) => {
+                    Ok(info) => {
                         let mut a = app_c.lock().await;
                         if !a.is_active_request(request_id) {
                             return;
                         }
+                        a.current_track_url = shareable_url(&a, &title, &source, &info);
+                        a.current_thumbnail = info.thumbnail;
+                        let out_local_path = info.local_path;
                         a.status = PlayerStatus::Playing;
+                        a.load_stage = Some(LoadStage::Buffering);
                         a.current_song = title.clone();
                         a.current_local_path = out_local_path.clone();
-                        if let Some(path) = out_local_path {
-                            a.update_favorite_local_path(&title, path);
-                        }
+                        a.record_play(&title, &source, out_local_path.clone());
+                        a.record_favorite_play(&title, &source, out_local_path);
                         a.sync_selected_favorite();
                     }
                     Err(e) => {
@@ -162,8 +436,7 @@ impl Player {
                         if !a.is_active_request(request_id) {
                             return;
                         }
-                        a.add_log(format!("播放失败: {}", e));
-                        a.status = PlayerStatus::Error(e.to_string());
+                        report_play_error(&mut a, e);
                     }
                 }
             });
@@ -172,39 +445,165 @@ impl Player {
         }
     }
 
+    /// 试听选中的搜索结果：以一半音量播放 `search.preview_secs` 秒后停止，停留在搜索结果中。
+    /// 试听会停止当前播放；再次试听或开始播放时，上一次试听的定时停止随任务一起取消
+    pub async fn preview_selected_result(&self) {
+        self.cancel_prefetch().await;
+        let secs = self.config.lock().await.search.preview_secs.max(1);
+        let (title, source, request_id) = {
+            let mut app_lock = self.app.lock().await;
+            let Some(result) = app_lock.get_selected_search_result() else {
+                return;
+            };
+            let title = result.title.clone();
+            let source = result.source.clone();
+            let request_id = app_lock.begin_async_request();
+            // 当前播放会被试听替换，离开搜索结果后不再恢复为播放状态
+            if matches!(
+                app_lock.saved_status,
+                Some(PlayerStatus::Playing | PlayerStatus::Paused)
+            ) {
+                app_lock.saved_status = Some(PlayerStatus::Waiting);
+            }
+            app_lock.preview = Some(title.clone());
+            app_lock.add_log(format!("🎧 试听 {} 秒: {}", secs, title));
+            (title, source, request_id)
+        };
+
+        let audio_c = Arc::clone(&self.audio);
+        let app_c = Arc::clone(&self.app);
+        let task = tokio::spawn(async move {
+            let log_tx = spawn_log_forwarder(Arc::clone(&app_c));
+            let result = audio_c
+                .search_and_play(
+                    &title,
+                    &source,
+                    None,
+                    PlayOptions {
+                        preview: true,
+                        ..PlayOptions::default()
+                    },
+                    |log| {
+                        let _ = log_tx.try_send(log);
+                    },
+                )
+                .await;
+            if let Err(e) = result {
+                let mut a = app_c.lock().await;
+                if a.is_active_request(request_id) {
+                    a.preview = None;
+                    a.add_log(format!("❌ 试听失败: {}", e));
+                }
+                return;
+            }
+
+            tokio::time::sleep(Duration::from_secs(secs)).await;
+            if !app_c.lock().await.is_active_request(request_id) {
+                return;
+            }
+            audio_c.quit().await;
+            let mut a = app_c.lock().await;
+            a.preview = None;
+            a.add_log(format!("试听结束: {}", title));
+        });
+
+        self.replace_active_task(task).await;
+    }
+
     pub async fn search_and_play(&self, song: String, local_path_hint: Option<String>) {
+        self.play_track(song, local_path_hint, None, None).await;
+    }
+
+    /// 强制重新解析当前歌曲：移除 URL 缓存后重新播放，尽量从当前位置继续。
+    /// 用于缓存的地址已过期导致播放失败的情况；本地缓存文件不受影响
+    pub async fn refresh_current(&self) {
+        let (song, source, position) = {
+            let app_lock = self.app.lock().await;
+            if app_lock.current_song.is_empty()
+                || matches!(app_lock.status, PlayerStatus::Searching)
+            {
+                return;
+            }
+            let position = matches!(
+                app_lock.status,
+                PlayerStatus::Playing | PlayerStatus::Paused
+            )
+            .then_some(app_lock.time_pos as u64)
+            .filter(|secs| *secs > 0);
+            (
+                app_lock.current_song.clone(),
+                app_lock.source_for_title(&app_lock.current_song),
+                position,
+            )
+        };
+        let evicted = self.audio.evict_cached(&song, &source).await;
+        self.app.lock().await.add_log(format!(
+            "↻ 强制刷新: {}{}",
+            song,
+            if evicted {
+                "（已移除缓存的 URL）"
+            } else {
+                ""
+            }
+        ));
+        self.play_track(song, None, position, None).await;
+    }
+
+    /// 播放指定歌曲；`start_secs` 为 None 时从收藏设置的起始位置开始，
+    /// `fade` 不为 None 时与正在播放的歌曲淡入淡出衔接
+    async fn play_track(
+        &self,
+        song: String,
+        local_path_hint: Option<String>,
+        start_secs: Option<u64>,
+        fade: Option<Duration>,
+    ) {
+        self.cancel_prefetch().await;
         let mut app_lock = self.app.lock().await;
         let request_id = app_lock.begin_async_request();
-        app_lock.status = PlayerStatus::Searching;
-        app_lock.current_song = song.clone();
+        app_lock.reset_track_state(&song);
         app_lock.current_local_path = local_path_hint.clone();
-        app_lock.progress = 0.0;
+        let source = app_lock.source_for_title(&song);
+        app_lock.current_source = source.clone();
+        let start_secs = start_secs.or_else(|| app_lock.favorite_start_offset(&song, &source));
         drop(app_lock);
 
         let audio_c = Arc::clone(&self.audio);
         let app_c = Arc::clone(&self.app);
 
-        let task = tokio::spawn(async move {
-            let log_tx = spawn_log_forwarder(app_c.clone());
+        let max_retries = self.config.lock().await.network.max_retries;
 
-            let result = audio_c
-                .search_and_play(&song, local_path_hint, |log| {
-                    let _ = log_tx.try_send(log);
-                })
-                .await;
+        let task = tokio::spawn(async move {
+            let result = play_with_retry(
+                &audio_c,
+                &app_c,
+                request_id,
+                PlayRequest {
+                    song: &song,
+                    source: &source,
+                    local_path_hint,
+                    start_secs,
+                    fade,
+                },
+                max_retries,
+            )
+            .await;
 
             match result {
-                Ok(out_local_path) => {
+                Ok(info) => {
                     let mut a = app_c.lock().await;
                     if !a.is_active_request(request_id) {
                         return;
                     }
+                    a.current_track_url = shareable_url(&a, &song, &source, &info);
+                    a.current_thumbnail = info.thumbnail;
+                    let out_local_path = info.local_path;
                     a.status = PlayerStatus::Playing;
+                    a.load_stage = Some(LoadStage::Buffering);
                     a.current_song = song.clone();
                     a.current_local_path = out_local_path.clone();
-                    if let Some(path) = out_local_path {
-                        a.update_favorite_local_path(&song, path);
-                    }
+                    a.record_play(&song, &source, out_local_path.clone());
+                    a.record_favorite_play(&song, &source, out_local_path);
                     a.sync_selected_favorite();
                 }
                 Err(e) => {
@@ -212,8 +611,7 @@ impl Player {
                     if !a.is_active_request(request_id) {
                         return;
                     }
-                    a.add_log(format!("播放失败: {}", e));
-                    a.status = PlayerStatus::Error(e.to_string());
+                    report_play_error(&mut a, e);
                 }
             }
         });
@@ -252,14 +650,37 @@ impl Player {
         }
     }
 
+    /// 如果自上次检查以来 mpv 已经结束播放，立即执行切歌逻辑并返回 true。
+    /// 主循环每轮都会调用，切歌不必等到下一个 tick。
+    pub async fn handle_playback_ended(&self) -> bool {
+        let ended = {
+            let mut rx = self.playback_ended.lock().await;
+            let ended = rx.has_changed().unwrap_or(false);
+            rx.mark_unchanged();
+            ended
+        };
+        if ended {
+            self.check_and_play_next().await;
+        }
+        ended
+    }
+
     pub async fn check_and_play_next(&self) {
+        let mpv_errors = self.audio.take_mpv_errors().await;
         let current_status = {
-            let app_lock = self.app.lock().await;
+            let mut app_lock = self.app.lock().await;
+            for error in mpv_errors {
+                app_lock.add_log(error);
+            }
+            // 不在播放时停掉收听计时，恢复播放后从下一个 tick 重新开始计
+            if !matches!(app_lock.status, PlayerStatus::Playing) {
+                app_lock.tick_listening(false);
+            }
             app_lock.status.clone()
         };
 
-        // 错误恢复：检测到错误状态时自动播放下一首
-        if let PlayerStatus::Error(_) = current_status {
+        // 错误恢复：只有播放错误（含歌曲不可用）才自动播放下一首，搜索错误留给用户重试
+        if let PlayerStatus::PlaybackError(_) | PlayerStatus::Unavailable(_) = current_status {
             let next_song_data = {
                 let mut app_lock = self.app.lock().await;
                 if let Some((next_song, next_path)) = app_lock.get_next_song() {
@@ -281,13 +702,91 @@ impl Player {
             return;
         }
 
+        // 单曲循环时让 mpv 播完后停在结尾；地址仍然有效就直接跳回开头，
+        // 不重新解析、不重启 mpv，循环之间没有停顿
+        let single = self.app.lock().await.play_mode == PlayMode::Single;
+        self.audio.set_keep_open(single).await;
+        let eof_reached = self.audio.is_eof_reached().await;
+        if eof_reached && single && self.rewind_single().await {
+            return;
+        }
+
         let progress_result = self.audio.get_progress().await;
-        let pause_state_result = self.audio.get_pause_state().await;
+        // 停在结尾（切换了播放模式或地址已过期）按播放结束处理，由下面的逻辑切歌或重新解析
+        let pause_state_result = if eof_reached {
+            PauseState::Stopped
+        } else {
+            self.audio.get_pause_state().await
+        };
+        let buffering_result = self.audio.get_buffering().await;
+        let time_pos_result = self.audio.get_time_pos().await;
+        let duration_result = self.audio.get_duration().await;
+        let (codec_result, bitrate_result) = self.audio.get_audio_quality().await;
+        let (chapters_result, chapter_result) = self.audio.get_chapters().await;
+        let loudness_result = self.audio.get_loudness().await;
+        let (muted_result, pre_mute_volume_result) = self.audio.get_mute().await;
+        // 淡入淡出期间的音量只是中间值，不同步到界面
+        let volume_result = if self.audio.is_fading().await {
+            None
+        } else {
+            Some(self.audio.get_volume().await)
+        };
+
+        // 开启淡入淡出时，在当前歌曲结束前 crossfade_secs 秒提前切到下一首；
+        // 太短的歌曲（不足两倍渐变时长）仍然等播放结束后直接切换
+        let (crossfade_secs, end_threshold_pct, stop_confirm_ticks, queue_end) = {
+            let config = self.config.lock().await;
+            (
+                config.playback.crossfade_secs,
+                config.playback.end_threshold_pct,
+                config.playback.stop_confirm_ticks.max(1),
+                config
+                    .playback
+                    .queue_end_action()
+                    .unwrap_or(QueueEndAction::Stop),
+            )
+        };
+        let crossfade_due = crossfade_secs > 0
+            && pause_state_result == PauseState::Playing
+            && duration_result > (crossfade_secs * 2) as f64
+            && duration_result - time_pos_result <= crossfade_secs as f64;
+
+        lyrics::sync(&self.app).await;
+        cover::sync(&self.app).await;
 
         let next_song_data = {
             let mut app_lock = self.app.lock().await;
 
             app_lock.progress = progress_result;
+            app_lock.time_pos = time_pos_result;
+            app_lock.duration = duration_result;
+            app_lock.buffering = buffering_result;
+            app_lock.audio_codec = codec_result;
+            app_lock.audio_bitrate = bitrate_result;
+            app_lock.chapters = chapters_result;
+            app_lock.chapter = chapter_result;
+            app_lock.loudness = loudness_result;
+            // mpv 开始推进播放位置后，加载阶段结束
+            if time_pos_result > 0.0 {
+                app_lock.load_stage = None;
+            }
+            // 以 mpv 回报的静音状态为准，避免切歌后界面与实际不一致
+            app_lock.muted = muted_result;
+            app_lock.pre_mute_volume = pre_mute_volume_result;
+            // 音量调节时界面先乐观更新，这里再以 mpv 回报的实际音量为准
+            if let Some(volume) = volume_result {
+                app_lock.volume = volume;
+            }
+            app_lock.tick_listening(pause_state_result == PauseState::Playing);
+
+            // 停止状态没能持续到确认次数就恢复了，说明只是网络抖动等造成的误报
+            if pause_state_result != PauseState::Stopped && app_lock.stopped_polls > 0 {
+                app_lock.stopped_polls = 0;
+                app_lock.add_log(format!(
+                    "已忽略一次短暂的停止（进度 {:.0}%），继续播放",
+                    progress_result * 100.0
+                ));
+            }
 
             match pause_state_result {
                 PauseState::Paused => {
@@ -300,39 +799,257 @@ impl Player {
                     if matches!(app_lock.status, PlayerStatus::Paused) {
                         app_lock.status = PlayerStatus::Playing;
                     }
-                    None
+                    if crossfade_due {
+                        let next = app_lock.get_next_song();
+                        if let Some((next_song, _)) = &next {
+                            app_lock.add_log(format!("淡入淡出切换到下一首: {}", next_song));
+                        }
+                        next
+                    } else {
+                        None
+                    }
                 }
                 PauseState::Stopped => {
-                    if let Some((next_song, next_path)) = app_lock.get_next_song() {
-                        app_lock.add_log(format!("自动播放下一首: {}", next_song));
-                        Some((next_song, next_path))
-                    } else {
-                        app_lock.status = PlayerStatus::Waiting;
-                        app_lock.add_log("播放完成".to_string());
+                    // 进度接近结尾时立即切歌；否则等停止状态连续出现 stop_confirm_ticks 次
+                    app_lock.stopped_polls += 1;
+                    let near_end = progress_result * 100.0 >= end_threshold_pct;
+                    if !near_end && app_lock.stopped_polls < stop_confirm_ticks {
                         None
+                    } else {
+                        app_lock.stopped_polls = 0;
+                        let next = app_lock
+                            .get_next_song()
+                            .or_else(|| app_lock.next_after_queue_end(queue_end));
+                        if let Some((next_song, next_path)) = next {
+                            app_lock.add_log(format!("自动播放下一首: {}", next_song));
+                            Some((next_song, next_path))
+                        } else {
+                            app_lock.status = PlayerStatus::Waiting;
+                            if queue_end == QueueEndAction::Quit {
+                                app_lock.add_log("播放完成，退出".to_string());
+                                app_lock.running = false;
+                            } else {
+                                app_lock.add_log("播放完成".to_string());
+                            }
+                            None
+                        }
                     }
                 }
             }
         };
 
         if let Some((next_song, next_path)) = next_song_data {
-            self.search_and_play(next_song, next_path).await;
+            let fade = crossfade_due.then(|| Duration::from_secs(crossfade_secs));
+            self.play_track(next_song, next_path, None, fade).await;
+        } else if pause_state_result == PauseState::Playing && progress_result >= PREFETCH_PROGRESS
+        {
+            self.prefetch_next().await;
+        }
+    }
+
+    /// 单曲循环：当前地址仍然有效时让 mpv 跳回开头，返回是否成功；
+    /// 地址已过期或跳转失败时返回 false，交给常规流程重新解析
+    async fn rewind_single(&self) -> bool {
+        let (song, source, local_path) = {
+            let app_lock = self.app.lock().await;
+            (
+                app_lock.current_song.clone(),
+                app_lock.current_source.clone(),
+                app_lock.current_local_path.clone(),
+            )
+        };
+        if !self
+            .audio
+            .stream_still_valid(&song, &source, local_path.as_deref())
+            .await
+        {
+            self.app
+                .lock()
+                .await
+                .add_log("🔂 播放地址已过期，重新解析".to_string());
+            return false;
+        }
+        if let Err(e) = self.audio.rewind().await {
+            self.app
+                .lock()
+                .await
+                .add_log(format!("🔂 跳回开头失败（{}），重新解析", e));
+            return false;
+        }
+        let mut app_lock = self.app.lock().await;
+        app_lock.progress = 0.0;
+        app_lock.time_pos = 0.0;
+        app_lock.status = PlayerStatus::Playing;
+        app_lock.record_play(&song, &source, local_path.clone());
+        app_lock.record_favorite_play(&song, &source, local_path);
+        app_lock.add_log(format!("🔂 单曲循环: {}", song));
+        true
+    }
+
+    pub async fn play_next(&self) {
+        self.step(true).await;
+    }
+
+    pub async fn play_previous(&self) {
+        self.step(false).await;
+    }
+
+    /// 回到上一首实际播放过的歌曲（与列表顺序和播放模式无关）
+    pub async fn play_back_in_history(&self) {
+        let target = {
+            let mut app_lock = self.app.lock().await;
+            let target = app_lock.track_back();
+            match &target {
+                Some(item) => app_lock.add_log(format!("⏮ 回到上一首: {}", item.title)),
+                None => app_lock.add_log("没有更早播放过的歌曲".to_string()),
+            }
+            target
+        };
+        if let Some(item) = target {
+            self.search_and_play(item.title, item.local_path).await;
+        }
+    }
+
+    /// 媒体键 / 远程控制的“上一首”：与 `p` 一样沿播放历史回退，
+    /// 没有更早播放过的歌曲时退回为按列表切到上一首
+    pub async fn play_previous_or_back(&self) {
+        let target = {
+            let mut app_lock = self.app.lock().await;
+            let target = app_lock.track_back();
+            if let Some(item) = &target {
+                app_lock.add_log(format!("⏮ 回到上一首: {}", item.title));
+            }
+            target
+        };
+        match target {
+            Some(item) => self.search_and_play(item.title, item.local_path).await,
+            None => self.play_previous().await,
+        }
+    }
+
+    async fn step(&self, forward: bool) {
+        let next_song_data = {
+            let mut app_lock = self.app.lock().await;
+            // 按 p 回退过时，下一首先沿原路返回
+            if forward {
+                if let Some(item) = app_lock.track_forward() {
+                    app_lock.add_log(format!("⏭ 返回: {}", item.title));
+                    drop(app_lock);
+                    self.search_and_play(item.title, item.local_path).await;
+                    return;
+                }
+            }
+            let next = app_lock.step_song(forward);
+            if next.is_none() {
+                app_lock.add_log("没有可切换的歌曲".to_string());
+            }
+            next
+        };
+
+        if let Some((song, path)) = next_song_data {
+            self.search_and_play(song, path).await;
         }
     }
 
     pub async fn quit(&self) {
         self.cancel_active_task().await;
+        self.cancel_prefetch().await;
         self.audio.quit().await;
     }
 
     pub async fn seek_forward(&self) {
-        self.seek_with_log(self.config.playback.seek_seconds, "快进")
-            .await;
+        let seconds = self.config.lock().await.playback.seek_seconds;
+        self.seek_with_log(seconds, "快进").await;
     }
 
     pub async fn seek_backward(&self) {
-        self.seek_with_log(-self.config.playback.seek_seconds, "快退")
-            .await;
+        let seconds = self.config.lock().await.playback.seek_seconds;
+        self.seek_with_log(-seconds, "快退").await;
+    }
+
+    /// 跳转到用户输入的时间点（`mm:ss`、`hh:mm:ss` 或纯秒数）。没有正在播放的歌曲时忽略。
+    pub async fn seek_to(&self, input: String) {
+        let seconds = {
+            let mut app_lock = self.app.lock().await;
+            if !matches!(
+                app_lock.status,
+                PlayerStatus::Playing | PlayerStatus::Paused
+            ) {
+                return;
+            }
+            match parse_timestamp(&input) {
+                Some(seconds) => seconds,
+                None => {
+                    app_lock.add_log(format!(
+                        "无效的时间: {}（格式 mm:ss / hh:mm:ss / 秒数）",
+                        input
+                    ));
+                    return;
+                }
+            }
+        };
+        let log_message = match self.audio.seek_absolute(seconds).await {
+            Ok(_) => format!("跳转到 {}", format_duration(seconds)),
+            Err(e) => format!("跳转失败: {}", e),
+        };
+        self.app.lock().await.add_log(log_message);
+    }
+
+    /// 从头播放当前歌曲：直接跳到 0 秒，不重新解析地址。没有正在播放的歌曲时忽略
+    pub async fn restart_current(&self) {
+        {
+            let app_lock = self.app.lock().await;
+            if !matches!(
+                app_lock.status,
+                PlayerStatus::Playing | PlayerStatus::Paused
+            ) {
+                return;
+            }
+        }
+        let result = self.audio.seek_absolute(0).await;
+        let mut app_lock = self.app.lock().await;
+        match result {
+            Ok(_) => {
+                app_lock.progress = 0.0;
+                app_lock.time_pos = 0.0;
+                let message = format!("⏮ 从头播放: {}", app_lock.current_song);
+                app_lock.add_log(message);
+            }
+            Err(e) => app_lock.add_log(format!("跳转失败: {}", e)),
+        }
+    }
+
+    /// 跳到下一章 / 上一章；当前歌曲没有章节时退回为普通的快进 / 快退
+    pub async fn step_chapter(&self, forward: bool) {
+        let (chapters, current) = self.audio.get_chapters().await;
+        if chapters.is_empty() {
+            if forward {
+                self.seek_forward().await;
+            } else {
+                self.seek_backward().await;
+            }
+            return;
+        }
+        let target = match current {
+            Some(i) if forward => i + 1,
+            Some(i) => i.saturating_sub(1),
+            None => 0,
+        };
+        let log_message = if target >= chapters.len() {
+            "已是最后一章".to_string()
+        } else {
+            match self.audio.set_chapter(target).await {
+                Ok(_) => format!(
+                    "{} 章节 {}/{}: {}",
+                    if forward { "⏭" } else { "⏮" },
+                    target + 1,
+                    chapters.len(),
+                    chapters[target].label(target)
+                ),
+                Err(e) => format!("章节跳转失败: {}", e),
+            }
+        };
+        self.app.lock().await.add_log(log_message);
     }
 
     async fn seek_with_log(&self, seconds: i32, direction: &str) {
@@ -346,32 +1063,276 @@ impl Player {
     }
 
     pub async fn volume_up(&self) {
-        volume::change_volume_with_log(&self.audio, &self.app, self.config.playback.volume_step)
-            .await;
+        let step = self.config.lock().await.playback.volume_step;
+        volume::change_volume_with_log(&self.audio, &self.app, step).await;
     }
 
     pub async fn volume_down(&self) {
-        volume::change_volume_with_log(&self.audio, &self.app, -self.config.playback.volume_step)
-            .await;
+        let step = self.config.lock().await.playback.volume_step;
+        volume::change_volume_with_log(&self.audio, &self.app, -step).await;
+    }
+
+    /// 设置为用户输入的精确音量（0–130）
+    pub async fn set_volume(&self, input: String) {
+        volume::set_volume_with_log(&self.audio, &self.app, &input).await;
+    }
+
+    /// 在后台把当前歌曲保存到 `paths.download_dir`
+    pub async fn download_current(&self) {
+        download::download_current(&self.audio, &self.app).await;
+    }
+
+    /// 把当前歌曲的分享链接复制到系统剪贴板，结果写入日志
+    pub async fn copy_track_url(&self) {
+        let url = {
+            let mut app_lock = self.app.lock().await;
+            match app_lock.current_track_url.clone() {
+                Some(url) => url,
+                None => {
+                    app_lock.add_log("当前歌曲没有可复制的链接".to_string());
+                    return;
+                }
+            }
+        };
+        let app_c = Arc::clone(&self.app);
+        tokio::spawn(async move {
+            let log = match clipboard::copy(&url).await {
+                Ok(()) => format!("📋 已复制链接: {}", url),
+                Err(e) => format!("复制链接失败: {}", e),
+            };
+            app_c.lock().await.add_log(log);
+        });
+    }
+
+    pub async fn toggle_mute(&self) {
+        volume::toggle_mute_with_log(&self.audio, &self.app).await;
+    }
+
+    /// 切换到下一个均衡器预设。没有在播放时只记录预设，下一首开始生效
+    pub async fn cycle_eq_preset(&self) {
+        let (preset, playing) = {
+            let app_lock = self.app.lock().await;
+            (
+                app_lock.eq_preset.next(),
+                matches!(
+                    app_lock.status,
+                    PlayerStatus::Playing | PlayerStatus::Paused
+                ),
+            )
+        };
+        let result = self.audio.set_eq_preset(preset).await;
+        let mut app_lock = self.app.lock().await;
+        app_lock.eq_preset = preset;
+        match result {
+            Err(e) if playing => app_lock.add_log(format!("均衡器切换失败: {}", e)),
+            _ => app_lock.add_log(format!("🎚 均衡器: {}", preset.label())),
+        }
+    }
+
+    /// 按指定幅度调节音量（远程控制 `vol +N` / `vol -N`）
+    pub async fn change_volume(&self, delta: i32) {
+        volume::change_volume_with_log(&self.audio, &self.app, delta).await;
     }
 
     pub async fn next_page(&self) {
-        playlist::next_page(
-            &self.audio,
-            &self.app,
-            self.config.search.max_results,
-            &self.active_task,
-        )
-        .await;
+        let page_size = self.config.lock().await.search.max_results;
+        playlist::next_page(&self.audio, &self.app, page_size, &self.active_task).await;
     }
 
     pub async fn prev_page(&self) {
-        playlist::prev_page(
-            &self.audio,
-            &self.app,
-            self.config.search.max_results,
-            &self.active_task,
-        )
-        .await;
+        let page_size = self.config.lock().await.search.max_results;
+        playlist::prev_page(&self.audio, &self.app, page_size, &self.active_task).await;
+    }
+
+    /// 跳转到用户输入的搜索结果页码
+    pub async fn jump_to_page(&self, input: String) {
+        let page_size = self.config.lock().await.search.max_results;
+        playlist::jump_to_page(&self.audio, &self.app, &input, page_size, &self.active_task).await;
+    }
+}
+
+/// 解析 `mm:ss` / `hh:mm:ss` / 纯秒数形式的时间点为总秒数。
+/// 除最高位外，分、秒字段必须小于 60。
+fn parse_timestamp(input: &str) -> Option<u64> {
+    let parts: Vec<&str> = input.trim().split(':').collect();
+    if parts.len() > 3 || parts.iter().any(|p| p.is_empty()) {
+        return None;
+    }
+    let mut total = 0u64;
+    for (i, part) in parts.iter().enumerate() {
+        let value: u64 = part.parse().ok()?;
+        if i > 0 && value >= 60 {
+            return None;
+        }
+        total = total.checked_mul(60)?.checked_add(value)?;
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::mock::{self, MockSource};
+    use crate::test_support::test_app;
+    use std::sync::atomic::Ordering;
+
+    fn player(name: &str, source: MockSource, config: Config) -> Player {
+        let audio =
+            AudioBackend::with_source(config.clone(), Box::new(source)).with_fake_playback();
+        let app = Arc::new(Mutex::new(test_app(name)));
+        Player::new(Arc::new(audio), app, config)
+    }
+
+    /// 等待当前的搜索或播放任务结束
+    async fn finish_active_task(player: &Player) {
+        let task = player.active_task.lock().await.take();
+        task.expect("没有正在进行的任务").await.unwrap();
+    }
+
+    /// 等待后台任务（如预取下一页）把 App 改到满足 `done` 的状态
+    async fn wait_until(player: &Player, done: impl Fn(&App) -> bool) {
+        for _ in 0..200 {
+            if done(&*player.app.lock().await) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("等待超时");
+    }
+
+    fn page_config(page_size: usize) -> Config {
+        let mut config = Config::default();
+        config.search.max_results = page_size;
+        config
+    }
+
+    #[tokio::test]
+    async fn search_shows_results_and_detects_last_page() {
+        let results = vec![mock::result("a", "yt"), mock::result("b", "bili")];
+        let player = player("player-search", MockSource::new(results), Config::default());
+
+        player.search("kw".to_string()).await;
+        finish_active_task(&player).await;
+
+        let app = player.app.lock().await;
+        assert!(matches!(app.status, PlayerStatus::SearchResults));
+        let titles: Vec<_> = app
+            .search_results
+            .iter()
+            .map(|r| r.title.as_str())
+            .collect();
+        assert_eq!(titles, ["a", "b"]);
+        assert_eq!(app.last_search_keyword, "kw");
+        // 结果少于一页，说明已经是最后一页
+        assert_eq!(app.total_pages, 1);
+    }
+
+    #[tokio::test]
+    async fn failed_search_discards_partial_results() {
+        let source = MockSource::new(vec![mock::result("a", "yt")]).failing_search("超时");
+        let player = player("player-search-error", source, Config::default());
+
+        player.search("kw".to_string()).await;
+        finish_active_task(&player).await;
+
+        let app = player.app.lock().await;
+        assert!(matches!(&app.status, PlayerStatus::SearchError(e) if e == "超时"));
+        assert!(app.search_results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn play_resolves_and_records_history() {
+        let source = MockSource::new(Vec::new());
+        let calls = source.resolve_calls();
+        let player = player("player-play", source, Config::default());
+
+        player.search_and_play("song".to_string(), None).await;
+        finish_active_task(&player).await;
+
+        let app = player.app.lock().await;
+        assert!(matches!(app.status, PlayerStatus::Playing));
+        assert_eq!(app.current_song, "song");
+        assert_eq!(app.current_track_url, Some(mock::watch_url("song")));
+        assert_eq!(
+            app.play_history.front().map(|i| i.title.as_str()),
+            Some("song")
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn unavailable_song_is_not_retried() {
+        let source = MockSource::new(Vec::new()).unavailable();
+        let calls = source.resolve_calls();
+        let mut config = Config::default();
+        config.network.max_retries = 3;
+        let player = player("player-unavailable", source, config);
+
+        player.search_and_play("gone".to_string(), None).await;
+        finish_active_task(&player).await;
+
+        let app = player.app.lock().await;
+        assert!(matches!(app.status, PlayerStatus::Unavailable(_)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_play_retries_then_reports_error() {
+        let source = MockSource::new(Vec::new()).failing_resolve("网络错误");
+        let calls = source.resolve_calls();
+        let mut config = Config::default();
+        // 重试前会等待 1 秒，只重试一次
+        config.network.max_retries = 1;
+        let player = player("player-retry", source, config);
+
+        player.search_and_play("song".to_string(), None).await;
+        finish_active_task(&player).await;
+
+        let app = player.app.lock().await;
+        assert!(matches!(&app.status, PlayerStatus::PlaybackError(e) if e == "网络错误"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn short_final_page_ends_paging() {
+        let pages = vec![
+            vec![mock::result("a", "yt"), mock::result("b", "yt")],
+            vec![mock::result("c", "yt")],
+        ];
+        let player = player("paging-short", MockSource::paged(pages), page_config(2));
+
+        player.search("kw".to_string()).await;
+        finish_active_task(&player).await;
+        // 第 1 页是满页，后台预取的第 2 页不足一页，说明它就是最后一页
+        wait_until(&player, |a| a.get_cached_page(2).is_some()).await;
+        assert_eq!(player.app.lock().await.total_pages, 2);
+
+        player.next_page().await;
+        let mut app = player.app.lock().await;
+        assert_eq!(app.current_page, 2);
+        let titles: Vec<_> = app
+            .search_results
+            .iter()
+            .map(|r| r.title.as_str())
+            .collect();
+        assert_eq!(titles, ["c"]);
+        assert!(!app.begin_page_prefetch(3));
+    }
+
+    #[tokio::test]
+    async fn repeated_page_is_not_shown_again() {
+        // 搜索源忽略页码，每页都返回同样的结果
+        let results = vec![mock::result("a", "yt"), mock::result("b", "yt")];
+        let player = player("paging-repeat", MockSource::new(results), page_config(2));
+
+        player.search("kw".to_string()).await;
+        finish_active_task(&player).await;
+        wait_until(&player, |a| a.total_pages == 1).await;
+
+        player.next_page().await;
+        let app = player.app.lock().await;
+        assert_eq!(app.current_page, 1);
+        assert_eq!(app.search_results.len(), 2);
+        assert!(app.get_cached_page(2).is_none());
     }
 }
@@ -1,5 +1,5 @@
 use crate::app::App;
-use crate::net::AudioBackend;
+use crate::net::{AudioBackend, SearchResult};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
@@ -62,6 +62,55 @@ pub async fn prev_page(
     .await;
 }
 
+/// 跳转到用户输入的页码。页码必须在 1 到已知总页数之间（总页数未知时只要求不小于 1），
+/// 否则只记录日志；已缓存的页直接显示。
+pub async fn jump_to_page(
+    audio: &Arc<AudioBackend>,
+    app: &Arc<Mutex<App>>,
+    input: &str,
+    page_size: usize,
+    active_task: &Mutex<Option<JoinHandle<()>>>,
+) {
+    let (keyword, page) = {
+        let mut app_lock = app.lock().await;
+        if app_lock.last_search_keyword.is_empty() {
+            return;
+        }
+        let total_pages = app_lock.total_pages;
+        match input.trim().parse::<usize>() {
+            Ok(page) if (1..=total_pages).contains(&page) => {
+                if page == app_lock.current_page {
+                    return;
+                }
+                (app_lock.last_search_keyword.clone(), page)
+            }
+            _ => {
+                let range_hint = if total_pages == usize::MAX {
+                    "需不小于 1".to_string()
+                } else {
+                    format!("范围 1–{}", total_pages)
+                };
+                app_lock.add_log(format!("无效的页码: {}（{}）", input, range_hint));
+                return;
+            }
+        }
+    };
+
+    search_page(audio, app, &keyword, page, page_size, active_task).await;
+}
+
+/// 不支持 `--playlist-items` 范围的搜索源每页都返回同样的开头几条结果。
+/// 与已缓存的上一页完全相同时视为没有更多结果
+fn repeats_previous_page(app: &App, page: usize, results: &[SearchResult]) -> bool {
+    let Some(previous) = page.checked_sub(1).and_then(|p| app.get_cached_page(p)) else {
+        return false;
+    };
+    let key = |r: &SearchResult| (r.title.clone(), r.webpage_url.clone());
+    !results.is_empty()
+        && previous.len() == results.len()
+        && previous.iter().map(key).eq(results.iter().map(key))
+}
+
 pub async fn search_page(
     audio: &Arc<AudioBackend>,
     app: &Arc<Mutex<App>>,
@@ -76,6 +125,7 @@ pub async fn search_page(
         let cached_results = cached_results.clone();
         app_lock.current_page = page;
         app_lock.set_search_results(cached_results, keyword.to_string());
+        prefetch_next_page(audio, app, &mut app_lock, page_size);
         return;
     }
 
@@ -107,7 +157,13 @@ pub async fn search_page(
                 if !a.is_active_request(request_id) {
                     return;
                 }
-                if results.is_empty() {
+                if repeats_previous_page(&a, page, &results) {
+                    a.total_pages = page - 1;
+                    a.add_log(format!(
+                        "⚠ 第 {} 页与上一页结果相同，搜索源可能不支持深度分页，已视为最后一页",
+                        page
+                    ));
+                } else if results.is_empty() {
                     if page > 1 {
                         a.total_pages = page - 1;
                         a.add_log(format!("已到达最后一页（第 {} 页）", page - 1));
@@ -122,6 +178,7 @@ pub async fn search_page(
                     }
                     a.cache_page(page, results.clone());
                     a.set_search_results(results, keyword_clone);
+                    prefetch_next_page(&audio_c, &app_c, &mut a, page_size);
                 }
                 a.is_loading_page = false;
             }
@@ -143,3 +200,45 @@ pub async fn search_page(
     }
     *guard = Some(task);
 }
+
+/// 当前页加载完成后，在后台把下一页写入 search_cache，让翻页可以直接命中缓存。
+/// 只预取一页；下一页已缓存、已知超出总页数或正在预取时跳过。
+/// 预取期间发起了新的搜索或翻页请求时丢弃结果。
+pub fn prefetch_next_page(
+    audio: &Arc<AudioBackend>,
+    app: &Arc<Mutex<App>>,
+    app_lock: &mut App,
+    page_size: usize,
+) {
+    let page = app_lock.current_page + 1;
+    if app_lock.last_search_keyword.is_empty() || !app_lock.begin_page_prefetch(page) {
+        return;
+    }
+    let request_id = app_lock.active_request_id();
+    let keyword = app_lock.last_search_keyword.clone();
+    let audio_c = Arc::clone(audio);
+    let app_c = Arc::clone(app);
+
+    tokio::spawn(async move {
+        // 预取过程不输出 yt-dlp 日志
+        let result = audio_c.search(&keyword, page, |_| {}).await;
+        let mut a = app_c.lock().await;
+        a.finish_page_prefetch(page);
+        if !a.is_active_request(request_id) || a.last_search_keyword != keyword {
+            return;
+        }
+        match result {
+            Ok(results) if results.is_empty() || repeats_previous_page(&a, page, &results) => {
+                a.total_pages = a.total_pages.min(page - 1);
+            }
+            Ok(results) => {
+                if results.len() < page_size {
+                    a.total_pages = page;
+                }
+                a.cache_page(page, results);
+            }
+            // 预取失败不影响当前页，翻页时会重新请求
+            Err(_) => {}
+        }
+    });
+}
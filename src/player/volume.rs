@@ -3,17 +3,22 @@ use crate::net::AudioBackend;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-/// 音量调节：+delta / -delta，读取更新后的实际音量并写日志
+/// 音量调节：+delta / -delta，音量限制在 0–`playback.max_volume`，到达上限/下限时在日志中提示
 pub async fn change_volume_with_log(audio: &Arc<AudioBackend>, app: &Arc<Mutex<App>>, delta: i32) {
+    let max_volume = audio.max_volume().await;
     match audio.change_volume(delta).await {
-        Ok(_) => {
-            // 读取 mpv 实际更新后的音量（稍等一个事件循环让 IPC 刷新）
-            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-            let vol = audio.get_volume().await;
+        Ok(vol) => {
             let mut app_lock = app.lock().await;
             app_lock.volume = vol;
             let direction = if delta > 0 { "🔊" } else { "🔈" };
-            app_lock.add_log(format!("{} 音量: {}%", direction, vol));
+            let limit_hint = if delta > 0 && vol == max_volume {
+                format!("（已达上限 {}%）", max_volume)
+            } else if delta < 0 && vol == 0 {
+                "（已达下限）".to_string()
+            } else {
+                String::new()
+            };
+            app_lock.add_log(format!("{} 音量: {}%{}", direction, vol, limit_hint));
         }
         Err(e) => {
             let mut app_lock = app.lock().await;
@@ -21,3 +26,55 @@ pub async fn change_volume_with_log(audio: &Arc<AudioBackend>, app: &Arc<Mutex<A
         }
     }
 }
+
+/// 设置为用户输入的精确音量：非数字输入只记录日志，超出 0–`playback.max_volume` 时截断。
+/// 界面立即显示目标音量，mpv 实际生效的值由轮询时的同步修正。
+pub async fn set_volume_with_log(audio: &Arc<AudioBackend>, app: &Arc<Mutex<App>>, input: &str) {
+    let max_volume = audio.max_volume().await;
+    let requested = match input.trim().parse::<u32>() {
+        Ok(v) => v,
+        Err(_) => {
+            app.lock().await.add_log(format!(
+                "无效的音量: {}（请输入 0–{} 的整数）",
+                input, max_volume
+            ));
+            return;
+        }
+    };
+    let target = requested.min(max_volume as u32) as u8;
+    match audio.set_volume(target).await {
+        Ok(vol) => {
+            let mut app_lock = app.lock().await;
+            app_lock.volume = vol;
+            let clamp_hint = if requested > max_volume as u32 {
+                "（已截断到上限）"
+            } else {
+                ""
+            };
+            app_lock.add_log(format!("🔊 音量: {}%{}", vol, clamp_hint));
+        }
+        Err(e) => {
+            app.lock().await.add_log(format!("音量设置失败: {}", e));
+        }
+    }
+}
+
+/// 切换静音，并同步界面上的静音状态与音量
+pub async fn toggle_mute_with_log(audio: &Arc<AudioBackend>, app: &Arc<Mutex<App>>) {
+    match audio.toggle_mute().await {
+        Ok((muted, vol)) => {
+            let mut app_lock = app.lock().await;
+            app_lock.muted = muted;
+            app_lock.volume = vol;
+            if muted {
+                app_lock.pre_mute_volume = vol;
+                app_lock.add_log(format!("🔇 已静音（静音前音量 {}%）", vol));
+            } else {
+                app_lock.add_log(format!("🔊 取消静音，音量恢复为 {}%", vol));
+            }
+        }
+        Err(e) => {
+            app.lock().await.add_log(format!("静音切换失败: {}", e));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! 远程控制：在 mpv socket 所在目录（默认 `/tmp`）的 `maboroshi-<PID>-ctl.sock` 上监听文本命令，
+//! 供脚本控制正在运行的实例。
+//!
+//! 每行一条命令，处理后回复一行 `ok` 或 `error: <原因>`：
+//! - `toggle`：暂停/继续
+//! - `next` / `prev`：下一首 / 上一首
+//! - `vol +10` / `vol -10`：调节音量
+//! - `search <关键词>`：搜索
+//! - `quit`：退出播放器
+//!
+//! 仅支持 Unix；其余平台上 [`spawn`] 直接返回 None。
+
+use crate::app::App;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+/// 单条命令的最大长度（字节），超出时断开连接
+#[cfg_attr(not(unix), allow(dead_code))]
+const MAX_LINE_LEN: usize = 1024;
+
+/// 远程发来的控制命令，由主循环转换为对应的 `Player` 操作
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(not(unix), allow(dead_code))]
+pub enum RemoteCommand {
+    Toggle,
+    Next,
+    Prev,
+    /// 音量相对调节量
+    Volume(i32),
+    Search(String),
+    Quit,
+}
+
+impl RemoteCommand {
+    /// 解析一行命令。关键词中的控制字符会被去掉。
+    #[cfg_attr(not(unix), allow(dead_code))]
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (name, arg) = match line.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (line, ""),
+        };
+        let no_arg = |cmd: RemoteCommand| {
+            if arg.is_empty() {
+                Ok(cmd)
+            } else {
+                Err(format!("{} 不接受参数", name))
+            }
+        };
+        match name {
+            "toggle" => no_arg(RemoteCommand::Toggle),
+            "next" => no_arg(RemoteCommand::Next),
+            "prev" => no_arg(RemoteCommand::Prev),
+            "quit" => no_arg(RemoteCommand::Quit),
+            "vol" => {
+                if !arg.starts_with(['+', '-']) {
+                    return Err("用法: vol +N / vol -N".to_string());
+                }
+                match arg.parse::<i32>() {
+                    Ok(delta) if delta != 0 && delta.abs() <= 130 => {
+                        Ok(RemoteCommand::Volume(delta))
+                    }
+                    _ => Err(format!("无效的音量调节: {}", arg)),
+                }
+            }
+            "search" => {
+                let keyword: String = arg.chars().filter(|c| !c.is_control()).collect();
+                if keyword.trim().is_empty() {
+                    Err("用法: search <关键词>".to_string())
+                } else {
+                    Ok(RemoteCommand::Search(keyword.trim().to_string()))
+                }
+            }
+            "" => Err("空命令".to_string()),
+            _ => Err(format!("未知命令: {}", name)),
+        }
+    }
+}
+
+/// 正在监听的控制 socket。drop 时停止监听并删除 socket 文件。
+pub struct RemoteControl {
+    rx: mpsc::Receiver<RemoteCommand>,
+    #[cfg(unix)]
+    path: std::path::PathBuf,
+    #[cfg(unix)]
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl RemoteControl {
+    pub async fn recv(&mut self) -> Option<RemoteCommand> {
+        self.rx.recv().await
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RemoteControl {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// 在 mpv socket `socket_path` 旁创建控制 socket 并开始监听。
+/// 创建失败时记录日志并返回 None（不影响正常播放）。
+#[cfg(unix)]
+pub async fn spawn(app: Arc<Mutex<App>>, socket_path: &str) -> Option<RemoteControl> {
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::UnixListener;
+
+    let path = crate::config::control_socket_path(socket_path, std::process::id());
+    let _ = std::fs::remove_file(&path);
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            app.lock().await.add_log(format!(
+                "⚠ 远程控制 socket 创建失败 ({}): {}",
+                path.display(),
+                e
+            ));
+            return None;
+        }
+    };
+    // 只允许当前用户连接
+    let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+
+    let (tx, rx) = mpsc::channel(16);
+    let task = tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(handle_connection(stream, tx.clone()));
+        }
+    });
+    Some(RemoteControl { rx, path, task })
+}
+
+#[cfg(not(unix))]
+pub async fn spawn(_app: Arc<Mutex<App>>, _socket_path: &str) -> Option<RemoteControl> {
+    None
+}
+
+/// 逐行读取命令并回复处理结果，连接关闭或命令过长时结束
+#[cfg(unix)]
+async fn handle_connection(stream: tokio::net::UnixStream, tx: mpsc::Sender<RemoteCommand>) {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = (&mut reader)
+            .take(MAX_LINE_LEN as u64 + 1)
+            .read_line(&mut line)
+            .await;
+        let reply = match read {
+            Ok(0) | Err(_) => break,
+            Ok(_) if line.len() > MAX_LINE_LEN => {
+                let _ = writer.write_all("error: 命令过长\n".as_bytes()).await;
+                break;
+            }
+            Ok(_) => match RemoteCommand::parse(&line) {
+                Ok(cmd) => match tx.send(cmd).await {
+                    Ok(()) => "ok".to_string(),
+                    Err(_) => break,
+                },
+                Err(e) => format!("error: {}", e),
+            },
+        };
+        if writer
+            .write_all(format!("{}\n", reply).as_bytes())
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! 退出信号：把 SIGINT / SIGTERM（Windows 下为 Ctrl-C）转成主循环里的退出请求，
+//! 让被 `kill` 或在 raw 模式之外按下 Ctrl-C 时也走正常的退出流程：
+//! 结束 mpv、删除 IPC / 远程控制 socket、恢复终端并保存会话。
+//!
+//! 终端恢复仍由主循环末尾统一处理（届时 `TerminalCleanupGuard` 已解除），这里不直接操作终端。
+
+use tokio::sync::mpsc;
+
+/// 开始监听退出信号，每收到一次就发送一次信号名。安装监听失败时返回的通道不会收到任何消息
+pub fn spawn() -> mpsc::UnboundedReceiver<&'static str> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(listen(tx));
+    rx
+}
+
+#[cfg(unix)]
+async fn listen(tx: mpsc::UnboundedSender<&'static str>) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let (Ok(mut sigint), Ok(mut sigterm)) = (
+        signal(SignalKind::interrupt()),
+        signal(SignalKind::terminate()),
+    ) else {
+        return;
+    };
+    loop {
+        let name = tokio::select! {
+            Some(()) = sigint.recv() => "SIGINT",
+            Some(()) = sigterm.recv() => "SIGTERM",
+            else => return,
+        };
+        if tx.send(name).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn listen(tx: mpsc::UnboundedSender<&'static str>) {
+    while tokio::signal::ctrl_c().await.is_ok() {
+        if tx.send("Ctrl-C").is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! 收听统计：累计收听时长和播放曲目数，保存在配置目录的 `stats.json`。
+//!
+//! 收听时长由主循环每个 tick 在内存中累加，每累计 [`SAVE_INTERVAL`] 写一次文件，退出时再写一次，
+//! 不会每个 tick 都读写磁盘。写入时先写临时文件再重命名，写到一半崩溃也不会损坏原文件；
+//! 文件无法解析时备份为 `stats.json.corrupt.<时间戳>` 并从零开始统计。
+
+use crate::app::App;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 未写盘的收听时长达到该值时保存一次
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 单次 tick 最多计入的时长，避免系统休眠唤醒后一次计入整段休眠时间
+const MAX_TICK: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListeningStats {
+    /// 累计收听秒数（只统计实际在播放的时间，暂停不计）
+    #[serde(default)]
+    pub total_secs: f64,
+    /// 累计开始播放的曲目数
+    #[serde(default)]
+    pub tracks_played: u64,
+    /// 上次保存后新增、尚未写盘的收听时长
+    #[serde(skip)]
+    unsaved: Duration,
+}
+
+impl ListeningStats {
+    fn path() -> PathBuf {
+        crate::config::config_dir().join("stats.json")
+    }
+
+    /// 读取统计文件；不存在时从零开始，损坏时备份后从零开始并返回提示
+    pub fn load() -> (Self, Option<String>) {
+        let path = Self::path();
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return (Self::default(), None);
+            }
+            Err(e) => {
+                return (
+                    Self::default(),
+                    Some(format!("读取收听统计失败 ({}): {}", path.display(), e)),
+                );
+            }
+        };
+        match serde_json::from_str::<Self>(&content) {
+            Ok(stats) if stats.total_secs.is_finite() && stats.total_secs >= 0.0 => (stats, None),
+            _ => match App::backup_corrupted_file(&path, "收听统计") {
+                Ok(backup_path) => (
+                    Self::default(),
+                    Some(format!(
+                        "收听统计已损坏并自动备份到: {}",
+                        backup_path.display()
+                    )),
+                ),
+                Err(e) => (Self::default(), Some(e)),
+            },
+        }
+    }
+
+    /// 写入统计文件（先写临时文件再重命名）
+    pub fn save(&mut self) -> Result<(), String> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| format!("序列化收听统计失败: {}", e))?;
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("创建收听统计目录失败 ({}): {}", parent.display(), e))?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, json)
+            .and_then(|_| fs::rename(&tmp_path, &path))
+            .map_err(|e| format!("保存收听统计失败 ({}): {}", path.display(), e))?;
+        self.unsaved = Duration::ZERO;
+        Ok(())
+    }
+
+    /// 计入一段收听时长；返回是否到了该写盘的时候
+    pub fn add_listening(&mut self, elapsed: Duration) -> bool {
+        let elapsed = elapsed.min(MAX_TICK);
+        self.total_secs += elapsed.as_secs_f64();
+        self.unsaved += elapsed;
+        self.unsaved >= SAVE_INTERVAL
+    }
+
+    /// 是否有尚未写盘的收听时长
+    pub fn is_dirty(&self) -> bool {
+        !self.unsaved.is_zero()
+    }
+}
+
+/// 累计时长格式化为 `X 小时 Y 分钟`（不足一小时只显示分钟）
+pub fn format_listening_time(secs: f64) -> String {
+    let minutes = (secs / 60.0) as u64;
+    let (h, m) = (minutes / 60, minutes % 60);
+    if h > 0 {
+        format!("{} 小时 {} 分钟", h, m)
+    } else {
+        format!("{} 分钟", m)
+    }
+}
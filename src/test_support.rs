@@ -0,0 +1,85 @@
+//! 测试共用的环境：把 HOME 和 XDG 目录指向进程内唯一的临时目录，测试不会读写真实的配置、收藏和历史。
+
+use crate::app::App;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, Once};
+
+static ISOLATE: Once = Once::new();
+
+/// 修改环境变量的测试持有这把锁，创建 App 时也会短暂持有，避免读到修改了一半的环境
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+fn base_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("maboroshi-test-{}", std::process::id()))
+}
+
+/// 第一次调用时把 HOME / XDG_CONFIG_HOME / XDG_DATA_HOME 指向临时目录
+pub fn isolate_dirs() {
+    ISOLATE.call_once(|| {
+        let base = base_dir();
+        std::env::set_var("HOME", base.join("home"));
+        std::env::set_var("XDG_CONFIG_HOME", base.join("config"));
+        std::env::set_var("XDG_DATA_HOME", base.join("data"));
+    });
+}
+
+/// 取得环境变量锁；其他测试断言失败导致锁中毒时照常使用
+pub fn env_lock() -> MutexGuard<'static, ()> {
+    isolate_dirs();
+    ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 临时修改环境变量：持有环境变量锁，drop 时恢复原值，断言失败时也会恢复
+pub struct EnvVars {
+    saved: Vec<(&'static str, Option<OsString>)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl EnvVars {
+    pub fn new() -> Self {
+        Self {
+            saved: Vec::new(),
+            _lock: env_lock(),
+        }
+    }
+
+    /// 设置（`Some`）或删除（`None`）环境变量
+    pub fn set(&mut self, key: &'static str, value: Option<&Path>) -> &mut Self {
+        if !self.saved.iter().any(|(k, _)| *k == key) {
+            self.saved.push((key, std::env::var_os(key)));
+        }
+        match value {
+            Some(value) => std::env::set_var(key, value),
+            None => std::env::remove_var(key),
+        }
+        self
+    }
+}
+
+impl Drop for EnvVars {
+    fn drop(&mut self) {
+        for (key, value) in self.saved.drain(..).rev() {
+            match value {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
+    }
+}
+
+/// 名为 `name` 的空临时目录，已存在时先清空
+pub fn temp_dir(name: &str) -> PathBuf {
+    isolate_dirs();
+    let dir = base_dir().join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("创建临时目录失败");
+    dir
+}
+
+/// 收藏保存在 `temp_dir(name)` 下的 App
+pub fn test_app(name: &str) -> App {
+    let favorites = temp_dir(name).join("favorites.json");
+    let _guard = env_lock();
+    App::new(&favorites.to_string_lossy())
+}
@@ -1,14 +1,54 @@
 mod theme;
 mod widgets;
 
+pub use theme::{format_duration, Theme};
+
 use crate::app::App;
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::Paragraph,
     Frame,
 };
 
-pub fn render(app: &mut App, frame: &mut Frame) {
-    let has_error = matches!(app.status, crate::app::PlayerStatus::Error(_));
+/// 歌词面板高度（含边框）
+const LYRICS_PANEL_HEIGHT: u16 = 7;
+
+/// 能正常渲染各个面板的最小终端尺寸：左侧分组栏 26 列 + 右侧列表，
+/// 高度至少容纳标题、帮助栏和几行列表
+const MIN_WIDTH: u16 = 60;
+const MIN_HEIGHT: u16 = 12;
+
+/// “正在播放”视图中显示封面所需的最少空白行数
+const MIN_COVER_HEIGHT: u16 = 4;
+
+pub fn render(app: &mut App, frame: &mut Frame, theme: &Theme) {
+    app.cover_area = None;
+    let size = frame.size();
+    if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+        widgets::render_too_small(frame, MIN_WIDTH, MIN_HEIGHT, theme);
+        return;
+    }
+
+    // 全屏“正在播放”视图：隐藏列表和日志，只保留浮层
+    if app.now_playing_mode {
+        let art_area = widgets::render_now_playing(app, frame, frame.size(), theme);
+        // 留出一行与状态行隔开；空间太小时不显示封面
+        if app.cover_art.is_some() && art_area.height > MIN_COVER_HEIGHT {
+            app.cover_area = Some(Rect {
+                height: art_area.height - 1,
+                ..art_area
+            });
+        }
+        widgets::render_help_overlay(app, frame, theme);
+        widgets::render_quit_confirm_overlay(app, frame, theme);
+        return;
+    }
+
+    let has_error = app.status.is_error();
+    // 错误详情是按 i 主动展开的，不受日志面板开关影响
+    let show_logs = has_error && app.show_logs;
 
     // 整体：左右分栏
     let main_chunks = Layout::default()
@@ -22,7 +62,14 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     let left_chunk = main_chunks[0];
 
     // 右侧面板：垂直分布 (Header区域, 歌曲/搜索列表区域, 错误日志区域, 底部Help)
-    let right_constraints = if has_error {
+    let right_constraints = if has_error && app.error_detail_mode {
+        vec![
+            Constraint::Length(4),
+            Constraint::Percentage(40),
+            Constraint::Percentage(60), // 错误详情
+            Constraint::Length(3),
+        ]
+    } else if show_logs {
         vec![
             Constraint::Length(4),      // Header (Title + Gauge)
             Constraint::Percentage(70), // List
@@ -44,20 +91,84 @@ pub fn render(app: &mut App, frame: &mut Frame) {
         .split(main_chunks[1]);
 
     // 调用 widgets 渲染
-    widgets::render_status_and_gauge(app, frame, right_chunks[0]);
+    widgets::render_status_and_gauge(app, frame, right_chunks[0], theme);
 
     // 左侧渲染分组，右侧渲染歌曲列表
-    widgets::render_groups(app, frame, left_chunk);
-    widgets::render_items(app, frame, right_chunks[1]);
+    widgets::render_groups(app, frame, left_chunk, theme);
+    if app.screensaver_active() {
+        render_screensaver(app, frame, right_chunks[1], theme);
+    } else if crate::lyrics::ENABLED {
+        // 启用歌词功能时，列表下方留出歌词面板
+        let list_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(LYRICS_PANEL_HEIGHT)])
+            .split(right_chunks[1]);
+        widgets::render_items(app, frame, list_chunks[0], theme);
+        widgets::render_lyrics(app, frame, list_chunks[1], theme);
+    } else {
+        widgets::render_items(app, frame, right_chunks[1], theme);
+    }
 
-    if has_error {
-        widgets::render_logs(app, frame, right_chunks[2]);
+    if has_error && app.error_detail_mode {
+        widgets::render_error_detail(app, frame, right_chunks[2], theme);
+    } else if show_logs {
+        widgets::render_logs(app, frame, right_chunks[2], theme);
     }
-    widgets::render_help(app, frame, right_chunks[3]);
+    widgets::render_help(app, frame, right_chunks[3], theme);
+    widgets::render_search_suggestions(app, frame, right_chunks[3], theme);
 
     // 移动模式浮层最后渲染，覆盖在所有内容之上
-    widgets::render_move_overlay(app, frame);
+    widgets::render_move_overlay(app, frame, theme);
+    widgets::render_stats_overlay(app, frame, theme);
 
     // 快捷键帮助浮层（最高优先级覆盖）
-    widgets::render_help_overlay(app, frame);
+    widgets::render_help_overlay(app, frame, theme);
+
+    widgets::render_quit_confirm_overlay(app, frame, theme);
+}
+
+/// 空闲屏保：两条相位不同的正弦波随 tick 横向流动。
+/// 只在空闲（`Waiting`）时渲染，帧数来自动画 tick，不额外占用 CPU
+fn render_screensaver(app: &App, frame: &mut Frame, area: Rect, theme: &Theme) {
+    let block = theme::default_block()
+        .title(" 💤 空闲中 · 按任意键返回 ")
+        .border_style(Style::default().fg(theme.inactive));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let (width, height) = (inner.width as usize, inner.height as usize);
+    if width == 0 || height == 0 {
+        return;
+    }
+    let phase = app.animation_tick as f64 * 0.25;
+    let amplitude = (height as f64 - 1.0) / 2.0;
+    // 每列两条波各占一个格子，后画的波覆盖先画的
+    let mut grid = vec![vec![(' ', theme.inactive); width]; height];
+    let waves = [
+        (0.18, 1.0, '·', theme.secondary),
+        (0.11, -0.6, '•', theme.primary),
+    ];
+    for (frequency, speed, glyph, color) in waves {
+        let rows = (0..width).map(|x| {
+            let wave = (x as f64 * frequency + phase * speed).sin();
+            ((amplitude * (1.0 + wave * 0.8)).round() as usize).min(height - 1)
+        });
+        for (x, row) in rows.enumerate() {
+            grid[row][x] = (glyph, color);
+        }
+    }
+
+    let lines: Vec<Line> = grid
+        .into_iter()
+        .map(|row| {
+            Line::from(
+                row.into_iter()
+                    .map(|(glyph, color)| {
+                        Span::styled(glyph.to_string(), Style::default().fg(color))
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
 }
@@ -1,8 +1,9 @@
+use crate::config::ThemeConfig;
 use ratatui::{
     style::{Color, Modifier, Style},
     widgets::ListState,
 };
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
 
 // ── 颜色主题 ──────────────────────────────────────────────────────────────────
 
@@ -13,16 +14,224 @@ pub const COLOR_BG_HIGHLIGHT: Color = Color::Rgb(35, 35, 55);
 pub const COLOR_WARNING: Color = Color::Rgb(255, 190, 90);
 pub const COLOR_INACTIVE: Color = Color::Rgb(100, 100, 100);
 
+/// 有默认颜色的搜索源，顺序与各预设中 `sources` 的颜色一一对应
+const KNOWN_SOURCES: [&str; 4] = ["yt", "bili", "sc", "local"];
+
+/// 运行时配色，由 `[theme]` 配置解析而来，渲染时传入各个 widget。
+#[derive(Clone, Debug)]
+pub struct Theme {
+    /// 主强调色：边框、帮助栏、搜索中
+    pub primary: Color,
+    /// 次强调色：列表边框、播放中进度条
+    pub secondary: Color,
+    /// 成功 / 正在播放的条目
+    pub success: Color,
+    /// 选中项背景
+    pub selected_bg: Color,
+    pub warning: Color,
+    pub inactive: Color,
+    /// 来源标记的颜色，键为 [`source_key`] 归一化后的源名
+    pub sources: HashMap<String, Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::neon()
+    }
+}
+
+impl Theme {
+    pub fn neon() -> Self {
+        Self {
+            primary: COLOR_NEON_CYAN,
+            secondary: COLOR_NEON_PINK,
+            success: COLOR_NEON_GREEN,
+            selected_bg: COLOR_BG_HIGHLIGHT,
+            warning: COLOR_WARNING,
+            inactive: COLOR_INACTIVE,
+            sources: source_colors([
+                Color::Rgb(255, 70, 70),
+                Color::Rgb(0, 175, 240),
+                Color::Rgb(255, 140, 0),
+                COLOR_NEON_GREEN,
+            ]),
+        }
+    }
+
+    pub fn mono() -> Self {
+        Self {
+            primary: Color::Rgb(220, 220, 220),
+            secondary: Color::Rgb(170, 170, 170),
+            success: Color::White,
+            selected_bg: Color::Rgb(60, 60, 60),
+            warning: Color::Rgb(200, 200, 200),
+            inactive: COLOR_INACTIVE,
+            sources: source_colors([
+                Color::White,
+                Color::Rgb(200, 200, 200),
+                Color::Rgb(160, 160, 160),
+                Color::Rgb(130, 130, 130),
+            ]),
+        }
+    }
+
+    pub fn solarized() -> Self {
+        Self {
+            primary: Color::Rgb(0x2a, 0xa1, 0x98),
+            secondary: Color::Rgb(0xd3, 0x36, 0x82),
+            success: Color::Rgb(0x85, 0x99, 0x00),
+            selected_bg: Color::Rgb(0x07, 0x36, 0x42),
+            warning: Color::Rgb(0xb5, 0x89, 0x00),
+            inactive: Color::Rgb(0x58, 0x6e, 0x75),
+            sources: source_colors([
+                Color::Rgb(0xdc, 0x32, 0x2f),
+                Color::Rgb(0x26, 0x8b, 0xd2),
+                Color::Rgb(0xcb, 0x4b, 0x16),
+                Color::Rgb(0x85, 0x99, 0x00),
+            ]),
+        }
+    }
+
+    /// 从配置构建主题：先取预设，再用单独配置的颜色逐项覆盖。
+    /// 无效的预设名或十六进制颜色会产生一条警告，对应项回退为预设值。
+    pub fn from_config(config: &ThemeConfig) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut theme = match config.preset.trim().to_lowercase().as_str() {
+            "" | "neon" => Self::neon(),
+            "mono" => Self::mono(),
+            "solarized" => Self::solarized(),
+            other => {
+                warnings.push(format!("未知的主题预设: {}，已回退为 neon", other));
+                Self::neon()
+            }
+        };
+
+        let overrides: [(&str, &Option<String>, &mut Color); 6] = [
+            ("primary", &config.primary, &mut theme.primary),
+            ("secondary", &config.secondary, &mut theme.secondary),
+            ("success", &config.success, &mut theme.success),
+            ("selected_bg", &config.selected_bg, &mut theme.selected_bg),
+            ("warning", &config.warning, &mut theme.warning),
+            ("inactive", &config.inactive, &mut theme.inactive),
+        ];
+        for (name, value, slot) in overrides {
+            let Some(hex) = value.as_deref() else {
+                continue;
+            };
+            match parse_hex_color(hex) {
+                Some(color) => *slot = color,
+                None => warnings.push(format!(
+                    "主题颜色 {} = \"{}\" 无效（应为 #RRGGBB），已使用默认值",
+                    name, hex
+                )),
+            }
+        }
+
+        for (source, hex) in &config.sources {
+            match parse_hex_color(hex) {
+                Some(color) => {
+                    theme.sources.insert(source_key(source).to_string(), color);
+                }
+                None => warnings.push(format!(
+                    "来源颜色 sources.{} = \"{}\" 无效（应为 #RRGGBB），已使用默认值",
+                    source, hex
+                )),
+            }
+        }
+
+        (theme, warnings)
+    }
+
+    /// 来源标记的颜色；没有配置颜色的源使用中性灰
+    pub fn source_color(&self, source: &str) -> Color {
+        self.sources
+            .get(source_key(source))
+            .copied()
+            .unwrap_or(Color::Gray)
+    }
+
+    /// 来源标记（如 `[BILI]`）的样式
+    pub fn source_style(&self, source: &str) -> Style {
+        Style::default().fg(self.source_color(source))
+    }
+
+    /// 选中项的统一高亮样式
+    pub fn selected_style(&self) -> Style {
+        Style::default()
+            .fg(Color::White)
+            .bg(self.selected_bg)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    pub fn style_for_log_line(&self, line: &str) -> Style {
+        if line.contains("失败") || line.contains("错误") || line.contains('❌') {
+            Style::default().fg(Color::Red)
+        } else if line.contains("警告") || line.contains("超时") {
+            Style::default().fg(self.warning)
+        } else if line.contains('✓') || line.contains("成功") || line.contains("就绪") {
+            Style::default().fg(self.success)
+        } else {
+            Style::default().fg(Color::Gray)
+        }
+    }
+}
+
+/// 按 [`KNOWN_SOURCES`] 的顺序组装预设的来源颜色
+fn source_colors(colors: [Color; 4]) -> HashMap<String, Color> {
+    KNOWN_SOURCES
+        .iter()
+        .map(|s| s.to_string())
+        .zip(colors)
+        .collect()
+}
+
+/// 归一化源名，使同一平台的别名共用一种颜色（`youtube` → `yt`、`bilibili` → `bili`、
+/// `soundcloud` → `sc`）；其余源名按小写原样使用
+fn source_key(source: &str) -> &str {
+    let source = source.trim();
+    let source = source.strip_suffix("search").unwrap_or(source);
+    match source.to_lowercase().as_str() {
+        "yt" | "youtube" => "yt",
+        "bili" | "bilibili" => "bili",
+        "sc" | "soundcloud" => "sc",
+        "local" => "local",
+        _ => source,
+    }
+}
+
+/// 解析 `#RRGGBB` / `RRGGBB` 形式的颜色
+fn parse_hex_color(hex: &str) -> Option<Color> {
+    let hex = hex.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+    Some(Color::Rgb(r, g, b))
+}
+
 // ── 通用辅助函数 ──────────────────────────────────────────────────────────────
 
-pub fn spinner_frame() -> &'static str {
+/// 加载中的盲文 spinner。`tick` 为 [`App::animation_tick`](crate::app::App::animation_tick)，
+/// 每个刷新周期加一，不受系统时钟调整影响
+pub fn spinner_frame(tick: u64) -> &'static str {
     const FRAMES: [&str; 8] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧"];
-    let tick = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis()
-        / 120;
-    FRAMES[(tick as usize) % FRAMES.len()]
+    FRAMES[(tick % FRAMES.len() as u64) as usize]
+}
+
+/// 缓冲中使用的旋转指示，与搜索用的盲文 spinner 区分开
+pub fn buffering_frame(tick: u64) -> &'static str {
+    const FRAMES: [&str; 4] = ["◐", "◓", "◑", "◒"];
+    FRAMES[(tick % FRAMES.len() as u64) as usize]
+}
+
+/// 音量迷你条：每格 10%，超过 100% 的部分不再加长
+pub fn volume_bar(volume: u8) -> String {
+    const CELLS: usize = 10;
+    let filled = (volume as usize).div_ceil(10).min(CELLS);
+    format!("{}{}", "▰".repeat(filled), "▱".repeat(CELLS - filled))
 }
 
 pub fn truncate_text(text: &str, max_chars: usize) -> String {
@@ -36,24 +245,30 @@ pub fn truncate_text(text: &str, max_chars: usize) -> String {
     format!("{}…", head)
 }
 
-pub fn style_for_log_line(line: &str) -> Style {
-    if line.contains("失败") || line.contains("错误") || line.contains('❌') {
-        Style::default().fg(Color::Red)
-    } else if line.contains("警告") || line.contains("超时") {
-        Style::default().fg(COLOR_WARNING)
-    } else if line.contains('✓') || line.contains("成功") || line.contains("就绪") {
-        Style::default().fg(COLOR_NEON_GREEN)
+/// 秒数格式化为 `m:ss` 或 `h:mm:ss`
+pub fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
     } else {
-        Style::default().fg(Color::Gray)
+        format!("{}:{:02}", m, s)
     }
 }
 
-/// 选中项的统一高亮样式
-pub fn selected_style() -> Style {
-    Style::default()
-        .fg(Color::White)
-        .bg(COLOR_BG_HIGHLIGHT)
-        .add_modifier(Modifier::BOLD)
+/// 大数字的紧凑表示，例如 1234 -> 1.2K，1_234_567 -> 1.2M。
+/// 按四舍五入后的值选择单位，999_950 显示为 1.0M 而不是 1000.0K
+pub fn format_count(n: u64) -> String {
+    if n < 1_000 {
+        return n.to_string();
+    }
+    let mut value = n as f64;
+    for unit in ["K", "M"] {
+        value /= 1_000.0;
+        if (value * 10.0).round() < 10_000.0 {
+            return format!("{:.1}{}", value, unit);
+        }
+    }
+    format!("{:.1}B", value / 1_000.0)
 }
 
 /// 构建已选中指定索引的 ListState
@@ -69,3 +284,20 @@ pub fn default_block<'a>() -> ratatui::widgets::Block<'a> {
         .borders(ratatui::widgets::Borders::ALL)
         .border_type(ratatui::widgets::BorderType::Rounded)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::format_count;
+
+    #[test]
+    fn format_count_picks_unit_after_rounding() {
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1_234), "1.2K");
+        assert_eq!(format_count(999_949), "999.9K");
+        assert_eq!(format_count(999_950), "1.0M");
+        assert_eq!(format_count(999_999), "1.0M");
+        assert_eq!(format_count(1_234_567), "1.2M");
+        assert_eq!(format_count(999_950_000), "1.0B");
+        assert_eq!(format_count(2_500_000_000), "2.5B");
+    }
+}
@@ -1,17 +1,20 @@
-use crate::app::{App, PlayerStatus};
+use crate::app::{normalize_title, App, FavoriteSort, PlayerStatus};
+use crate::config::EqPreset;
+use crate::lyrics::{self, LyricsState};
+use crate::net::LoadStage;
 use crate::ui::theme::{
-    self, selected_style, spinner_frame, style_for_log_line, truncate_text, COLOR_NEON_CYAN,
-    COLOR_NEON_PINK,
+    self, buffering_frame, format_count, format_duration, spinner_frame, truncate_text, volume_bar,
+    Theme,
 };
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Clear, Gauge, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
-pub fn render_status_and_gauge(app: &App, frame: &mut Frame, area: Rect) {
+pub fn render_status_and_gauge(app: &App, frame: &mut Frame, area: Rect, theme: &Theme) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -22,44 +25,90 @@ pub fn render_status_and_gauge(app: &App, frame: &mut Frame, area: Rect) {
         .split(area);
 
     let gauge_color = match app.status {
-        PlayerStatus::Playing => theme::COLOR_NEON_PINK,
-        PlayerStatus::Paused => theme::COLOR_WARNING,
-        PlayerStatus::Searching => theme::COLOR_NEON_CYAN,
-        PlayerStatus::SearchResults => theme::COLOR_NEON_GREEN,
-        PlayerStatus::Error(_) => Color::Red,
-        PlayerStatus::Waiting => theme::COLOR_INACTIVE,
+        PlayerStatus::Playing => theme.secondary,
+        PlayerStatus::Paused => theme.warning,
+        PlayerStatus::Searching => theme.primary,
+        PlayerStatus::SearchResults => theme.success,
+        PlayerStatus::SearchError(_) => theme.warning,
+        PlayerStatus::PlaybackError(_) => Color::Red,
+        PlayerStatus::Unavailable(_) => theme.warning,
+        PlayerStatus::Waiting => theme.inactive,
     };
 
     // --- Header Text ---
-    let title_prefix = format!(
-        "🌀 Maboroshi | {} [{}] ",
-        app.get_play_mode_text(),
-        app.current_source.to_uppercase()
-    );
+    let title_prefix = format!("🌀 Maboroshi | {} ", app.get_play_mode_text());
+    let source_badge = format!("[{}]", app.current_source.to_uppercase());
+    let group_label = format!(" 「{}」 ", app.active_group().name);
 
     let status_text = match &app.status {
-        PlayerStatus::Waiting => "等待播放".to_string(),
-        PlayerStatus::Searching => format!("{} 正在搜索...", spinner_frame()),
+        PlayerStatus::Waiting => match app.current_tip() {
+            Some(tip) => format!("等待播放 · 💡 {}", tip),
+            None => "等待播放".to_string(),
+        },
+        PlayerStatus::Searching if !app.search_results.is_empty() => format!(
+            "{} 正在搜索... 已找到 {} 首",
+            spinner_frame(app.animation_tick),
+            app.search_results.len()
+        ),
+        PlayerStatus::Searching => format!("{} 正在搜索...", spinner_frame(app.animation_tick)),
         PlayerStatus::SearchResults => format!("🎯 找到 {} 首", app.search_results.len()),
         PlayerStatus::Playing => format!("▶ 正在播放: {}", app.current_song),
         PlayerStatus::Paused => format!("⏸ 暂停: {}", app.current_song),
-        PlayerStatus::Error(e) => format!("❌ {}", e),
+        PlayerStatus::SearchError(e) => format!("🔍 搜索失败: {}", e),
+        PlayerStatus::PlaybackError(e) => format!("❌ 播放失败: {}", e),
+        PlayerStatus::Unavailable(e) => format!("⛔ {}，跳到下一首", e),
+    };
+
+    let status_text = match (load_stage_text(app), app.buffering, &app.status) {
+        (Some(stage), _, _) => format!("{}: {}", stage, app.current_song),
+        (None, Some(pct), PlayerStatus::Playing) => {
+            format!(
+                "{} {}  ⟳ 缓冲中 {}%",
+                buffering_frame(app.animation_tick),
+                status_text,
+                pct
+            )
+        }
+        _ => status_text,
     };
 
     let favorite_indicator = if app.is_favorite() { " ⭐" } else { "" };
-    let vol_text = format!(" [VOL:{}%]", app.volume);
+    let playing = matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused);
+    let quality = match quality_badge(app) {
+        Some(badge) if playing => format!(" {}", badge),
+        _ => String::new(),
+    };
+    let chapter = match chapter_badge(app) {
+        Some(badge) if playing => format!(" {}", badge),
+        _ => String::new(),
+    };
+    let eq = if app.eq_preset == EqPreset::Flat {
+        String::new()
+    } else {
+        format!(" [EQ {}]", app.eq_preset.label())
+    };
+    let vol_text = if app.muted {
+        format!(" [🔇 MUTED {}%]", app.pre_mute_volume)
+    } else {
+        format!(" [VOL {} {}%]", volume_bar(app.volume), app.volume)
+    };
 
     let full_status = format!(
-        "{}{}{}{}",
-        title_prefix, status_text, favorite_indicator, vol_text
+        "{}{}{}{}{}{}{}",
+        group_label, status_text, chapter, quality, favorite_indicator, eq, vol_text
     );
 
-    let header_line = Paragraph::new(Span::styled(
-        full_status,
-        Style::default()
-            .fg(Color::White)
-            .add_modifier(Modifier::BOLD),
-    ));
+    let header_style = Style::default()
+        .fg(Color::White)
+        .add_modifier(Modifier::BOLD);
+    let header_line = Paragraph::new(Line::from(vec![
+        Span::styled(title_prefix, header_style),
+        Span::styled(
+            source_badge,
+            header_style.fg(theme.source_color(&app.current_source)),
+        ),
+        Span::styled(full_status, header_style),
+    ]));
 
     // --- Progress Gauge ---
     let progress_label = if matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused) {
@@ -86,14 +135,313 @@ pub fn render_status_and_gauge(app: &App, frame: &mut Frame, area: Rect) {
     // --- Container Block ---
     let block = theme::default_block()
         .title(" 控制台 ")
-        .border_style(Style::default().fg(gauge_color.clone()));
+        .border_style(Style::default().fg(gauge_color));
 
     frame.render_widget(block, area);
-    frame.render_widget(header_line, chunks[0]);
-    frame.render_widget(gauge, chunks[1]);
+    match level_meter_text(app) {
+        Some(meter) => {
+            let gauge_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Min(0),
+                    Constraint::Length(meter.chars().count() as u16 + 1),
+                ])
+                .split(chunks[1]);
+            frame.render_widget(gauge, gauge_chunks[0]);
+            frame.render_widget(
+                Paragraph::new(Span::styled(meter, Style::default().fg(theme.success)))
+                    .alignment(Alignment::Right),
+                gauge_chunks[1],
+            );
+        }
+        None => frame.render_widget(gauge, chunks[1]),
+    }
+
+    // 时钟固定在标题行右侧；终端较窄时由左侧的标题和状态先被截断
+    if app.show_clock {
+        let clock = chrono::Local::now().format("%H:%M").to_string();
+        let header_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(clock.len() as u16 + 1),
+            ])
+            .split(chunks[0]);
+        frame.render_widget(header_line, header_chunks[0]);
+        frame.render_widget(
+            Paragraph::new(Span::styled(clock, Style::default().fg(theme.primary)))
+                .alignment(Alignment::Right),
+            header_chunks[1],
+        );
+    } else {
+        frame.render_widget(header_line, chunks[0]);
+    }
+}
+
+/// 歌曲加载中的分阶段指示，例如 `⠙ [2/3] 连接中`；缓冲阶段附带 mpv 回报的缓冲百分比。
+/// 没有在加载歌曲时返回 None。
+fn load_stage_text(app: &App) -> Option<String> {
+    let stage = app.load_stage?;
+    let frame = match (&app.status, stage) {
+        (PlayerStatus::Searching, _) => spinner_frame(app.animation_tick),
+        (PlayerStatus::Playing, LoadStage::Buffering) => buffering_frame(app.animation_tick),
+        _ => return None,
+    };
+    let mut text = format!(
+        "{} [{}/{}] {}",
+        frame,
+        stage.step(),
+        LoadStage::COUNT,
+        stage.label()
+    );
+    if let (LoadStage::Buffering, Some(pct)) = (stage, app.buffering) {
+        text.push_str(&format!(" {}%", pct));
+    }
+    Some(text)
+}
+
+/// 音频编码 / 码率标记，例如 `[opus 160k]`；mpv 两者都尚未上报时返回 None
+fn quality_badge(app: &App) -> Option<String> {
+    let bitrate = app
+        .audio_bitrate
+        .map(|bps| format!("{}k", (bps + 500) / 1000));
+    let parts: Vec<String> = app.audio_codec.iter().cloned().chain(bitrate).collect();
+    (!parts.is_empty()).then(|| format!("[{}]", parts.join(" ")))
+}
+
+/// 电平表的格数
+const LEVEL_METER_CELLS: usize = 8;
+
+/// 电平表显示的响度范围（LUFS）：低于下限为空，达到上限为满格
+const LEVEL_METER_FLOOR: f64 = -50.0;
+const LEVEL_METER_CEIL: f64 = -5.0;
+
+/// 进度条右侧的电平表，例如 `♪▇▇▇▇▁▁▁▁`。mpv 没有提供响度数据时按动画 tick 跳动，
+/// 暂停时归零；未启用 `ui.level_meter` 或没有在播放时返回 None
+fn level_meter_text(app: &App) -> Option<String> {
+    if !app.level_meter || !matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused) {
+        return None;
+    }
+    let level = match (&app.status, app.loudness) {
+        (PlayerStatus::Paused, _) => 0.0,
+        (_, Some(lufs)) => (lufs - LEVEL_METER_FLOOR) / (LEVEL_METER_CEIL - LEVEL_METER_FLOOR),
+        (_, None) => {
+            let t = app.animation_tick as f64;
+            0.45 + 0.25 * (t * 0.9).sin() + 0.1 * (t * 2.3).sin()
+        }
+    };
+    let filled = (level.clamp(0.0, 1.0) * LEVEL_METER_CELLS as f64).round() as usize;
+    Some(format!(
+        "♪{}{}",
+        "▇".repeat(filled),
+        "▁".repeat(LEVEL_METER_CELLS - filled)
+    ))
+}
+
+/// 当前章节标记，例如 `§ 3/12 Intro`；没有章节或还在第一章之前时返回 None
+fn chapter_badge(app: &App) -> Option<String> {
+    let index = app.chapter?;
+    let chapter = app.chapters.get(index)?;
+    Some(format!(
+        "§ {}/{} {}",
+        index + 1,
+        app.chapters.len(),
+        truncate_text(&chapter.label(index), 30)
+    ))
 }
 
-pub fn render_groups(app: &mut App, frame: &mut Frame, area: Rect) {
+/// 终端尺寸不足时只显示一条提示，避免各面板的布局被挤成 0 高度
+pub fn render_too_small(frame: &mut Frame, min_width: u16, min_height: u16, theme: &Theme) {
+    let size = frame.size();
+    let text = vec![
+        Line::from(Span::styled(
+            "终端窗口太小",
+            Style::default()
+                .fg(theme.warning)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!("当前 {}×{}", size.width, size.height)),
+        Line::from(format!("至少需要 {}×{}", min_width, min_height)),
+    ];
+    let y = size.height.saturating_sub(text.len() as u16) / 2;
+    let area = Rect::new(
+        size.x,
+        size.y + y,
+        size.width,
+        size.height.saturating_sub(y),
+    );
+    frame.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        area,
+    );
+}
+
+/// 全屏“正在播放”视图：标题、进度与时间、音量、播放模式和收藏状态居中显示
+/// 渲染全屏“正在播放”视图，返回状态行上方的空白区域（用于显示封面）
+pub fn render_now_playing(app: &App, frame: &mut Frame, area: Rect, theme: &Theme) -> Rect {
+    let playing = matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused);
+    let accent = match app.status {
+        PlayerStatus::Paused => theme.warning,
+        _ => theme.secondary,
+    };
+
+    let block = theme::default_block()
+        .title(" 🌀 正在播放 ")
+        .border_style(Style::default().fg(accent));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    // 内容宽度随终端变化，窄屏时占满
+    let width = (inner.width * 7 / 10).max(inner.width.min(40));
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(width),
+            Constraint::Min(0),
+        ])
+        .split(inner);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(1), // 状态
+            Constraint::Length(1),
+            Constraint::Length(3), // 标题（最多换行三行）
+            Constraint::Length(1),
+            Constraint::Length(1), // 进度条
+            Constraint::Length(1), // 时间
+            Constraint::Length(1),
+            Constraint::Length(1), // 音量 / 模式 / 收藏
+            Constraint::Min(0),
+            Constraint::Length(1), // 按键提示
+        ])
+        .split(columns[1]);
+
+    let status = match &app.status {
+        PlayerStatus::Playing => "▶ 播放中".to_string(),
+        PlayerStatus::Paused => "⏸ 已暂停".to_string(),
+        PlayerStatus::Searching => format!("{} 正在搜索...", spinner_frame(app.animation_tick)),
+        PlayerStatus::SearchError(e) => format!("🔍 搜索失败: {}", e),
+        PlayerStatus::PlaybackError(e) => format!("❌ 播放失败: {}", e),
+        PlayerStatus::Unavailable(e) => format!("⛔ {}，跳到下一首", e),
+        _ => "等待播放".to_string(),
+    };
+    let status = match (load_stage_text(app), app.buffering, &app.status) {
+        (Some(stage), _, _) => stage,
+        (None, Some(pct), PlayerStatus::Playing) => {
+            format!(
+                "{} {}  ⟳ 缓冲中 {}%",
+                buffering_frame(app.animation_tick),
+                status,
+                pct
+            )
+        }
+        _ => status,
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(status, Style::default().fg(accent)))
+            .alignment(Alignment::Center),
+        rows[1],
+    );
+
+    let title = if app.current_song.is_empty() {
+        "—".to_string()
+    } else {
+        app.current_song.clone()
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            title,
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true }),
+        rows[3],
+    );
+
+    let progress = if playing && app.progress.is_finite() {
+        app.progress.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    frame.render_widget(
+        Gauge::default()
+            .gauge_style(Style::default().fg(accent))
+            .ratio(progress)
+            .label(""),
+        rows[5],
+    );
+
+    let time_text = if !playing {
+        String::new()
+    } else if app.duration > 0.0 {
+        format!(
+            "{} / {}",
+            format_duration(app.time_pos as u64),
+            format_duration(app.duration as u64)
+        )
+    } else {
+        format_duration(app.time_pos as u64)
+    };
+    let time_text = match quality_badge(app) {
+        Some(badge) if playing => format!("{}  {}", time_text, badge),
+        _ => time_text,
+    };
+    let time_text = match chapter_badge(app) {
+        Some(badge) if playing => format!("{}  {}", time_text, badge),
+        _ => time_text,
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(time_text, Style::default().fg(theme.inactive)))
+            .alignment(Alignment::Center),
+        rows[6],
+    );
+
+    let favorite = if app.is_favorite() {
+        "⭐ 已收藏"
+    } else {
+        "☆ 未收藏"
+    };
+    let volume_text = if app.muted {
+        format!("🔇 MUTED {}%", app.pre_mute_volume)
+    } else {
+        format!("🔊 {} {}%", volume_bar(app.volume), app.volume)
+    };
+    let info = format!(
+        "{}   {} {}   {}",
+        volume_text,
+        app.get_play_mode_text(),
+        app.get_play_mode_name(),
+        favorite
+    );
+    frame.render_widget(
+        Paragraph::new(Span::styled(info, Style::default().fg(theme.primary)))
+            .alignment(Alignment::Center),
+        rows[8],
+    );
+
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            format!(
+                "[n] 返回  [Space] 暂停/继续  [←→] ±{}s{}  [+/-] ±{}%  [x] 静音  [f] 收藏  [m] 模式",
+                app.seek_seconds.abs(),
+                if app.chapters.is_empty() { "" } else { "  [[]] 章节" },
+                app.volume_step.abs()
+            ),
+            Style::default().fg(Color::Gray),
+        ))
+        .alignment(Alignment::Center),
+        rows[10],
+    );
+    rows[0]
+}
+
+pub fn render_groups(app: &mut App, frame: &mut Frame, area: Rect, theme: &Theme) {
     let group_items: Vec<ListItem> = app
         .groups
         .iter()
@@ -102,9 +450,9 @@ pub fn render_groups(app: &mut App, frame: &mut Frame, area: Rect) {
             let is_selected = i == app.selected_group;
 
             let style = if is_selected {
-                selected_style()
+                theme.selected_style()
             } else {
-                Style::default().fg(theme::COLOR_INACTIVE)
+                Style::default().fg(theme.inactive)
             };
 
             let marker = if is_selected { "▶" } else { " " };
@@ -115,38 +463,103 @@ pub fn render_groups(app: &mut App, frame: &mut Frame, area: Rect) {
     let groups_list = List::new(group_items).block(
         theme::default_block()
             .title(" 🗂  分组 (Tab) ")
-            .border_style(Style::default().fg(theme::COLOR_NEON_CYAN)),
+            .border_style(Style::default().fg(theme.primary)),
     );
 
     let mut list_state = theme::make_list_state(app.selected_group);
     frame.render_stateful_widget(groups_list, area, &mut list_state);
 }
 
-pub fn render_items(app: &mut App, frame: &mut Frame, area: Rect) {
+pub fn render_items(app: &mut App, frame: &mut Frame, area: Rect, theme: &Theme) {
     let list_text_max = area.width.saturating_sub(6) as usize;
 
-    if !app.search_results.is_empty() {
+    if app.history_mode {
+        // --- 渲染播放历史 ---
+        let history_items: Vec<ListItem> = app
+            .play_history
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let is_playing = app.is_current_track(&item.title, &item.source)
+                    && matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused);
+                let is_selected = i == app.selected_history;
+
+                let style = if is_selected {
+                    theme.selected_style()
+                } else if is_playing {
+                    Style::default()
+                        .fg(theme.success)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+
+                let marker = if is_playing {
+                    "▶"
+                } else if is_selected {
+                    "›"
+                } else {
+                    " "
+                };
+                let fav_icon = if app.is_in_active_group(&item.title, &item.source) {
+                    " ♥"
+                } else {
+                    ""
+                };
+                let display_text = format!("{}. {}{}", i + 1, item.title, fav_icon);
+                let badge = format!(" [{}]", item.source);
+                let text_max = list_text_max.saturating_sub(badge.chars().count()).max(8);
+
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!(
+                        "{} {}",
+                        marker,
+                        truncate_text(&display_text, text_max)
+                    )),
+                    Span::styled(badge, theme.source_style(&item.source)),
+                ]))
+                .style(style)
+            })
+            .collect();
+
+        let history_list = List::new(history_items).block(
+            theme::default_block()
+                .title(format!(" 🕘 播放历史 ({}) ", app.play_history.len()))
+                .border_style(Style::default().fg(theme.secondary)),
+        );
+
+        let mut list_state = theme::make_list_state(app.selected_history);
+        frame.render_stateful_widget(history_list, area, &mut list_state);
+    } else if !app.search_results.is_empty() {
         // --- 渲染搜索结果 ---
+        // 结果来自多个源时，每行前加上来源标记
+        let mixed_sources = app
+            .search_results
+            .iter()
+            .any(|r| r.source != app.search_results[0].source);
+        // 已收藏（任意分组）的结果行标 ⭐；集合只构建一次，避免每行扫描全部收藏
+        let favorite_titles = app.favorite_title_set();
         let search_items: Vec<ListItem> = app
             .search_results
             .iter()
             .enumerate()
             .map(|(i, result)| {
                 let is_selected = i == app.selected_search_result;
-                let is_playing = result.title == app.current_song
+                let is_playing = app.is_current_track(&result.title, &result.source)
                     && matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused);
 
                 let style = if is_selected {
-                    selected_style()
+                    theme.selected_style()
                 } else if is_playing {
                     Style::default()
-                        .fg(theme::COLOR_NEON_GREEN)
+                        .fg(theme.success)
                         .add_modifier(Modifier::BOLD)
                 } else {
                     Style::default()
                 };
 
-                let is_fav = app.is_title_in_active_group(&result.title);
+                let is_fav = favorite_titles
+                    .contains(&(normalize_title(&result.title), result.source.clone()));
 
                 let marker = if is_playing {
                     "▶"
@@ -155,14 +568,40 @@ pub fn render_items(app: &mut App, frame: &mut Frame, area: Rect) {
                 } else {
                     " "
                 };
-                let fav_icon = if is_fav { " ♥" } else { "" };
+                let fav_icon = if is_fav { " ⭐" } else { "" };
                 let base = format!("{}. {}{}", i + 1, result.title, fav_icon);
 
-                ListItem::new(format!(
-                    "{} {}",
-                    marker,
-                    truncate_text(&base, list_text_max)
-                ))
+                // 元数据：时长 · 上传者 · 播放量，缺失的字段直接省略
+                let mut meta_parts = Vec::new();
+                if let Some(duration) = result.duration {
+                    meta_parts.push(format_duration(duration));
+                }
+                if let Some(uploader) = &result.uploader {
+                    meta_parts.push(truncate_text(uploader, 20));
+                }
+                if let Some(views) = result.view_count {
+                    meta_parts.push(format!("{} 次观看", format_count(views)));
+                }
+                let meta = if meta_parts.is_empty() {
+                    String::new()
+                } else {
+                    format!("  {}", meta_parts.join(" · "))
+                };
+                let badge = if mixed_sources {
+                    format!("[{}] ", result.source.to_uppercase())
+                } else {
+                    String::new()
+                };
+                let title_max = list_text_max
+                    .saturating_sub(meta.chars().count() + badge.chars().count())
+                    .max(8);
+
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{} ", marker)),
+                    Span::styled(badge, theme.source_style(&result.source)),
+                    Span::raw(truncate_text(&base, title_max)),
+                    Span::styled(meta, Style::default().fg(theme.inactive)),
+                ]))
                 .style(style)
             })
             .collect();
@@ -170,9 +609,15 @@ pub fn render_items(app: &mut App, frame: &mut Frame, area: Rect) {
         let title = if app.is_loading_page {
             format!(
                 " {} 加载中... - 第 {} 页 ",
-                spinner_frame(),
+                spinner_frame(app.animation_tick),
                 app.current_page + 1
             )
+        } else if let Some(preview) = &app.preview {
+            format!(
+                " 🎧 试听中: {} - 第 {} 页 ",
+                truncate_text(preview, 40),
+                app.current_page
+            )
         } else {
             format!(
                 " 🎯 搜索结果 ({}) - 第 {} 页 ",
@@ -184,36 +629,38 @@ pub fn render_items(app: &mut App, frame: &mut Frame, area: Rect) {
         let search_list = List::new(search_items).block(
             theme::default_block()
                 .title(title)
-                .border_style(Style::default().fg(theme::COLOR_NEON_PINK)),
+                .border_style(Style::default().fg(theme.secondary)),
         );
 
         let mut list_state = theme::make_list_state(app.selected_search_result);
         frame.render_stateful_widget(search_list, area, &mut list_state);
     } else {
-        // --- 渲染分组曲目 ---
+        // --- 渲染分组曲目（有过滤关键字时只显示匹配的歌曲）---
         let active_items = app.active_items();
-        let favorite_items: Vec<ListItem> = active_items
+        let visible = app.visible_favorite_indices();
+        let favorite_items: Vec<ListItem> = visible
             .iter()
-            .enumerate()
+            .map(|&i| (i, &active_items[i]))
             .map(|(i, item)| {
-                let is_playing = item.title == app.current_song
+                let is_playing = app.is_current_track(&item.title, &item.source)
                     && matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused);
                 let is_selected = i == app.selected_favorite;
 
                 let style = if is_selected {
-                    selected_style()
+                    theme.selected_style()
                 } else if is_playing {
                     Style::default()
-                        .fg(theme::COLOR_NEON_GREEN)
+                        .fg(theme.success)
                         .add_modifier(Modifier::BOLD)
                 } else {
                     Style::default()
                 };
 
-                let display_text = if item.source == "yt" {
-                    item.title.clone()
+                // 默认源 yt 不显示来源标记
+                let badge = if item.source == "yt" {
+                    String::new()
                 } else {
-                    format!("{} [{}]", item.title, item.source)
+                    format!(" [{}]", item.source)
                 };
 
                 let marker = if is_playing {
@@ -224,39 +671,84 @@ pub fn render_items(app: &mut App, frame: &mut Frame, area: Rect) {
                     "♥"
                 };
 
-                ListItem::new(format!(
-                    "{} {}",
-                    marker,
-                    truncate_text(&display_text, list_text_max)
-                ))
+                // 起始位置和播放次数靠右显示，标题按剩余宽度截断
+                let offset = item
+                    .start_offset_secs
+                    .map(|secs| format!("⏩{}", format_duration(secs)));
+                let plays = (item.play_count > 0).then(|| format!("×{}", item.play_count));
+                let count = offset
+                    .into_iter()
+                    .chain(plays)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let count_width = count.chars().count();
+                let badge_width = badge.chars().count();
+                let text = truncate_text(
+                    item.label(),
+                    list_text_max
+                        .saturating_sub(count_width + badge_width + 1)
+                        .max(8),
+                );
+                let padding = list_text_max
+                    .saturating_sub(text.chars().count() + badge_width + count_width)
+                    .max(1);
+
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{} {}", marker, text)),
+                    Span::styled(badge, theme.source_style(&item.source)),
+                    Span::raw(" ".repeat(padding)),
+                    Span::styled(count, Style::default().fg(theme.inactive)),
+                ]))
                 .style(style)
             })
             .collect();
 
         let group_name = app.active_group().name.clone();
+        let sort_label = if app.favorite_sort == FavoriteSort::Insertion {
+            String::new()
+        } else {
+            format!("⇅ {} ", app.favorite_sort.label())
+        };
+        let title = if app.favorite_filter.is_empty() {
+            format!(
+                " 🎵 {} ({}) {}",
+                group_name,
+                app.active_items().len(),
+                sort_label
+            )
+        } else {
+            format!(
+                " 🎵 {} 🔍 \"{}\" ({}/{}) {}",
+                group_name,
+                app.favorite_filter,
+                visible.len(),
+                app.active_items().len(),
+                sort_label
+            )
+        };
         let favorites_list = List::new(favorite_items).block(
             theme::default_block()
-                .title(format!(
-                    " 🎵 {} ({}) ",
-                    group_name,
-                    app.active_items().len()
-                ))
-                .border_style(Style::default().fg(theme::COLOR_NEON_PINK)),
+                .title(title)
+                .border_style(Style::default().fg(theme.secondary)),
         );
 
-        let mut list_state = theme::make_list_state(app.selected_favorite);
+        let selected_row = visible
+            .iter()
+            .position(|&i| i == app.selected_favorite)
+            .unwrap_or(0);
+        let mut list_state = theme::make_list_state(selected_row);
         frame.render_stateful_widget(favorites_list, area, &mut list_state);
     }
 }
 
-pub fn render_logs(app: &App, frame: &mut Frame, area: Rect) {
+pub fn render_logs(app: &App, frame: &mut Frame, area: Rect, theme: &Theme) {
     let log_height = area.height.saturating_sub(2) as usize;
     let log_start = app.logs.len().saturating_sub(log_height);
     let log_lines: Vec<Line> = app
         .logs
         .iter()
         .skip(log_start)
-        .map(|line| Span::styled(line.clone(), style_for_log_line(line)))
+        .map(|line| Span::styled(line.clone(), theme.style_for_log_line(line)))
         .map(Line::from)
         .collect();
 
@@ -264,12 +756,94 @@ pub fn render_logs(app: &App, frame: &mut Frame, area: Rect) {
         Block::default()
             .title("📋 日志")
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(COLOR_NEON_CYAN)),
+            .border_style(Style::default().fg(theme.primary)),
     );
     frame.render_widget(logs, area);
 }
 
-pub fn render_help(app: &App, frame: &mut Frame, area: Rect) {
+/// 同步歌词面板：当前行居中高亮，其余行按时间顺序上下展开
+pub fn render_lyrics(app: &App, frame: &mut Frame, area: Rect, theme: &Theme) {
+    let height = area.height.saturating_sub(2) as usize;
+    let playing = matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused);
+
+    let lines: Vec<Line> = match &app.lyrics {
+        LyricsState::Found(lyrics) if playing => {
+            let current = lyrics::current_line(lyrics, app.time_pos);
+            let start = current
+                .unwrap_or(0)
+                .saturating_sub(height / 2)
+                .min(lyrics.len().saturating_sub(height));
+            lyrics
+                .iter()
+                .enumerate()
+                .skip(start)
+                .take(height)
+                .map(|(i, line)| {
+                    let style = if Some(i) == current {
+                        Style::default()
+                            .fg(theme.success)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(theme.inactive)
+                    };
+                    Line::from(Span::styled(line.text.clone(), style))
+                })
+                .collect()
+        }
+        state => {
+            let message = match state {
+                _ if !playing => "未在播放",
+                LyricsState::Loading => "正在查找歌词...",
+                _ => "暂无歌词",
+            };
+            vec![Line::from(Span::styled(
+                message,
+                Style::default().fg(theme.inactive),
+            ))]
+        }
+    };
+
+    let panel = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Center)
+        .block(
+            theme::default_block()
+                .title(" 🎤 歌词 ")
+                .border_style(Style::default().fg(theme.secondary)),
+        );
+    frame.render_widget(panel, area);
+}
+
+/// 错误详情面板：显示最近一次 yt-dlp 报错的完整 stderr
+pub fn render_error_detail(app: &App, frame: &mut Frame, area: Rect, theme: &Theme) {
+    let message = app.status.error_message().unwrap_or("");
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("❌ {}", message),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    if let Some(detail) = &app.last_error_detail {
+        lines.extend(detail.lines().map(|line| {
+            Line::from(Span::styled(
+                line.to_string(),
+                theme.style_for_log_line(line),
+            ))
+        }));
+    }
+
+    let panel = Paragraph::new(Text::from(lines))
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title(" 🔍 错误详情（yt-dlp stderr，按 i 收起） ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red)),
+        );
+    frame.render_widget(panel, area);
+}
+
+pub fn render_help(app: &App, frame: &mut Frame, area: Rect, theme: &Theme) {
     let mut spans = Vec::new();
 
     // 辅助函数：生成形如 " [Key] Action " 的样式
@@ -299,6 +873,17 @@ pub fn render_help(app: &App, frame: &mut Frame, area: Rect) {
         add_bind(&mut spans, "y", "确认");
         add_bind(&mut spans, "Esc", "取消");
         Color::Red
+    } else if app.clear_favorites_confirm_mode {
+        spans.push(Span::styled(
+            format!(
+                " ⚠️  确认清空全部 {} 个分组的收藏？（会先备份收藏文件） ",
+                app.groups.len()
+            ),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+        add_bind(&mut spans, "y", "确认");
+        add_bind(&mut spans, "Esc", "取消");
+        Color::Red
     } else if app.rename_mode {
         spans.push(Span::styled(
             format!(" 重命名分组: {} ", app.input_buffer),
@@ -306,7 +891,53 @@ pub fn render_help(app: &App, frame: &mut Frame, area: Rect) {
         ));
         add_bind(&mut spans, "Enter", "确认");
         add_bind(&mut spans, "Esc", "取消");
-        theme::COLOR_NEON_CYAN
+        theme.primary
+    } else if app.display_name_mode {
+        spans.push(Span::styled(
+            format!(" 显示名称: {} ", app.input_buffer),
+            Style::default().fg(Color::Yellow),
+        ));
+        add_bind(&mut spans, "Enter", "确认（留空恢复原标题）");
+        add_bind(&mut spans, "Esc", "取消");
+        theme.primary
+    } else if app.exclude_input_mode {
+        spans.push(Span::styled(
+            format!(" 排除关键词: {} ", app.input_buffer),
+            Style::default().fg(Color::Yellow),
+        ));
+        add_bind(&mut spans, "Enter", "确认（逗号分隔，留空清除）");
+        add_bind(&mut spans, "Esc", "取消");
+        theme.primary
+    } else if app.volume_input_mode {
+        spans.push(Span::styled(
+            format!(" 设置音量: {} ", app.input_buffer),
+            Style::default().fg(Color::Yellow),
+        ));
+        let range = format!("确认 (0–{})", app.max_volume);
+        add_bind(&mut spans, "Enter", &range);
+        add_bind(&mut spans, "Esc", "取消");
+        theme.primary
+    } else if app.page_input_mode {
+        spans.push(Span::styled(
+            format!(" 跳转到第几页: {} ", app.input_buffer),
+            Style::default().fg(Color::Yellow),
+        ));
+        let range = if app.total_pages == usize::MAX {
+            "跳转".to_string()
+        } else {
+            format!("跳转 (1–{})", app.total_pages)
+        };
+        add_bind(&mut spans, "Enter", &range);
+        add_bind(&mut spans, "Esc", "取消");
+        theme.primary
+    } else if app.seek_input_mode {
+        spans.push(Span::styled(
+            format!(" 跳转到: {} ", app.input_buffer),
+            Style::default().fg(Color::Yellow),
+        ));
+        add_bind(&mut spans, "Enter", "跳转 (mm:ss / hh:mm:ss / 秒)");
+        add_bind(&mut spans, "Esc", "取消");
+        theme.primary
     } else if app.move_mode {
         spans.push(Span::styled(
             " 移动到: ",
@@ -315,7 +946,15 @@ pub fn render_help(app: &App, frame: &mut Frame, area: Rect) {
         add_bind(&mut spans, "↑↓", "切换分组");
         add_bind(&mut spans, "Enter", "确认");
         add_bind(&mut spans, "Esc", "取消");
-        theme::COLOR_NEON_CYAN
+        theme.primary
+    } else if app.snapshot_input_mode {
+        spans.push(Span::styled(
+            format!(" 历史保存到分组: {} ", app.input_buffer),
+            Style::default().fg(Color::Yellow),
+        ));
+        add_bind(&mut spans, "Enter", "确认（留空加入当前分组）");
+        add_bind(&mut spans, "Esc", "取消");
+        theme.primary
     } else if app.group_input_mode {
         spans.push(Span::styled(
             format!(" 新建分组: {} ", app.input_buffer),
@@ -323,7 +962,7 @@ pub fn render_help(app: &App, frame: &mut Frame, area: Rect) {
         ));
         add_bind(&mut spans, "Enter", "确认");
         add_bind(&mut spans, "Esc", "取消");
-        theme::COLOR_NEON_CYAN
+        theme.primary
     } else if app.input_mode {
         let history_hint = if app.search_history.is_empty() {
             String::new()
@@ -335,33 +974,103 @@ pub fn render_help(app: &App, frame: &mut Frame, area: Rect) {
             Style::default().fg(Color::Yellow),
         ));
         add_bind(&mut spans, "Enter", "搜索");
+        if !app.search_suggestions().is_empty() {
+            add_bind(&mut spans, "Tab", "补全");
+        }
         if !app.search_history.is_empty() {
             add_bind(&mut spans, "↑↓", &format!("历史{}", history_hint));
         }
         add_bind(&mut spans, "Esc", "取消");
-        theme::COLOR_NEON_CYAN
+        theme.primary
+    } else if app.filter_input_mode {
+        spans.push(Span::styled(
+            format!(" 过滤收藏: {} ", app.favorite_filter),
+            Style::default().fg(Color::Yellow),
+        ));
+        add_bind(&mut spans, "↑↓", "选择");
+        add_bind(&mut spans, "Enter", "完成");
+        add_bind(&mut spans, "Esc", "清除过滤");
+        theme.primary
+    } else if app.history_mode {
+        if matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused) {
+            add_bind(&mut spans, "Space", "暂停/继续");
+        }
+        add_bind(&mut spans, "↑↓", "选择");
+        add_bind(&mut spans, "Enter", "重新播放");
+        add_bind(&mut spans, "f", "加入收藏");
+        add_bind(&mut spans, "F", "全部存为分组");
+        add_bind(&mut spans, "h/Esc", "返回收藏");
+        add_bind(&mut spans, "q", "退出");
+        theme.primary
     } else if !app.search_results.is_empty() {
         if matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused) {
             add_bind(&mut spans, "Space", "暂停/继续");
         }
         add_bind(&mut spans, "↑↓", "选择");
         add_bind(&mut spans, "←→", "翻页");
+        add_bind(&mut spans, "G", "跳页");
         add_bind(&mut spans, "Enter", "播放");
+        add_bind(&mut spans, "v", "试听");
         add_bind(&mut spans, "f", "收藏");
         add_bind(&mut spans, "F", "全部收藏");
-        add_bind(&mut spans, "Esc", "返回");
+        add_bind(&mut spans, "r", "刷新");
+        if app.is_loading_page || matches!(app.status, PlayerStatus::Searching) {
+            add_bind(&mut spans, "Esc", "取消加载");
+        } else {
+            add_bind(&mut spans, "Esc", "返回");
+        }
         add_bind(&mut spans, "q", "退出");
-        theme::COLOR_NEON_CYAN
+        theme.primary
     } else {
         if matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused) {
             add_bind(&mut spans, "Space", "暂停/继续");
-            add_bind(&mut spans, "←→", "快退/快进");
-            add_bind(&mut spans, "+/-", "音量");
+            add_bind(
+                &mut spans,
+                "←→",
+                &format!("快退/快进 ±{}s", app.seek_seconds.abs()),
+            );
+            add_bind(&mut spans, "t", "跳转");
+            add_bind(
+                &mut spans,
+                "+/-",
+                &format!("音量 ±{}%", app.volume_step.abs()),
+            );
+            add_bind(&mut spans, "x", if app.muted { "取消静音" } else { "静音" });
+            add_bind(&mut spans, "w", "保存");
+            add_bind(&mut spans, "y", "复制链接");
+            add_bind(&mut spans, "p/N", "上一首/下一首");
         }
+        add_bind(&mut spans, "a", "播放全部");
         add_bind(&mut spans, "s", "搜索");
+        if !matches!(app.status, PlayerStatus::Playing | PlayerStatus::Paused) {
+            add_bind(&mut spans, "r", "重复搜索");
+        }
+        if matches!(app.status, PlayerStatus::Searching) {
+            add_bind(&mut spans, "Esc", "取消搜索");
+        } else if app.favorite_filter.is_empty() {
+            add_bind(&mut spans, "/", "过滤");
+        } else {
+            add_bind(&mut spans, "Esc", "清除过滤");
+        }
+        add_bind(&mut spans, "o", "排序");
+        add_bind(&mut spans, "h", "播放历史");
+        if app.status.is_error() && app.last_error_detail.is_some() {
+            add_bind(&mut spans, "i", "错误详情");
+        }
+        if app.status.is_error() {
+            add_bind(
+                &mut spans,
+                "L",
+                if app.show_logs {
+                    "隐藏日志"
+                } else {
+                    "显示日志"
+                },
+            );
+        }
         add_bind(&mut spans, "q", "退出");
         add_bind(&mut spans, "?", "操作帮助");
-        theme::COLOR_NEON_CYAN
+        theme.primary
     };
 
     let help = Paragraph::new(Line::from(spans))
@@ -374,8 +1083,73 @@ pub fn render_help(app: &App, frame: &mut Frame, area: Rect) {
     frame.render_widget(help, area);
 }
 
+/// 播放中按 q 时的退出确认浮层
+pub fn render_quit_confirm_overlay(app: &App, frame: &mut Frame, theme: &Theme) {
+    if !app.quit_confirm_mode {
+        return;
+    }
+    let height = 3u16.min(frame.size().height);
+    let width = 36u16.min(frame.size().width);
+    let x = (frame.size().width.saturating_sub(width)) / 2;
+    let y = (frame.size().height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let popup = Paragraph::new(Line::from(vec![
+        Span::styled(
+            " 正在播放，确定退出？ ",
+            Style::default()
+                .fg(theme.warning)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("[y/n]", Style::default().fg(Color::White)),
+    ]))
+    .block(
+        theme::default_block()
+            .title(" 退出 ")
+            .border_style(Style::default().fg(theme.warning)),
+    );
+    frame.render_widget(popup, popup_area);
+}
+
+/// 搜索输入时的历史建议，贴在底部输入栏（`anchor`）上方；第一条为 Tab 补全的内容
+pub fn render_search_suggestions(app: &App, frame: &mut Frame, anchor: Rect, theme: &Theme) {
+    let suggestions = app.search_suggestions();
+    if suggestions.is_empty() {
+        return;
+    }
+    let height = (suggestions.len() as u16 + 2).min(anchor.y);
+    let width = 40u16.min(anchor.width);
+    let popup_area = Rect::new(anchor.x, anchor.y - height, width, height);
+    frame.render_widget(Clear, popup_area);
+
+    let items: Vec<ListItem> = suggestions
+        .iter()
+        .enumerate()
+        .map(|(i, keyword)| {
+            let text = truncate_text(keyword, width.saturating_sub(4) as usize);
+            if i == 0 {
+                ListItem::new(format!("› {}", text)).style(
+                    Style::default()
+                        .fg(theme.primary)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                ListItem::new(format!("  {}", text)).style(Style::default().fg(Color::Gray))
+            }
+        })
+        .collect();
+    let popup = List::new(items).block(
+        theme::default_block()
+            .title(" 搜索历史 ")
+            .border_style(Style::default().fg(theme.primary)),
+    );
+    frame.render_widget(popup, popup_area);
+}
+
 /// 移动模式下的分组选择浮层
-pub fn render_move_overlay(app: &App, frame: &mut Frame) {
+pub fn render_move_overlay(app: &App, frame: &mut Frame, theme: &Theme) {
     if !app.move_mode {
         return;
     }
@@ -392,7 +1166,7 @@ pub fn render_move_overlay(app: &App, frame: &mut Frame) {
     let item_label = app
         .active_items()
         .get(app.selected_favorite)
-        .map(|i| truncate_text(&i.title, 30))
+        .map(|i| truncate_text(i.label(), 30))
         .unwrap_or_default();
 
     let items: Vec<ListItem> = app
@@ -405,7 +1179,7 @@ pub fn render_move_overlay(app: &App, frame: &mut Frame) {
             let marker = if is_target { "›" } else { " " };
             let style = if is_target {
                 Style::default()
-                    .fg(COLOR_NEON_PINK)
+                    .fg(theme.secondary)
                     .add_modifier(Modifier::BOLD)
             } else {
                 Style::default()
@@ -418,42 +1192,266 @@ pub fn render_move_overlay(app: &App, frame: &mut Frame) {
         Block::default()
             .title(format!("移动「{}」到", item_label))
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(COLOR_NEON_PINK)),
+            .border_style(Style::default().fg(theme.secondary)),
     );
     frame.render_widget(popup, popup_area);
 }
 
-pub fn render_help_overlay(app: &App, frame: &mut Frame) {
-    if !app.help_mode {
+/// 收听统计浮层：累计收听时长、播放曲目数和最常播放的收藏
+pub fn render_stats_overlay(app: &App, frame: &mut Frame, theme: &Theme) {
+    if !app.stats_mode {
         return;
     }
+    const TOP_FAVORITES: usize = 10;
+    let top = App::top_played(&app.groups, TOP_FAVORITES);
+    let label_style = Style::default().fg(theme.inactive);
+    let value_style = Style::default()
+        .fg(theme.primary)
+        .add_modifier(Modifier::BOLD);
 
-    let help_text = vec![
-        Line::from(Span::styled("【全局操作】", Style::default().fg(theme::COLOR_NEON_PINK).add_modifier(Modifier::BOLD))),
-        Line::from(" [q] 退出程序        [s] 搜索网络歌曲        [?] 打开/关闭帮助        [m] 切换播放模式"),
-        Line::from(""),
-        Line::from(Span::styled("【播放控制】", Style::default().fg(theme::COLOR_NEON_PINK).add_modifier(Modifier::BOLD))),
-        Line::from(" [Space] 暂停/继续   [Enter] 播放选定歌曲    [←/→] 快退/快进      [+/-] 调节音量"),
-        Line::from(""),
-        Line::from(Span::styled("【列表 & 分组】", Style::default().fg(theme::COLOR_NEON_PINK).add_modifier(Modifier::BOLD))),
-        Line::from(" [↑/↓] 上下移动      [Tab/Shift+Tab] 切换上下分组"),
-        Line::from(" [g] 新建分组        [R] 重命名当前分组      [D] 删除当前分组"),
-        Line::from(" [M] 移动当前歌曲    [f] 收藏/取消收藏       [F] 收藏搜索列表所有歌曲"),
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(" 累计收听  ", label_style),
+            Span::styled(
+                crate::stats::format_listening_time(app.stats.total_secs),
+                value_style,
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled(" 播放曲目  ", label_style),
+            Span::styled(format!("{} 首", app.stats.tracks_played), value_style),
+        ]),
         Line::from(""),
     ];
+    if top.is_empty() {
+        lines.push(Line::from(Span::styled(" 收藏还没有播放记录", label_style)));
+    } else {
+        lines.push(Line::from(Span::styled(" 最常播放的收藏", label_style)));
+        for (i, item) in top.iter().enumerate() {
+            lines.push(Line::from(vec![
+                Span::raw(format!(
+                    " {:>2}. {} ",
+                    i + 1,
+                    truncate_text(item.label(), 36)
+                )),
+                Span::styled(
+                    format!("×{}", item.play_count),
+                    Style::default().fg(theme.secondary),
+                ),
+            ]));
+        }
+    }
 
-    let height = (help_text.len() as u16 + 2).min(frame.size().height);
-    let width = 86u16.min(frame.size().width);
+    let height = (lines.len() as u16 + 2).min(frame.size().height);
+    let width = 52u16.min(frame.size().width);
     let x = (frame.size().width.saturating_sub(width)) / 2;
     let y = (frame.size().height.saturating_sub(height)) / 2;
     let popup_area = Rect::new(x, y, width, height);
-
     frame.render_widget(Clear, popup_area);
-
-    let popup = Paragraph::new(help_text).block(
+    let popup = Paragraph::new(lines).block(
         theme::default_block()
-            .title(" 全部快捷键说明 ")
-            .border_style(Style::default().fg(theme::COLOR_NEON_CYAN)),
+            .title(" 收听统计 ")
+            .title_bottom(" T/Esc 关闭 ")
+            .border_style(Style::default().fg(theme.primary)),
     );
     frame.render_widget(popup, popup_area);
 }
+
+/// 帮助浮层的内容：按使用场景分组的（按键, 说明）列表
+fn help_sections(app: &App) -> Vec<(&'static str, Vec<(&'static str, String)>)> {
+    let entries = |items: &[(&'static str, &str)]| -> Vec<(&'static str, String)> {
+        items
+            .iter()
+            .map(|(key, desc)| (*key, desc.to_string()))
+            .collect()
+    };
+    let mut playing = vec![
+        ("Space", "暂停/继续".to_string()),
+        (
+            "←/→",
+            format!("快退/快进 ±{}s（搜索结果中为翻页）", app.seek_seconds.abs()),
+        ),
+        ("+/-", format!("调节音量 ±{}%", app.volume_step.abs())),
+    ];
+    playing.extend(entries(&[
+        ("[/]", "上一章/下一章（没有章节时为快退/快进）"),
+        ("t", "跳转到时间点（1:30、1:02:03 或 90）"),
+        ("0/Home", "从头播放当前歌曲"),
+        ("V", "输入精确音量 (0–130)"),
+        ("x", "静音/取消静音"),
+        ("E", "切换均衡器预设"),
+        ("S", "把当前位置标记为这首收藏的起始位置（再按清除）"),
+        ("u", "重新解析当前歌曲（URL 过期时使用）"),
+        ("p/N", "上一首（按实际播放顺序）/ 下一首"),
+        ("f", "收藏/取消收藏当前歌曲"),
+        ("w", "保存当前歌曲到下载目录"),
+        ("y", "复制当前歌曲链接到剪贴板（需 clipboard feature）"),
+    ]));
+    vec![
+        (
+            "全局",
+            entries(&[
+                ("q", "退出程序"),
+                ("?", "打开/关闭本帮助"),
+                ("s", "搜索网络歌曲"),
+                ("r", "用上一次的关键词重新搜索"),
+                ("m", "切换播放模式"),
+                ("h", "播放历史"),
+                ("n", "全屏“正在播放”视图（n/Esc 返回）"),
+                ("C", "重新加载配置"),
+                (",", "在 $EDITOR 中编辑配置，返回后自动重新加载"),
+                ("L", "显示/隐藏日志面板"),
+                ("i", "出错时展开/收起错误详情"),
+                ("B", "编辑搜索排除关键词"),
+            ]),
+        ),
+        ("播放中", playing),
+        (
+            "搜索结果",
+            entries(&[
+                ("↑/↓", "选择"),
+                ("Enter", "播放选中的歌曲"),
+                ("v", "试听选中的歌曲（一半音量，几秒后自动停止）"),
+                ("←/→", "上一页/下一页"),
+                ("G", "输入页码直接跳页"),
+                ("f", "收藏选中的歌曲"),
+                ("F", "全部收藏到当前分组"),
+                ("Esc", "取消加载 / 返回收藏"),
+            ]),
+        ),
+        (
+            "收藏 & 分组",
+            entries(&[
+                ("↑/↓", "上下移动"),
+                ("Enter", "播放选中的歌曲"),
+                ("a", "从选中的收藏开始播放全部（也可 Shift+Enter）"),
+                ("Tab/S-Tab", "切换上下分组"),
+                ("Shift+↑/↓", "调整收藏顺序"),
+                ("/", "过滤收藏列表（Esc 清除）"),
+                ("o / O", "切换排序 / 按当前排序保存"),
+                ("g", "新建分组"),
+                ("R", "重命名当前分组"),
+                ("D", "删除当前分组"),
+                ("X", "清空全部收藏"),
+                ("M", "移动选中的歌曲到其他分组"),
+                ("f", "移除选中的收藏"),
+                ("e", "设置显示名称（留空恢复）"),
+                ("T", "收听统计（累计时长、最常播放的收藏）"),
+            ]),
+        ),
+        (
+            "播放历史",
+            entries(&[
+                ("↑/↓", "选择"),
+                ("Enter", "重新播放"),
+                ("f", "加入收藏"),
+                (
+                    "F",
+                    "把整个播放历史加入收藏（输入分组名，留空加入当前分组）",
+                ),
+                ("h/Esc", "返回"),
+            ]),
+        ),
+        (
+            "输入框",
+            entries(&[
+                ("Enter", "确认"),
+                ("Esc", "取消"),
+                ("Backspace", "删除一个字符"),
+                ("↑/↓", "浏览搜索历史（搜索框）"),
+                ("Tab", "补全为第一条历史建议（搜索框）"),
+                ("粘贴", "可直接粘贴关键词或歌单链接"),
+            ]),
+        ),
+    ]
+}
+
+/// 帮助浮层中一个分组的文本行
+fn help_section_lines(
+    title: &str,
+    items: &[(&'static str, String)],
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(Span::styled(
+        format!("【{}】", title),
+        Style::default()
+            .fg(theme.secondary)
+            .add_modifier(Modifier::BOLD),
+    ))];
+    for (key, desc) in items {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(" {:<12}", format!("[{}]", key)),
+                Style::default().fg(theme.primary),
+            ),
+            Span::raw(desc.clone()),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines
+}
+
+/// 全屏快捷键帮助：背景变暗，按场景分组列出所有按键；宽度足够时分两栏，内容超出时可滚动。
+/// 只是浮层，不影响播放
+pub fn render_help_overlay(app: &mut App, frame: &mut Frame, theme: &Theme) {
+    if !app.help_mode {
+        return;
+    }
+
+    let size = frame.size();
+    let buf = frame.buffer_mut();
+    for cell in buf.content.iter_mut() {
+        cell.set_style(Style::default().add_modifier(Modifier::DIM));
+    }
+
+    let popup_area = Rect::new(
+        size.x + 2.min(size.width / 10),
+        size.y + 1.min(size.height / 10),
+        size.width.saturating_sub(2 * 2.min(size.width / 10)),
+        size.height.saturating_sub(2 * 1.min(size.height / 10)),
+    );
+    frame.render_widget(Clear, popup_area);
+    let block = theme::default_block()
+        .title(" 全部快捷键说明 ")
+        .title_bottom(" ↑/↓ 滚动 · ?/Esc 关闭 ")
+        .border_style(Style::default().fg(theme.primary));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // 宽度够放两栏时，按行数把分组大致平分到左右两栏
+    const COLUMN_WIDTH: u16 = 52;
+    let sections: Vec<Vec<Line>> = help_sections(app)
+        .iter()
+        .map(|(title, items)| help_section_lines(title, items, theme))
+        .collect();
+    let total: usize = sections.iter().map(Vec::len).sum();
+    let mut columns: Vec<Vec<Line>> = if inner.width >= COLUMN_WIDTH * 2 {
+        vec![Vec::new(), Vec::new()]
+    } else {
+        vec![Vec::new()]
+    };
+    let column_count = columns.len();
+    for section in sections {
+        let col = if column_count == 2 && columns[0].len() * 2 >= total {
+            1
+        } else {
+            0
+        };
+        columns[col].extend(section);
+    }
+
+    let longest = columns.iter().map(Vec::len).max().unwrap_or(0) as u16;
+    let max_scroll = longest.saturating_sub(inner.height);
+    app.help_scroll = app.help_scroll.min(max_scroll);
+
+    let areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Ratio(1, column_count as u32);
+            column_count
+        ])
+        .split(inner);
+    for (lines, area) in columns.into_iter().zip(areas.iter()) {
+        frame.render_widget(Paragraph::new(lines).scroll((app.help_scroll, 0)), *area);
+    }
+}